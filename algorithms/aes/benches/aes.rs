@@ -0,0 +1,80 @@
+use std::convert::TryInto;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use aes::bitslice::{BitslicedAes, BLOCKS};
+use aes::gcm::Gcm;
+use aes::ttable::TTableAes;
+use aes::{parse_key, Aes, Backend};
+
+const SIZES: [usize; 2] = [1 << 10, 1 << 16];
+
+const KEYS: [&str; 3] = [
+    "000102030405060708090a0b0c0d0e0f",
+    "000102030405060708090a0b0c0d0e0f1011121314151617",
+    "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+];
+
+const BLOCK: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+
+fn bench_block(c: &mut Criterion) {
+    let mut group = c.benchmark_group("block");
+    group.throughput(Throughput::Bytes(16));
+    for (bits, key) in [128, 192, 256].iter().zip(KEYS.iter()) {
+        let key = parse_key(key).unwrap();
+        let aes = Aes::new(&key);
+        group.bench_function(BenchmarkId::new("reference", bits), |b| b.iter(|| aes.encrypt_block(black_box(BLOCK))));
+        let aes = TTableAes::new(&key);
+        group.bench_function(BenchmarkId::new("ttable", bits), |b| b.iter(|| aes.encrypt_block(black_box(BLOCK))));
+        group.bench_function(BenchmarkId::new("ttable-decrypt", bits), |b| b.iter(|| aes.decrypt_block(black_box(BLOCK))));
+        let aes = BitslicedAes::new(&key);
+        group.throughput(Throughput::Bytes(16 * BLOCKS as u64));
+        group.bench_function(BenchmarkId::new("bitsliced-4", bits), |b| b.iter(|| aes.encrypt_blocks(black_box(&[BLOCK; BLOCKS]))));
+        group.bench_function(BenchmarkId::new("bitsliced-4-decrypt", bits), |b| b.iter(|| aes.decrypt_blocks(black_box(&[BLOCK; BLOCKS]))));
+        group.throughput(Throughput::Bytes(16));
+        #[cfg(target_arch = "x86_64")]
+        if let Some(aes) = aes::aesni::AesNi::new(&key) {
+            group.bench_function(BenchmarkId::new("aesni", bits), |b| b.iter(|| aes.encrypt_block(black_box(BLOCK))));
+            group.throughput(Throughput::Bytes(16 * aes::aesni::PARALLEL as u64));
+            group.bench_function(BenchmarkId::new("aesni-8", bits), |b| b.iter(|| aes.encrypt_blocks(&mut black_box([BLOCK; aes::aesni::PARALLEL]))));
+            group.throughput(Throughput::Bytes(16));
+        }
+    }
+    group.finish();
+}
+
+fn bench_ecb(c: &mut Criterion) {
+    let key = parse_key(KEYS[0]).unwrap();
+    let mut group = c.benchmark_group("ecb");
+    for &size in SIZES.iter() {
+        let input: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        group.throughput(Throughput::Bytes(size as u64));
+        if size <= 1 << 10 {
+            let aes = Aes::new(&key);
+            group.bench_with_input(BenchmarkId::new("reference", size), &input, |b, input| b.iter(|| {
+                input.chunks(16).map(|chunk| aes.encrypt_block(chunk.try_into().unwrap())).collect::<Vec<_>>()
+            }));
+        }
+        for backend in [Backend::TTable, Backend::Bitsliced, Backend::AesNi].iter().copied().filter(Backend::is_available) {
+            group.bench_with_input(BenchmarkId::new(backend.name(), size), &input, |b, input| b.iter(|| aes::encrypt_with(input, &key, backend)));
+        }
+    }
+    group.finish();
+}
+
+fn bench_gcm(c: &mut Criterion) {
+    let key = parse_key(KEYS[0]).unwrap();
+    let mut group = c.benchmark_group("gcm");
+    for &size in SIZES.iter() {
+        let input: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        group.throughput(Throughput::Bytes(size as u64));
+        for backend in [Backend::Bitsliced, Backend::AesNi].iter().copied().filter(Backend::is_available) {
+            let gcm = Gcm::with_backend(&key, backend);
+            group.bench_with_input(BenchmarkId::new(backend.name(), size), &input, |b, input| b.iter(|| gcm.encrypt(&[0; 12], &[], input)));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_block, bench_ecb, bench_gcm);
+criterion_main!(benches);
@@ -0,0 +1,190 @@
+//! AES with the AES-NI instructions of x86-64 processors.
+//!
+//! A round is one `aesenc` (or `aesdec`, with round keys run through `aesimc` for the equivalent
+//! inverse cipher) and runs in constant time in hardware. The key schedule gets SubWord and RotWord
+//! from `aeskeygenassist`, one word at a time, so the three key sizes share the loop of FIPS-197
+//! section 5.2. Eight blocks are kept in flight to hide the latency of the instruction.
+//!
+//! The instructions are only used after [`detected`] found them at run time.
+
+use std::arch::x86_64::*;
+use std::convert::TryInto;
+
+use crate::Key;
+
+#[cfg(test)]
+mod test;
+
+/// Blocks encrypted in parallel by [`AesNi::encrypt_blocks`].
+pub const PARALLEL: usize = 8;
+
+/// Whether the processor has the AES instructions.
+pub fn detected() -> bool {
+    is_x86_feature_detected!("aes") && is_x86_feature_detected!("sse2")
+}
+
+/// Whether the processor has carry-less multiplication, which GHASH uses.
+pub fn pclmulqdq_detected() -> bool {
+    is_x86_feature_detected!("pclmulqdq") && is_x86_feature_detected!("sse2")
+}
+
+/// `aeskeygenassist` on the word `x` placed in the second column: the first word of the result
+/// is SubWord(`x`) and the second SubWord(RotWord(`x`)) ⊕ Rcon. Words are in memory byte order.
+#[target_feature(enable = "aes,sse2")]
+unsafe fn key_assist(x: u32, rcon: u8) -> (u32, u32) {
+    let x = _mm_set_epi32(0, 0, x as i32, 0);
+    let result = match rcon {
+        0x01 => _mm_aeskeygenassist_si128::<0x01>(x),
+        0x02 => _mm_aeskeygenassist_si128::<0x02>(x),
+        0x04 => _mm_aeskeygenassist_si128::<0x04>(x),
+        0x08 => _mm_aeskeygenassist_si128::<0x08>(x),
+        0x10 => _mm_aeskeygenassist_si128::<0x10>(x),
+        0x20 => _mm_aeskeygenassist_si128::<0x20>(x),
+        0x40 => _mm_aeskeygenassist_si128::<0x40>(x),
+        0x80 => _mm_aeskeygenassist_si128::<0x80>(x),
+        0x1b => _mm_aeskeygenassist_si128::<0x1b>(x),
+        0x36 => _mm_aeskeygenassist_si128::<0x36>(x),
+        _ => _mm_aeskeygenassist_si128::<0x00>(x),
+    };
+    (_mm_cvtsi128_si32(result) as u32, _mm_cvtsi128_si32(_mm_srli_si128::<4>(result)) as u32)
+}
+
+/// KeyExpansion of FIPS-197 section 5.2 with SubWord and RotWord from `aeskeygenassist`.
+#[target_feature(enable = "aes,sse2")]
+unsafe fn expand_key(key: &[u8]) -> Vec<__m128i> {
+    let nk = key.len() / 4;
+    let rounds = nk + 6;
+    let mut words: Vec<u32> = key.chunks(4).map(|word| u32::from_le_bytes(word.try_into().unwrap())).collect();
+    let mut rcon = 1u8;
+    for i in nk..4 * (rounds + 1) {
+        let mut temp = words[i - 1];
+        match i % nk {
+            0 => {
+                temp = key_assist(temp, rcon).1;
+                rcon = (rcon << 1) ^ ((rcon >> 7) * 0x1b);
+            }
+            4 if nk > 6 => temp = key_assist(temp, 0).0,
+            _ => {}
+        }
+        words.push(words[i - nk] ^ temp);
+    }
+    words.chunks(4).map(|w| _mm_set_epi32(w[3] as i32, w[2] as i32, w[1] as i32, w[0] as i32)).collect()
+}
+
+/// The round keys of the equivalent inverse cipher, in the order decryption uses them.
+#[target_feature(enable = "aes,sse2")]
+unsafe fn decryption_keys(encryption: &[__m128i]) -> Vec<__m128i> {
+    let last = encryption.len() - 1;
+    encryption.iter().rev().enumerate().map(|(i, &k)| if i == 0 || i == last { k } else { _mm_aesimc_si128(k) }).collect()
+}
+
+#[target_feature(enable = "aes,sse2")]
+unsafe fn aesenc_blocks<const N: usize>(keys: &[__m128i], blocks: &mut [[u8; 16]; N]) {
+    let (first, rest) = keys.split_first().unwrap();
+    let (last, middle) = rest.split_last().unwrap();
+    let mut s = blocks.map(|block| _mm_xor_si128(_mm_loadu_si128(block.as_ptr() as *const __m128i), *first));
+    for &k in middle {
+        for s in s.iter_mut() {
+            *s = _mm_aesenc_si128(*s, k);
+        }
+    }
+    for (block, s) in blocks.iter_mut().zip(s.iter()) {
+        _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, _mm_aesenclast_si128(*s, *last));
+    }
+}
+
+#[target_feature(enable = "aes,sse2")]
+unsafe fn aesdec_blocks<const N: usize>(keys: &[__m128i], blocks: &mut [[u8; 16]; N]) {
+    let (first, rest) = keys.split_first().unwrap();
+    let (last, middle) = rest.split_last().unwrap();
+    let mut s = blocks.map(|block| _mm_xor_si128(_mm_loadu_si128(block.as_ptr() as *const __m128i), *first));
+    for &k in middle {
+        for s in s.iter_mut() {
+            *s = _mm_aesdec_si128(*s, k);
+        }
+    }
+    for (block, s) in blocks.iter_mut().zip(s.iter()) {
+        _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, _mm_aesdeclast_si128(*s, *last));
+    }
+}
+
+/// AES with the round keys of one key computed up front. Only built when [`detected`].
+#[derive(Debug, Clone)]
+pub struct AesNi {
+    encryption: Vec<__m128i>,
+    decryption: Vec<__m128i>,
+}
+
+impl AesNi {
+    /// `None` when the processor lacks the AES instructions.
+    pub fn new(key: &Key) -> Option<AesNi> {
+        if !detected() {
+            return None;
+        }
+        // Safe: the instructions were detected above.
+        unsafe {
+            let encryption = expand_key(key.as_slice());
+            let decryption = decryption_keys(&encryption);
+            Some(AesNi { encryption, decryption })
+        }
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let mut blocks = [block];
+        // Safe: an `AesNi` is only built after detection.
+        unsafe { aesenc_blocks(&self.encryption, &mut blocks) };
+        blocks[0]
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let mut blocks = [block];
+        unsafe { aesdec_blocks(&self.decryption, &mut blocks) };
+        blocks[0]
+    }
+
+    pub fn encrypt_blocks(&self, blocks: &mut [[u8; 16]; PARALLEL]) {
+        unsafe { aesenc_blocks(&self.encryption, blocks) };
+    }
+
+    pub fn decrypt_blocks(&self, blocks: &mut [[u8; 16]; PARALLEL]) {
+        unsafe { aesdec_blocks(&self.decryption, blocks) };
+    }
+
+    /// The round keys, first round first, for comparing with the software key schedule.
+    pub fn round_keys(&self) -> Vec<[u8; 16]> {
+        self.encryption.iter().map(|&k| {
+            let mut bytes = [0; 16];
+            unsafe { _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, k) };
+            bytes
+        }).collect()
+    }
+}
+
+/// Runs `crypt` on the 16-byte blocks of `input`, eight at a time. A trailing partial block is
+/// padded with zero bytes.
+fn batches(input: &[u8], mut crypt: impl FnMut(&mut [[u8; 16]; PARALLEL])) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len().div_ceil(16) * 16);
+    for batch in input.chunks(16 * PARALLEL) {
+        let mut blocks = [[0; 16]; PARALLEL];
+        for (block, chunk) in blocks.iter_mut().zip(batch.chunks(16)) {
+            block[..chunk.len()].copy_from_slice(chunk);
+        }
+        crypt(&mut blocks);
+        for block in blocks[..batch.len().div_ceil(16)].iter() {
+            output.extend_from_slice(block);
+        }
+    }
+    output
+}
+
+/// Encrypts `input` in ECB mode like [`crate::encrypt`], or `None` without AES-NI.
+pub fn encrypt(input: &[u8], key: &Key) -> Option<Vec<u8>> {
+    let aes = AesNi::new(key)?;
+    Some(batches(input, |blocks| aes.encrypt_blocks(blocks)))
+}
+
+/// Decrypts `input` in ECB mode like [`crate::decrypt`], or `None` without AES-NI.
+pub fn decrypt(input: &[u8], key: &Key) -> Option<Vec<u8>> {
+    let aes = AesNi::new(key)?;
+    Some(batches(input, |blocks| aes.decrypt_blocks(blocks)))
+}
@@ -0,0 +1,81 @@
+use rand::{thread_rng, Rng};
+
+use crate::ttable::TTableAes;
+use crate::{generate_sub_key, keygen_128, keygen_192, keygen_256, parse_key};
+
+use super::*;
+
+/// The tests pass vacuously on processors without AES-NI.
+fn available() -> bool {
+    if !detected() {
+        eprintln!("AES-NI not detected, skipping");
+    }
+    detected()
+}
+
+#[test]
+fn test_key_schedule() {
+    if !available() {
+        return;
+    }
+    for key in [keygen_128(), keygen_192(), keygen_256()] {
+        let expected: Vec<[u8; 16]> = generate_sub_key(&key).words().iter().map(|words| {
+            let mut bytes = [0; 16];
+            for (chunk, word) in bytes.chunks_mut(4).zip(words.iter()) {
+                chunk.copy_from_slice(&word.to_be_bytes());
+            }
+            bytes
+        }).collect();
+        assert_eq!(AesNi::new(&key).unwrap().round_keys(), expected);
+    }
+}
+
+#[test]
+fn test_known_answer() {
+    if !available() {
+        return;
+    }
+    let plaintext = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+    let aes = AesNi::new(&parse_key("000102030405060708090a0b0c0d0e0f1011121314151617").unwrap()).unwrap();
+    let ciphertext = [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91];
+    assert_eq!(aes.encrypt_block(plaintext), ciphertext);
+    assert_eq!(aes.decrypt_block(ciphertext), plaintext);
+}
+
+#[test]
+fn test_cross_check() {
+    if !available() {
+        return;
+    }
+    let mut rng = thread_rng();
+    for _ in 0..50 {
+        for key in [keygen_128(), keygen_192(), keygen_256()] {
+            let blocks: [[u8; 16]; PARALLEL] = rng.gen();
+            let aes = AesNi::new(&key).unwrap();
+            let software = TTableAes::new(&key);
+            let mut encrypted = blocks;
+            aes.encrypt_blocks(&mut encrypted);
+            assert_eq!(encrypted, blocks.map(|block| software.encrypt_block(block)));
+            let mut decrypted = blocks;
+            aes.decrypt_blocks(&mut decrypted);
+            assert_eq!(decrypted, blocks.map(|block| software.decrypt_block(block)));
+            assert_eq!(aes.encrypt_block(blocks[0]), encrypted[0]);
+            assert_eq!(aes.decrypt_block(blocks[0]), decrypted[0]);
+        }
+    }
+}
+
+#[test]
+fn test_ecb() {
+    if !available() {
+        return;
+    }
+    let mut rng = thread_rng();
+    let key = keygen_256();
+    for length in [0, 1, 16, 127, 128, 129, 300] {
+        let input: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+        let encrypted = encrypt(&input, &key).unwrap();
+        assert_eq!(encrypted, crate::bitslice::encrypt(&input, &key));
+        assert_eq!(decrypt(&encrypted, &key).unwrap(), crate::bitslice::decrypt(&encrypted, &key));
+    }
+}
@@ -0,0 +1,400 @@
+//! Constant-time bitsliced AES, four blocks at a time.
+//!
+//! The 64 bytes of four blocks are spread over eight words: word `b` holds bit `b` of every byte,
+//! byte `k` of block `j` in bit `16 * j + k`. SubBytes is then the S-box circuit of Boyar and
+//! Peralta, 32 AND and 83 XOR/XNOR gates on the eight words, and ShiftRows and MixColumns move bits
+//! within the words with shifts and masks. Nothing depends on the data but the values: there are no
+//! table lookups indexed by it and no branches on it. The key schedule runs its SubWord through
+//! the same circuit.
+
+use std::convert::TryInto;
+
+use crate::{expand_key, Key};
+
+#[cfg(test)]
+mod test;
+
+/// Blocks processed at once.
+pub const BLOCKS: usize = 4;
+
+type State = [u64; 8];
+
+/// The S-box of Boyar and Peralta, "A depth-16 circuit for the AES S-box" (2011), on bit planes:
+/// `x[b]` holds bit `b` of the inputs.
+fn sbox(x: &mut State) {
+    let [x7, x6, x5, x4, x3, x2, x1, x0] = *x;
+
+    // Top linear transformation.
+    let y14 = x3 ^ x5;
+    let y13 = x0 ^ x6;
+    let y9 = x0 ^ x3;
+    let y8 = x0 ^ x5;
+    let t0 = x1 ^ x2;
+    let y1 = t0 ^ x7;
+    let y4 = y1 ^ x3;
+    let y12 = y13 ^ y14;
+    let y2 = y1 ^ x0;
+    let y5 = y1 ^ x6;
+    let y3 = y5 ^ y8;
+    let t1 = x4 ^ y12;
+    let y15 = t1 ^ x5;
+    let y20 = t1 ^ x1;
+    let y6 = y15 ^ x7;
+    let y10 = y15 ^ t0;
+    let y11 = y20 ^ y9;
+    let y7 = x7 ^ y11;
+    let y17 = y10 ^ y11;
+    let y19 = y10 ^ y8;
+    let y16 = t0 ^ y11;
+    let y21 = y13 ^ y16;
+    let y18 = x0 ^ y16;
+
+    // Inversion in GF(2^8).
+    let t2 = y12 & y15;
+    let t3 = y3 & y6;
+    let t4 = t3 ^ t2;
+    let t5 = y4 & x7;
+    let t6 = t5 ^ t2;
+    let t7 = y13 & y16;
+    let t8 = y5 & y1;
+    let t9 = t8 ^ t7;
+    let t10 = y2 & y7;
+    let t11 = t10 ^ t7;
+    let t12 = y9 & y11;
+    let t13 = y14 & y17;
+    let t14 = t13 ^ t12;
+    let t15 = y8 & y10;
+    let t16 = t15 ^ t12;
+    let t17 = t4 ^ t14;
+    let t18 = t6 ^ t16;
+    let t19 = t9 ^ t14;
+    let t20 = t11 ^ t16;
+    let t21 = t17 ^ y20;
+    let t22 = t18 ^ y19;
+    let t23 = t19 ^ y21;
+    let t24 = t20 ^ y18;
+
+    let t25 = t21 ^ t22;
+    let t26 = t21 & t23;
+    let t27 = t24 ^ t26;
+    let t28 = t25 & t27;
+    let t29 = t28 ^ t22;
+    let t30 = t23 ^ t24;
+    let t31 = t22 ^ t26;
+    let t32 = t31 & t30;
+    let t33 = t32 ^ t24;
+    let t34 = t23 ^ t33;
+    let t35 = t27 ^ t33;
+    let t36 = t24 & t35;
+    let t37 = t36 ^ t34;
+    let t38 = t27 ^ t36;
+    let t39 = t29 & t38;
+    let t40 = t25 ^ t39;
+
+    let t41 = t40 ^ t37;
+    let t42 = t29 ^ t33;
+    let t43 = t29 ^ t40;
+    let t44 = t33 ^ t37;
+    let t45 = t42 ^ t41;
+    let z0 = t44 & y15;
+    let z1 = t37 & y6;
+    let z2 = t33 & x7;
+    let z3 = t43 & y16;
+    let z4 = t40 & y1;
+    let z5 = t29 & y7;
+    let z6 = t42 & y11;
+    let z7 = t45 & y17;
+    let z8 = t41 & y10;
+    let z9 = t44 & y12;
+    let z10 = t37 & y3;
+    let z11 = t33 & y4;
+    let z12 = t43 & y13;
+    let z13 = t40 & y5;
+    let z14 = t29 & y2;
+    let z15 = t42 & y9;
+    let z16 = t45 & y14;
+    let z17 = t41 & y8;
+
+    // Bottom linear transformation.
+    let t46 = z15 ^ z16;
+    let t47 = z10 ^ z11;
+    let t48 = z5 ^ z13;
+    let t49 = z9 ^ z10;
+    let t50 = z2 ^ z12;
+    let t51 = z2 ^ z5;
+    let t52 = z7 ^ z8;
+    let t53 = z0 ^ z3;
+    let t54 = z6 ^ z7;
+    let t55 = z16 ^ z17;
+    let t56 = z12 ^ t48;
+    let t57 = t50 ^ t53;
+    let t58 = z4 ^ t46;
+    let t59 = z3 ^ t54;
+    let t60 = t46 ^ t57;
+    let t61 = z14 ^ t57;
+    let t62 = t52 ^ t58;
+    let t63 = t49 ^ t58;
+    let t64 = z4 ^ t59;
+    let t65 = t61 ^ t62;
+    let t66 = z1 ^ t63;
+    let s0 = t59 ^ t63;
+    let s6 = t56 ^ !t62;
+    let s7 = t48 ^ !t60;
+    let t67 = t64 ^ t65;
+    let s3 = t53 ^ t66;
+    let s4 = t51 ^ t66;
+    let s5 = t47 ^ t65;
+    let s1 = t64 ^ !s3;
+    let s2 = t55 ^ !t67;
+
+    *x = [s7, s6, s5, s4, s3, s2, s1, s0];
+}
+
+/// Adds 0x63 and applies the inverse of the affine map of the S-box.
+fn inv_affine(x: &mut State) {
+    let [x0, x1, x2, x3, x4, x5, x6, x7] = *x;
+    let [x0, x1, x5, x6] = [!x0, !x1, !x5, !x6];
+    *x = [
+        x2 ^ x5 ^ x7,
+        x3 ^ x6 ^ x0,
+        x4 ^ x7 ^ x1,
+        x5 ^ x0 ^ x2,
+        x6 ^ x1 ^ x3,
+        x7 ^ x2 ^ x4,
+        x0 ^ x3 ^ x5,
+        x1 ^ x4 ^ x6,
+    ];
+}
+
+/// The inverse S-box from the circuit: S(x) = A(x⁻¹) + 0x63, so x⁻¹ = A⁻¹(S(x) + 0x63) and
+/// S⁻¹(y) = (A⁻¹(y + 0x63))⁻¹.
+fn inv_sbox(x: &mut State) {
+    inv_affine(x);
+    sbox(x);
+    inv_affine(x);
+}
+
+/// Bits of lanes `r`, `r + 4`, `r + 8` and `r + 12` of every block: row `r` of the state.
+const fn row_mask(r: u32) -> u64 {
+    0x1111111111111111 << r
+}
+
+/// Moves every lane `i` of each 16-bit group to lane `i - n` of the group, wrapping around.
+#[inline(always)]
+fn rotate_groups(x: u64, n: u32) -> u64 {
+    if n == 0 {
+        return x;
+    }
+    let low = 0xffffu64 >> n;
+    let low = low * 0x0001000100010001;
+    (x >> n & low) | (x << (16 - n) & !low)
+}
+
+/// Moves every lane `i` of each nibble to lane `i - n` of the nibble, wrapping around: within a
+/// column, row `r` receives row `r + n`.
+#[inline(always)]
+fn rotate_rows(x: u64, n: u32) -> u64 {
+    let low = (0xfu64 >> n) * 0x1111111111111111;
+    (x >> n & low) | (x << (4 - n) & !low)
+}
+
+fn shift_rows(state: &mut State) {
+    for x in state.iter_mut() {
+        *x = (0..4).fold(0, |acc, r| acc | rotate_groups(*x & row_mask(r), 4 * r));
+    }
+}
+
+fn inv_shift_rows(state: &mut State) {
+    for x in state.iter_mut() {
+        *x = (0..4).fold(0, |acc, r| acc | rotate_groups(*x & row_mask(r), (16 - 4 * r) & 15));
+    }
+}
+
+/// Multiplication of every byte by x.
+fn xtime(x: &State) -> State {
+    [x[7], x[0] ^ x[7], x[1], x[2] ^ x[7], x[3] ^ x[7], x[4], x[5], x[6]]
+}
+
+fn xor(a: &State, b: &State) -> State {
+    let mut result = *a;
+    for (r, b) in result.iter_mut().zip(b.iter()) {
+        *r ^= b;
+    }
+    result
+}
+
+fn rotated(x: &State, n: u32) -> State {
+    x.map(|x| rotate_rows(x, n))
+}
+
+/// Row `r` becomes `2 a[r] + 3 a[r + 1] + a[r + 2] + a[r + 3]`.
+fn mix_columns(state: &mut State) {
+    let a1 = rotated(state, 1);
+    let a2 = rotated(state, 2);
+    let a3 = rotated(state, 3);
+    *state = xor(&xor(&xtime(&xor(state, &a1)), &a1), &xor(&a2, &a3));
+}
+
+/// InvMixColumns is MixColumns after adding `4 (a[r] + a[r + 2])` to every row `r`.
+fn inv_mix_columns(state: &mut State) {
+    let u = xtime(&xtime(&xor(state, &rotated(state, 2))));
+    *state = xor(state, &u);
+    mix_columns(state);
+}
+
+fn add_round_key(state: &mut State, key: &State) {
+    *state = xor(state, key);
+}
+
+/// The 8x8 bit matrix transpose: bit `i` of byte `j` becomes bit `j` of byte `i`.
+#[inline(always)]
+fn transpose8(mut x: u64) -> u64 {
+    let t = (x ^ x >> 7) & 0x00aa00aa00aa00aa;
+    x ^= t ^ t << 7;
+    let t = (x ^ x >> 14) & 0x0000cccc0000cccc;
+    x ^= t ^ t << 14;
+    let t = (x ^ x >> 28) & 0x00000000f0f0f0f0;
+    x ^= t ^ t << 28;
+    x
+}
+
+/// Bit planes of 64 bytes: bit `b` of `bytes[i]` is bit `i` of word `b`.
+fn to_planes(bytes: &[u8; 64]) -> State {
+    let mut state = [0; 8];
+    for (group, chunk) in bytes.chunks(8).enumerate() {
+        let x = transpose8(u64::from_le_bytes(chunk.try_into().unwrap()));
+        for (b, plane) in state.iter_mut().enumerate() {
+            *plane |= (x >> (8 * b) & 0xff) << (8 * group);
+        }
+    }
+    state
+}
+
+/// The inverse of [`to_planes`].
+fn from_planes(state: &State) -> [u8; 64] {
+    let mut bytes = [0; 64];
+    for (group, chunk) in bytes.chunks_mut(8).enumerate() {
+        let x = state.iter().enumerate().fold(0, |acc, (b, plane)| acc | (plane >> (8 * group) & 0xff) << (8 * b));
+        chunk.copy_from_slice(&transpose8(x).to_le_bytes());
+    }
+    bytes
+}
+
+/// SubWord of the key schedule through the circuit.
+fn sub_word(word: u32) -> u32 {
+    let mut bytes = [0; 64];
+    bytes[..4].copy_from_slice(&word.to_be_bytes());
+    let mut state = to_planes(&bytes);
+    sbox(&mut state);
+    u32::from_be_bytes(from_planes(&state)[..4].try_into().unwrap())
+}
+
+/// AES on four blocks at once with the round keys of one key.
+#[derive(Debug, Clone)]
+pub struct BitslicedAes {
+    /// Every round key repeated for the four blocks, as bit planes.
+    round_keys: Vec<State>,
+}
+
+impl BitslicedAes {
+    pub fn new(key: &Key) -> BitslicedAes {
+        let round_keys = expand_key(key, sub_word).words().iter().map(|words| {
+            let mut bytes = [0; 64];
+            for block in bytes.chunks_mut(16) {
+                for (chunk, word) in block.chunks_mut(4).zip(words.iter()) {
+                    chunk.copy_from_slice(&word.to_be_bytes());
+                }
+            }
+            to_planes(&bytes)
+        }).collect();
+        BitslicedAes { round_keys }
+    }
+
+    pub fn encrypt_blocks(&self, blocks: &[[u8; 16]; BLOCKS]) -> [[u8; 16]; BLOCKS] {
+        let (first, rest) = self.round_keys.split_first().unwrap();
+        let (last, middle) = rest.split_last().unwrap();
+        let mut state = to_planes(&join(blocks));
+        add_round_key(&mut state, first);
+        for key in middle {
+            sbox(&mut state);
+            shift_rows(&mut state);
+            mix_columns(&mut state);
+            add_round_key(&mut state, key);
+        }
+        sbox(&mut state);
+        shift_rows(&mut state);
+        add_round_key(&mut state, last);
+        split(&from_planes(&state))
+    }
+
+    pub fn decrypt_blocks(&self, blocks: &[[u8; 16]; BLOCKS]) -> [[u8; 16]; BLOCKS] {
+        let (last, rest) = self.round_keys.split_last().unwrap();
+        let (first, middle) = rest.split_first().unwrap();
+        let mut state = to_planes(&join(blocks));
+        add_round_key(&mut state, last);
+        for key in middle.iter().rev() {
+            inv_shift_rows(&mut state);
+            inv_sbox(&mut state);
+            add_round_key(&mut state, key);
+            inv_mix_columns(&mut state);
+        }
+        inv_shift_rows(&mut state);
+        inv_sbox(&mut state);
+        add_round_key(&mut state, first);
+        split(&from_planes(&state))
+    }
+
+    /// Encrypts one block, at the cost of four.
+    pub fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        self.encrypt_blocks(&[block; BLOCKS])[0]
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        self.decrypt_blocks(&[block; BLOCKS])[0]
+    }
+}
+
+fn join(blocks: &[[u8; 16]; BLOCKS]) -> [u8; 64] {
+    let mut bytes = [0; 64];
+    for (chunk, block) in bytes.chunks_mut(16).zip(blocks.iter()) {
+        chunk.copy_from_slice(block);
+    }
+    bytes
+}
+
+fn split(bytes: &[u8; 64]) -> [[u8; 16]; BLOCKS] {
+    let mut blocks = [[0; 16]; BLOCKS];
+    for (block, chunk) in blocks.iter_mut().zip(bytes.chunks(16)) {
+        block.copy_from_slice(chunk);
+    }
+    blocks
+}
+
+/// Runs `crypt` on the 16-byte blocks of `input`, four at a time. A trailing partial block is
+/// padded with zero bytes.
+fn batches(input: &[u8], mut crypt: impl FnMut(&[[u8; 16]; BLOCKS]) -> [[u8; 16]; BLOCKS]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len().div_ceil(16) * 16);
+    for batch in input.chunks(16 * BLOCKS) {
+        let mut blocks = [[0; 16]; BLOCKS];
+        for (block, chunk) in blocks.iter_mut().zip(batch.chunks(16)) {
+            block[..chunk.len()].copy_from_slice(chunk);
+        }
+        let count = batch.len().div_ceil(16);
+        for block in crypt(&blocks)[..count].iter() {
+            output.extend_from_slice(block);
+        }
+    }
+    output
+}
+
+/// Encrypts `input` in ECB mode like [`crate::encrypt`]: the last block is padded with zero bytes.
+pub fn encrypt(input: &[u8], key: &Key) -> Vec<u8> {
+    let aes = BitslicedAes::new(key);
+    batches(input, |blocks| aes.encrypt_blocks(blocks))
+}
+
+/// Decrypts `input` in ECB mode like [`crate::decrypt`].
+pub fn decrypt(input: &[u8], key: &Key) -> Vec<u8> {
+    let aes = BitslicedAes::new(key);
+    batches(input, |blocks| aes.decrypt_blocks(blocks))
+}
@@ -0,0 +1,78 @@
+use rand::{thread_rng, Rng};
+
+use crate::ttable::TTableAes;
+use crate::{keygen_128, keygen_192, keygen_256, parse_key, Backend, INV_S_BOX, S_BOX};
+
+use super::*;
+
+#[test]
+fn test_planes() {
+    let mut rng = thread_rng();
+    let mut bytes = [0; 64];
+    rng.fill(&mut bytes[..]);
+    let state = to_planes(&bytes);
+    for (i, byte) in bytes.iter().enumerate() {
+        for (b, plane) in state.iter().enumerate() {
+            assert_eq!(plane >> i & 1, (byte >> b & 1) as u64);
+        }
+    }
+    assert_eq!(from_planes(&state), bytes);
+}
+
+#[test]
+fn test_sbox() {
+    for batch in 0..4 {
+        let bytes: [u8; 64] = (0..64).map(|i| (64 * batch + i) as u8).collect::<Vec<_>>().try_into().unwrap();
+        let mut state = to_planes(&bytes);
+        sbox(&mut state);
+        let output = from_planes(&state);
+        inv_sbox(&mut state);
+        assert_eq!(from_planes(&state), bytes);
+        for (x, y) in bytes.iter().zip(output.iter()) {
+            assert_eq!(*y, S_BOX[*x as usize]);
+            assert_eq!(INV_S_BOX[*y as usize], *x);
+        }
+    }
+    assert_eq!(sub_word(0xcf4f3c09), 0x8a84eb01);
+}
+
+#[test]
+fn test_known_answer() {
+    let plaintext = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+    let ciphertexts = [
+        ("000102030405060708090a0b0c0d0e0f", [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a]),
+        ("000102030405060708090a0b0c0d0e0f1011121314151617", [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91]),
+        ("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f", [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89]),
+    ];
+    for (key, ciphertext) in ciphertexts.iter() {
+        let aes = BitslicedAes::new(&parse_key(key).unwrap());
+        assert_eq!(aes.encrypt_block(plaintext), *ciphertext);
+        assert_eq!(aes.decrypt_block(*ciphertext), plaintext);
+    }
+}
+
+#[test]
+fn test_cross_check() {
+    let mut rng = thread_rng();
+    for _ in 0..50 {
+        for key in [keygen_128(), keygen_192(), keygen_256()] {
+            let blocks: [[u8; 16]; BLOCKS] = rng.gen();
+            let aes = BitslicedAes::new(&key);
+            let table = TTableAes::new(&key);
+            assert_eq!(aes.encrypt_blocks(&blocks), blocks.map(|block| table.encrypt_block(block)));
+            assert_eq!(aes.decrypt_blocks(&blocks), blocks.map(|block| table.decrypt_block(block)));
+        }
+    }
+}
+
+#[test]
+fn test_ecb() {
+    let mut rng = thread_rng();
+    let key = keygen_128();
+    for length in [0, 1, 16, 17, 63, 64, 65, 200] {
+        let input: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+        let encrypted = encrypt(&input, &key);
+        assert_eq!(encrypted, crate::encrypt_with(&input, &key, Backend::TTable));
+        assert_eq!(decrypt(&encrypted, &key), crate::decrypt_with(&encrypted, &key, Backend::TTable));
+    }
+}
@@ -0,0 +1,185 @@
+//! Counter with CBC-MAC (NIST SP 800-38C, RFC 3610), authenticated encryption with associated
+//! data.
+//!
+//! The tag is a CBC-MAC over a first block B0 holding the flags, the nonce and the length of the
+//! payload, then the length-prefixed associated data and the payload, each padded with zero bytes
+//! to whole blocks. The payload is encrypted in counter mode with counter blocks holding the nonce,
+//! and the tag is masked with the encryption of counter block 0.
+//!
+//! The nonce and the length field share the 15 bytes after the flags: a nonce of 7 to 13 bytes
+//! leaves 8 to 2 bytes to count the payload. Tags are 4 to 16 bytes long, an even number.
+
+use std::fmt;
+
+use crate::{constant_time_eq, Backend, BlockCipher, Key};
+
+#[cfg(test)]
+mod test;
+
+pub const MIN_NONCE_BYTES: usize = 7;
+
+pub const MAX_NONCE_BYTES: usize = 13;
+
+/// Tag lengths allowed by SP 800-38C section A.1, full tags first.
+pub const TAG_BYTES: [usize; 7] = [16, 14, 12, 10, 8, 6, 4];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CcmError {
+    InvalidNonceLength(usize),
+    InvalidTagLength(usize),
+    /// The payload does not fit in the length field left by the nonce.
+    TooLong,
+    /// The tag does not match: the ciphertext, the associated data, the nonce or the key is wrong.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for CcmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CcmError::InvalidNonceLength(bytes) => write!(f, "nonce of {} bytes is not allowed, expected {} to {}", bytes, MIN_NONCE_BYTES, MAX_NONCE_BYTES),
+            CcmError::InvalidTagLength(bytes) => write!(f, "tag of {} bytes is not allowed", bytes),
+            CcmError::TooLong => write!(f, "input is too long for the nonce length"),
+            CcmError::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for CcmError {}
+
+/// The encoding of the length of the associated data of SP 800-38C section A.2.2.
+fn encode_aad_length(length: usize) -> Vec<u8> {
+    let length = length as u64;
+    if length < (1 << 16) - (1 << 8) {
+        (length as u16).to_be_bytes().to_vec()
+    } else if length < 1 << 32 {
+        [&[0xff, 0xfe][..], &(length as u32).to_be_bytes()].concat()
+    } else {
+        [&[0xff, 0xff][..], &length.to_be_bytes()].concat()
+    }
+}
+
+/// A block of `flags`, `nonce` and `value` in the remaining bytes, big-endian: B0 when `value` is
+/// the payload length, counter block `value` otherwise.
+fn format_block(flags: u8, nonce: &[u8], value: u64) -> [u8; 16] {
+    let mut block = [0; 16];
+    block[0] = flags;
+    block[1..1 + nonce.len()].copy_from_slice(nonce);
+    let length_bytes = 15 - nonce.len();
+    block[1 + nonce.len()..].copy_from_slice(&value.to_be_bytes()[8 - length_bytes..]);
+    block
+}
+
+/// AES-CCM under one key.
+///
+/// ```
+/// use aes::ccm::{Ccm, CcmError};
+/// use aes::parse_key;
+///
+/// let ccm = Ccm::new(&parse_key("000102030405060708090a0b0c0d0e0f").unwrap()).truncate_tag(8).unwrap();
+/// let nonce = [0; 13];
+/// let sealed = ccm.encrypt(&nonce, b"header", b"message").unwrap();
+/// assert_eq!(sealed.len(), 7 + 8);
+/// assert_eq!(ccm.decrypt(&nonce, b"header", &sealed), Ok(b"message".to_vec()));
+/// assert_eq!(ccm.decrypt(&nonce, b"other header", &sealed), Err(CcmError::AuthenticationFailed));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ccm {
+    cipher: BlockCipher,
+    tag_bytes: usize,
+}
+
+impl Ccm {
+    /// With the [default](Backend::default) backend and full 16-byte tags.
+    pub fn new(key: &Key) -> Ccm {
+        Ccm::with_backend(key, Backend::default())
+    }
+
+    /// Panics if the backend is not [available](Backend::is_available).
+    pub fn with_backend(key: &Key, backend: Backend) -> Ccm {
+        Ccm { cipher: BlockCipher::new(key, backend), tag_bytes: 16 }
+    }
+
+    /// Truncates the tags to `bytes` bytes, one of [`TAG_BYTES`]. Unlike GCM, the tag length is
+    /// part of B0, so a tag of another length is not a prefix of the full one.
+    pub fn truncate_tag(mut self, bytes: usize) -> Result<Ccm, CcmError> {
+        if !TAG_BYTES.contains(&bytes) {
+            return Err(CcmError::InvalidTagLength(bytes));
+        }
+        self.tag_bytes = bytes;
+        Ok(self)
+    }
+
+    pub fn tag_bytes(&self) -> usize {
+        self.tag_bytes
+    }
+
+    /// Checks the nonce length and that the payload length fits in the bytes the nonce leaves.
+    fn check_lengths(nonce: &[u8], payload: usize) -> Result<(), CcmError> {
+        if !(MIN_NONCE_BYTES..=MAX_NONCE_BYTES).contains(&nonce.len()) {
+            return Err(CcmError::InvalidNonceLength(nonce.len()));
+        }
+        let length_bits = 8 * (15 - nonce.len());
+        if length_bits < 64 && payload as u64 >> length_bits != 0 {
+            return Err(CcmError::TooLong);
+        }
+        Ok(())
+    }
+
+    /// The unmasked tag: the CBC-MAC of B0, the associated data and the payload.
+    fn mac(&self, nonce: &[u8], aad: &[u8], payload: &[u8]) -> [u8; 16] {
+        let tag_field = ((self.tag_bytes - 2) / 2) as u8;
+        let flags = ((!aad.is_empty() as u8) << 6) | (tag_field << 3) | (14 - nonce.len()) as u8;
+        let mut y = self.cipher.encrypt_block(format_block(flags, nonce, payload.len() as u64));
+        let mut absorb = |data: &[u8]| {
+            for chunk in data.chunks(16) {
+                for (y, x) in y.iter_mut().zip(chunk.iter()) {
+                    *y ^= x;
+                }
+                y = self.cipher.encrypt_block(y);
+            }
+        };
+        if !aad.is_empty() {
+            absorb(&[&encode_aad_length(aad.len())[..], aad].concat());
+        }
+        absorb(payload);
+        y
+    }
+
+    /// Counter mode with counter blocks 1, 2 and so on, and the mask of the tag from block 0.
+    fn ctr(&self, nonce: &[u8], input: &[u8]) -> (Vec<u8>, [u8; 16]) {
+        let flags = (14 - nonce.len()) as u8;
+        let mut blocks: Vec<[u8; 16]> = (0..=input.len().div_ceil(16) as u64).map(|i| format_block(flags, nonce, i)).collect();
+        self.cipher.encrypt_blocks(&mut blocks);
+        let output = input.iter().zip(blocks[1..].iter().flatten()).map(|(x, k)| x ^ k).collect();
+        (output, blocks[0])
+    }
+
+    /// Encrypts `plaintext` and authenticates it along with `aad`. Returns the ciphertext, as long
+    /// as the plaintext, followed by the tag.
+    ///
+    /// A nonce must never be used twice with the same key.
+    pub fn encrypt(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CcmError> {
+        Ccm::check_lengths(nonce, plaintext.len())?;
+        let mac = self.mac(nonce, aad, plaintext);
+        let (mut output, mask) = self.ctr(nonce, plaintext);
+        output.extend(mac.iter().zip(mask.iter()).take(self.tag_bytes).map(|(t, m)| t ^ m));
+        Ok(output)
+    }
+
+    /// Decrypts the ciphertext at the start of `input` and checks the tag at its end. No plaintext
+    /// is returned unless the tag matches.
+    pub fn decrypt(&self, nonce: &[u8], aad: &[u8], input: &[u8]) -> Result<Vec<u8>, CcmError> {
+        if input.len() < self.tag_bytes {
+            return Err(CcmError::AuthenticationFailed);
+        }
+        let (ciphertext, tag) = input.split_at(input.len() - self.tag_bytes);
+        Ccm::check_lengths(nonce, ciphertext.len())?;
+        let (plaintext, mask) = self.ctr(nonce, ciphertext);
+        let mac = self.mac(nonce, aad, &plaintext);
+        let expected: Vec<u8> = mac.iter().zip(mask.iter()).take(self.tag_bytes).map(|(t, m)| t ^ m).collect();
+        if !constant_time_eq(&expected, tag) {
+            return Err(CcmError::AuthenticationFailed);
+        }
+        Ok(plaintext)
+    }
+}
@@ -0,0 +1,138 @@
+use rand::{thread_rng, Rng};
+
+use crate::{keygen_128, keygen_256, parse_key, Backend};
+
+use super::*;
+
+/// Packet vectors 1 to 12 of RFC 3610 under the key c0c1...cf: nonce, associated data, plaintext,
+/// tag length and ciphertext followed by the tag.
+const RFC_3610: [(&str, &str, &str, usize, &str); 12] = [
+    ("00000003020100a0a1a2a3a4a5", "0001020304050607", "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e", 8, "588c979a61c663d2f066d0c2c0f989806d5f6b61dac38417e8d12cfdf926e0"),
+    ("00000004030201a0a1a2a3a4a5", "0001020304050607", "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f", 8, "72c91a36e135f8cf291ca894085c87e3cc15c439c9e43a3ba091d56e10400916"),
+    ("00000005040302a0a1a2a3a4a5", "0001020304050607", "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20", 8, "51b1e5f44a197d1da46b0f8e2d282ae871e838bb64da8596574adaa76fbd9fb0c5"),
+    ("00000006050403a0a1a2a3a4a5", "000102030405060708090a0b", "0c0d0e0f101112131415161718191a1b1c1d1e", 8, "a28c6865939a9a79faaa5c4c2a9d4a91cdac8c96c861b9c9e61ef1"),
+    ("00000007060504a0a1a2a3a4a5", "000102030405060708090a0b", "0c0d0e0f101112131415161718191a1b1c1d1e1f", 8, "dcf1fb7b5d9e23fb9d4e131253658ad86ebdca3e51e83f077d9c2d93"),
+    ("00000008070605a0a1a2a3a4a5", "000102030405060708090a0b", "0c0d0e0f101112131415161718191a1b1c1d1e1f20", 8, "6fc1b011f006568b5171a42d953d469b2570a4bd87405a0443ac91cb94"),
+    ("00000009080706a0a1a2a3a4a5", "0001020304050607", "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e", 10, "0135d1b2c95f41d5d1d4fec185d166b8094e999dfed96c048c56602c97acbb7490"),
+    ("0000000a090807a0a1a2a3a4a5", "0001020304050607", "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f", 10, "7b75399ac0831dd2f0bbd75879a2fd8f6cae6b6cd9b7db24c17b4433f434963f34b4"),
+    ("0000000b0a0908a0a1a2a3a4a5", "0001020304050607", "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20", 10, "82531a60cc24945a4b8279181ab5c84df21ce7f9b73f42e197ea9c07e56b5eb17e5f4e"),
+    ("0000000c0b0a09a0a1a2a3a4a5", "000102030405060708090a0b", "0c0d0e0f101112131415161718191a1b1c1d1e", 10, "07342594157785152b074098330abb141b947b566aa9406b4d999988dd"),
+    ("0000000d0c0b0aa0a1a2a3a4a5", "000102030405060708090a0b", "0c0d0e0f101112131415161718191a1b1c1d1e1f", 10, "676bb20380b0e301e8ab79590a396da78b834934f53aa2e9107a8b6c022c"),
+    ("0000000e0d0c0ba0a1a2a3a4a5", "000102030405060708090a0b", "0c0d0e0f101112131415161718191a1b1c1d1e1f20", 10, "c0ffa0d6f05bdb67f24d43a4338d2aa4bed7b20e43cd1aa31662e7ad65d6db"),
+];
+
+/// Examples 1 to 3 of SP 800-38C appendix C under the key 404142...4f: nonce, associated data,
+/// plaintext, tag length and ciphertext followed by the tag.
+const SP_800_38C: [(&str, &str, &str, usize, &str); 3] = [
+    ("10111213141516", "0001020304050607", "20212223", 4, "7162015b4dac255d"),
+    ("1011121314151617", "000102030405060708090a0b0c0d0e0f", "202122232425262728292a2b2c2d2e2f", 6, "d2a1f0e051ea5f62081a7792073d593d1fc64fbfaccd"),
+    ("101112131415161718191a1b", "000102030405060708090a0b0c0d0e0f10111213", "202122232425262728292a2b2c2d2e2f3031323334353637", 8, "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5484392fbc1b09951"),
+];
+
+fn hex(value: &str) -> Vec<u8> {
+    (0..value.len()).step_by(2).map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap()).collect()
+}
+
+fn available_backends() -> impl Iterator<Item = Backend> {
+    Backend::ALL.iter().copied().filter(Backend::is_available)
+}
+
+fn check(key: &str, vectors: &[(&str, &str, &str, usize, &str)]) {
+    for backend in available_backends() {
+        for &(nonce, aad, plaintext, tag_bytes, sealed) in vectors {
+            let ccm = Ccm::with_backend(&parse_key(key).unwrap(), backend).truncate_tag(tag_bytes).unwrap();
+            assert_eq!(ccm.encrypt(&hex(nonce), &hex(aad), &hex(plaintext)), Ok(hex(sealed)), "{} {}", backend.name(), nonce);
+            assert_eq!(ccm.decrypt(&hex(nonce), &hex(aad), &hex(sealed)), Ok(hex(plaintext)));
+        }
+    }
+}
+
+#[test]
+fn test_rfc_3610() {
+    check("c0c1c2c3c4c5c6c7c8c9cacbcccdcecf", &RFC_3610);
+}
+
+#[test]
+fn test_sp_800_38c() {
+    check("404142434445464748494a4b4c4d4e4f", &SP_800_38C);
+}
+
+/// Example 4 of SP 800-38C: 65536 bytes of associated data, whose length takes six bytes.
+#[test]
+fn test_long_aad() {
+    let ccm = Ccm::new(&parse_key("404142434445464748494a4b4c4d4e4f").unwrap()).truncate_tag(14).unwrap();
+    let aad: Vec<u8> = (0..1 << 16).map(|i| i as u8).collect();
+    let nonce = hex("101112131415161718191a1b1c");
+    let plaintext = hex("202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f");
+    let sealed = hex("69915dad1e84c6376a68c2967e4dab615ae0fd1faec44cc484828529463ccf72b4ac6bec93e8598e7f0dadbcea5b");
+    assert_eq!(ccm.encrypt(&nonce, &aad, &plaintext), Ok(sealed.clone()));
+    assert_eq!(ccm.decrypt(&nonce, &aad, &sealed), Ok(plaintext));
+}
+
+#[test]
+fn test_encode_aad_length() {
+    assert_eq!(encode_aad_length(1), [0x00, 0x01]);
+    assert_eq!(encode_aad_length(0xfeff), [0xfe, 0xff]);
+    assert_eq!(encode_aad_length(0xff00), [0xff, 0xfe, 0x00, 0x00, 0xff, 0x00]);
+    assert_eq!(encode_aad_length(0x1_0000), [0xff, 0xfe, 0x00, 0x01, 0x00, 0x00]);
+    assert_eq!(encode_aad_length(0x1_0000_0000), [0xff, 0xff, 0, 0, 0, 0x01, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_format_block() {
+    // B0 of SP 800-38C example 1.
+    assert_eq!(format_block(0x4f, &hex("10111213141516"), 4).to_vec(), hex("4f101112131415160000000000000004"));
+    assert_eq!(format_block(0x01, &hex("00000003020100a0a1a2a3a4a5"), 0x0102).to_vec(), hex("0100000003020100a0a1a2a3a4a50102"));
+}
+
+#[test]
+fn test_parameters() {
+    let ccm = Ccm::new(&keygen_128());
+    for bytes in [0, 6, 14, 15] {
+        assert_eq!(ccm.encrypt(&vec![0; bytes], &[], b"message"), Err(CcmError::InvalidNonceLength(bytes)));
+        assert_eq!(ccm.decrypt(&vec![0; bytes], &[], &[0; 16]), Err(CcmError::InvalidNonceLength(bytes)));
+    }
+    for bytes in [0, 2, 3, 5, 11, 17] {
+        assert_eq!(Ccm::new(&keygen_128()).truncate_tag(bytes).err(), Some(CcmError::InvalidTagLength(bytes)));
+    }
+    // A 13-byte nonce leaves two bytes for the length.
+    assert_eq!(ccm.encrypt(&[0; 13], &[], &vec![0; 1 << 16]), Err(CcmError::TooLong));
+    assert_eq!(ccm.encrypt(&[0; 13], &[], &vec![0; (1 << 16) - 1]).map(|sealed| sealed.len()), Ok((1 << 16) - 1 + 16));
+    assert!(ccm.encrypt(&[0; 12], &[], &vec![0; 1 << 16]).is_ok());
+}
+
+#[test]
+fn test_authentication() {
+    let ccm = Ccm::new(&keygen_256()).truncate_tag(8).unwrap();
+    let nonce = [7; 13];
+    let sealed = ccm.encrypt(&nonce, b"associated data", b"attack at dawn").unwrap();
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 0x01;
+        assert_eq!(ccm.decrypt(&nonce, b"associated data", &tampered), Err(CcmError::AuthenticationFailed));
+    }
+    assert_eq!(ccm.decrypt(&nonce, b"", &sealed), Err(CcmError::AuthenticationFailed));
+    assert_eq!(ccm.decrypt(&[8; 13], b"associated data", &sealed), Err(CcmError::AuthenticationFailed));
+    assert_eq!(ccm.decrypt(&nonce, b"associated data", &sealed[..7]), Err(CcmError::AuthenticationFailed));
+    // The tag length is authenticated: the same key with longer tags rejects a truncated tag.
+    let long = ccm.clone().truncate_tag(10).unwrap();
+    assert_eq!(long.decrypt(&nonce, b"associated data", &[&sealed[..], &[0, 0]].concat()), Err(CcmError::AuthenticationFailed));
+    assert_eq!(ccm.decrypt(&nonce, b"associated data", &sealed), Ok(b"attack at dawn".to_vec()));
+}
+
+#[test]
+fn test_backends() {
+    let mut rng = thread_rng();
+    let key = keygen_256();
+    for length in [0, 1, 15, 16, 17, 63, 64, 65, 300] {
+        let plaintext: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+        let aad: Vec<u8> = (0..rng.gen_range(0, 40)).map(|_| rng.gen()).collect();
+        let nonce: Vec<u8> = (0..rng.gen_range(MIN_NONCE_BYTES, MAX_NONCE_BYTES + 1)).map(|_| rng.gen()).collect();
+        let sealed = Ccm::with_backend(&key, Backend::Reference).encrypt(&nonce, &aad, &plaintext).unwrap();
+        for backend in available_backends() {
+            let ccm = Ccm::with_backend(&key, backend);
+            assert_eq!(ccm.encrypt(&nonce, &aad, &plaintext).as_ref(), Ok(&sealed), "{}", backend.name());
+            assert_eq!(ccm.decrypt(&nonce, &aad, &sealed), Ok(plaintext.clone()));
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! CMAC (NIST SP 800-38B, RFC 4493), a message authentication code from the block cipher.
+//!
+//! CMAC is CBC-MAC with the last block masked by one of two subkeys derived from E(K, 0): K1
+//! when the message fills its last block, K2 when the last block has been padded with a one bit
+//! and zero bits. The masks make the MAC secure for messages of any length.
+
+use crate::{Backend, BlockCipher, Key};
+
+#[cfg(test)]
+mod test;
+
+/// Multiplication by x in GF(2^128) modulo x^128 + x^7 + x^2 + x + 1, the first byte of the block
+/// holding the highest coefficients. Called dbl() by RFC 5297.
+pub fn double(block: [u8; 16]) -> [u8; 16] {
+    let value = u128::from_be_bytes(block);
+    // 0x87 when the top bit is set, without a branch on it.
+    ((value << 1) ^ ((value >> 127) * 0x87)).to_be_bytes()
+}
+
+/// CMAC under one key.
+#[derive(Debug, Clone)]
+pub struct Cmac {
+    cipher: BlockCipher,
+    k1: [u8; 16],
+    k2: [u8; 16],
+}
+
+impl Cmac {
+    /// With the [default](Backend::default) backend.
+    pub fn new(key: &Key) -> Cmac {
+        Cmac::with_backend(key, Backend::default())
+    }
+
+    /// Panics if the backend is not [available](Backend::is_available).
+    pub fn with_backend(key: &Key, backend: Backend) -> Cmac {
+        let cipher = BlockCipher::new(key, backend);
+        let k1 = double(cipher.encrypt_block([0; 16]));
+        let k2 = double(k1);
+        Cmac { cipher, k1, k2 }
+    }
+
+    /// The 16-byte tag of `message`. Shorter tags are its first bytes.
+    pub fn mac(&self, message: &[u8]) -> [u8; 16] {
+        // The last block is complete unless the message is empty or ends in a partial block.
+        let last_start = message.len().saturating_sub(1) / 16 * 16;
+        let (body, last) = message.split_at(last_start);
+        let mut y = [0; 16];
+        for block in body.chunks(16) {
+            for (y, x) in y.iter_mut().zip(block.iter()) {
+                *y ^= x;
+            }
+            y = self.cipher.encrypt_block(y);
+        }
+        let mask = if last.len() == 16 {
+            self.k1
+        } else {
+            y[last.len()] ^= 0x80;
+            self.k2
+        };
+        for (i, y) in y.iter_mut().enumerate() {
+            *y ^= last.get(i).copied().unwrap_or(0) ^ mask[i];
+        }
+        self.cipher.encrypt_block(y)
+    }
+}
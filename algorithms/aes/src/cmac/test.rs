@@ -0,0 +1,74 @@
+use rand::{thread_rng, Rng};
+
+use crate::{keygen_128, parse_key, Backend};
+
+use super::*;
+
+const MESSAGE: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+
+/// The examples of SP 800-38B appendix D, those for AES-128 being the ones of RFC 4493: key, length
+/// of the prefix of [`MESSAGE`] and tag.
+const EXAMPLES: [(&str, usize, &str); 12] = [
+    ("2b7e151628aed2a6abf7158809cf4f3c", 0, "bb1d6929e95937287fa37d129b756746"),
+    ("2b7e151628aed2a6abf7158809cf4f3c", 16, "070a16b46b4d4144f79bdd9dd04a287c"),
+    ("2b7e151628aed2a6abf7158809cf4f3c", 40, "dfa66747de9ae63030ca32611497c827"),
+    ("2b7e151628aed2a6abf7158809cf4f3c", 64, "51f0bebf7e3b9d92fc49741779363cfe"),
+    ("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b", 0, "d17ddf46adaacde531cac483de7a9367"),
+    ("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b", 16, "9e99a7bf31e710900662f65e617c5184"),
+    ("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b", 40, "8a1de5be2eb31aad089a82e6ee908b0e"),
+    ("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b", 64, "a1d5df0eed790f794d77589659f39a11"),
+    ("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4", 0, "028962f61b7bf89efc6b551f4667d983"),
+    ("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4", 16, "28a7023f452e8f82bd4bf28d8c37c35c"),
+    ("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4", 40, "aaf3d8f1de5640c232f5b169b9c911e6"),
+    ("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4", 64, "e1992190549f6ed5696a2c056c315410"),
+];
+
+fn hex(value: &str) -> Vec<u8> {
+    (0..value.len()).step_by(2).map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn test_subkeys() {
+    // RFC 4493 section 4.
+    let cmac = Cmac::new(&parse_key("2b7e151628aed2a6abf7158809cf4f3c").unwrap());
+    assert_eq!(cmac.k1.to_vec(), hex("fbeed618357133667c85e08f7236a8de"));
+    assert_eq!(cmac.k2.to_vec(), hex("f7ddac306ae266ccf90bc11ee46d513b"));
+}
+
+#[test]
+fn test_double() {
+    assert_eq!(double([0; 16]), [0; 16]);
+    let mut one = [0; 16];
+    one[15] = 1;
+    assert_eq!(double(one)[15], 2);
+    let mut top = [0; 16];
+    top[0] = 0x80;
+    assert_eq!(double(top)[15], 0x87);
+}
+
+#[test]
+fn test_examples() {
+    let message = hex(MESSAGE);
+    for backend in Backend::ALL.iter().copied().filter(Backend::is_available) {
+        for &(key, length, tag) in EXAMPLES.iter() {
+            let cmac = Cmac::with_backend(&parse_key(key).unwrap(), backend);
+            assert_eq!(cmac.mac(&message[..length]).to_vec(), hex(tag), "{} {} {}", backend.name(), key, length);
+        }
+    }
+}
+
+/// The padded and the unpadded last block give different tags.
+#[test]
+fn test_padding() {
+    let mut rng = thread_rng();
+    let cmac = Cmac::new(&keygen_128());
+    let message: [u8; 32] = rng.gen();
+    let mut padded = message[..31].to_vec();
+    padded.push(0x80);
+    assert_ne!(cmac.mac(&message[..31]), cmac.mac(&padded));
+    assert_ne!(cmac.mac(&[]), cmac.mac(&[0x80]));
+    let tags: Vec<_> = (0..=32).map(|length| cmac.mac(&message[..length])).collect();
+    for (i, tag) in tags.iter().enumerate() {
+        assert!(tags[i + 1..].iter().all(|other| other != tag));
+    }
+}
@@ -0,0 +1,302 @@
+//! Galois/Counter Mode (NIST SP 800-38D), authenticated encryption with associated data.
+//!
+//! The plaintext is encrypted in counter mode and a tag is computed over the associated data and
+//! the ciphertext with GHASH, a polynomial evaluated in GF(2^128) at the hash subkey H = E(K, 0).
+//! Decryption checks the tag before it decrypts anything and returns no plaintext when the tag does
+//! not match.
+//!
+//! GHASH multiplies with the PCLMULQDQ instruction when the block cipher runs on AES-NI and the
+//! processor has it, and otherwise with integer multiplications whose operands have holes of
+//! three zero bits between the useful ones, so that carries never reach another useful bit (the
+//! `ctmul64` technique of BearSSL). Neither looks up tables indexed by secret data.
+
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::{constant_time_eq, Backend, BlockCipher, Key};
+
+#[cfg(test)]
+mod test;
+
+pub const NONCE_BYTES: usize = 12;
+
+/// Tag lengths allowed by SP 800-38D section 5.2.1.2, full tags first.
+pub const TAG_BYTES: [usize; 7] = [16, 15, 14, 13, 12, 8, 4];
+
+/// Longest plaintext: 2^39 - 256 bits.
+pub const MAX_PLAINTEXT_BYTES: u64 = (1 << 36) - 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcmError {
+    EmptyNonce,
+    InvalidTagLength(usize),
+    TooLong,
+    /// The tag does not match: the ciphertext, the associated data, the nonce or the key is wrong.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for GcmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GcmError::EmptyNonce => write!(f, "nonce is empty"),
+            GcmError::InvalidTagLength(bytes) => write!(f, "tag of {} bytes is not allowed", bytes),
+            GcmError::TooLong => write!(f, "input is too long"),
+            GcmError::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for GcmError {}
+
+/// Carry-less multiplication of 64-bit polynomials: the high and the low half of the product.
+type Clmul = fn(u64, u64) -> (u64, u64);
+
+/// The low half of the carry-less product, with the bits of each operand split into four sets
+/// four bits apart. A product of two sets has at most 16 terms per bit, so its carries stay in the
+/// three bits above it and are masked away.
+fn bmul64(x: u64, y: u64) -> u64 {
+    const M: [u64; 4] = [0x1111_1111_1111_1111, 0x2222_2222_2222_2222, 0x4444_4444_4444_4444, 0x8888_8888_8888_8888];
+    let x = M.map(|m| x & m);
+    let y = M.map(|m| y & m);
+    let mut z = 0;
+    for (i, m) in M.iter().enumerate() {
+        let mut sum = 0;
+        for j in 0..4 {
+            sum ^= x[j].wrapping_mul(y[(i + 4 - j) & 3]);
+        }
+        z |= sum & m;
+    }
+    z
+}
+
+/// The high half is the low half of the product of the bit-reversed operands, reversed back.
+fn clmul_software(x: u64, y: u64) -> (u64, u64) {
+    let high = bmul64(x.reverse_bits(), y.reverse_bits()).reverse_bits() >> 1;
+    (high, bmul64(x, y))
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "pclmulqdq,sse2")]
+unsafe fn pclmulqdq(x: u64, y: u64) -> (u64, u64) {
+    use std::arch::x86_64::*;
+    let product = _mm_clmulepi64_si128::<0x00>(_mm_cvtsi64_si128(x as i64), _mm_cvtsi64_si128(y as i64));
+    (_mm_cvtsi128_si64(_mm_unpackhi_epi64(product, product)) as u64, _mm_cvtsi128_si64(product) as u64)
+}
+
+#[cfg(target_arch = "x86_64")]
+fn clmul_hardware(x: u64, y: u64) -> (u64, u64) {
+    // Safe: only chosen after `pclmulqdq_detected`.
+    unsafe { pclmulqdq(x, y) }
+}
+
+/// The product of `x` and `h` in GF(2^128) with the bit order of GCM. Both are the two big-endian
+/// halves of a block, first half first.
+fn gf_mul(x: [u64; 2], h: [u64; 2], clmul: Clmul) -> [u64; 2] {
+    let [x1, x0] = x;
+    let [h1, h0] = h;
+    // Karatsuba: three 64-bit products make the 256-bit one.
+    let (z0h, z0) = clmul(x0, h0);
+    let (z1h, z1) = clmul(x1, h1);
+    let (z2h, z2) = clmul(x0 ^ x1, h0 ^ h1);
+    let (z2h, z2) = (z2h ^ z0h ^ z1h, z2 ^ z0 ^ z1);
+    let (v0, v1, v2, v3) = (z0, z0h ^ z2, z1 ^ z2h, z1h);
+    // The bits are reflected, so the product of two 127-degree polynomials is one bit short.
+    let (v0, v1, v2, v3) = (v0 << 1, (v1 << 1) | (v0 >> 63), (v2 << 1) | (v1 >> 63), (v3 << 1) | (v2 >> 63));
+    // Reduction modulo x^128 + x^7 + x^2 + x + 1, a word at a time.
+    let v2 = v2 ^ v0 ^ (v0 >> 1) ^ (v0 >> 2) ^ (v0 >> 7);
+    let v1 = v1 ^ (v0 << 63) ^ (v0 << 62) ^ (v0 << 57);
+    let v3 = v3 ^ v1 ^ (v1 >> 1) ^ (v1 >> 2) ^ (v1 >> 7);
+    let v2 = v2 ^ (v1 << 63) ^ (v1 << 62) ^ (v1 << 57);
+    [v3, v2]
+}
+
+fn halves(block: &[u8]) -> [u64; 2] {
+    [u64::from_be_bytes(block[..8].try_into().unwrap()), u64::from_be_bytes(block[8..].try_into().unwrap())]
+}
+
+/// GHASH of SP 800-38D section 6.4 under one hash subkey.
+#[derive(Debug, Clone)]
+pub struct Ghash {
+    h: [u64; 2],
+    y: [u64; 2],
+    clmul: Clmul,
+}
+
+impl Ghash {
+    /// With PCLMULQDQ if `hardware` and the processor has it.
+    pub fn new(h: [u8; 16], hardware: bool) -> Ghash {
+        #[cfg(target_arch = "x86_64")]
+        let clmul: Clmul = if hardware && crate::aesni::pclmulqdq_detected() { clmul_hardware } else { clmul_software };
+        #[cfg(not(target_arch = "x86_64"))]
+        let clmul: Clmul = {
+            let _ = hardware;
+            clmul_software
+        };
+        Ghash { h: halves(&h), y: [0; 2], clmul }
+    }
+
+    /// Hashes `data`, padded with zero bytes to a whole number of blocks.
+    pub fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            let [x1, x0] = halves(&block);
+            self.y = gf_mul([self.y[0] ^ x1, self.y[1] ^ x0], self.h, self.clmul);
+        }
+    }
+
+    pub fn finalize(&self) -> [u8; 16] {
+        let mut result = [0; 16];
+        result[..8].copy_from_slice(&self.y[0].to_be_bytes());
+        result[8..].copy_from_slice(&self.y[1].to_be_bytes());
+        result
+    }
+}
+
+/// The block of two 64-bit lengths in bits, as GHASH takes them last.
+fn lengths(a: usize, b: usize) -> [u8; 16] {
+    let mut block = [0; 16];
+    block[..8].copy_from_slice(&(8 * a as u64).to_be_bytes());
+    block[8..].copy_from_slice(&(8 * b as u64).to_be_bytes());
+    block
+}
+
+/// Increments the last 32 bits of `block`, modulo 2^32.
+fn inc32(block: &mut [u8; 16]) {
+    let counter = u32::from_be_bytes(block[12..].try_into().unwrap()).wrapping_add(1);
+    block[12..].copy_from_slice(&counter.to_be_bytes());
+}
+
+/// Counter blocks encrypted at once, enough for the widest backend.
+const CTR_BATCH: usize = 32;
+
+/// GCTR of SP 800-38D section 6.5: `input` xored with the encryptions of `counter` and the blocks
+/// following it.
+fn gctr(cipher: &BlockCipher, mut counter: [u8; 16], input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    for batch in input.chunks(16 * CTR_BATCH) {
+        let mut blocks = [[0; 16]; CTR_BATCH];
+        let count = batch.len().div_ceil(16);
+        for block in blocks[..count].iter_mut() {
+            *block = counter;
+            inc32(&mut counter);
+        }
+        cipher.encrypt_blocks(&mut blocks[..count]);
+        output.extend(batch.iter().zip(blocks.iter().flatten()).map(|(x, k)| x ^ k));
+    }
+    output
+}
+
+/// AES-GCM under one key.
+///
+/// ```
+/// use aes::gcm::{Gcm, GcmError};
+/// use aes::parse_key;
+///
+/// let gcm = Gcm::new(&parse_key("000102030405060708090a0b0c0d0e0f").unwrap());
+/// let nonce = [0; 12];
+/// let sealed = gcm.encrypt(&nonce, b"header", b"message").unwrap();
+/// assert_eq!(sealed.len(), 7 + 16);
+/// assert_eq!(gcm.decrypt(&nonce, b"header", &sealed), Ok(b"message".to_vec()));
+/// assert_eq!(gcm.decrypt(&nonce, b"other header", &sealed), Err(GcmError::AuthenticationFailed));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Gcm {
+    cipher: BlockCipher,
+    h: [u8; 16],
+    tag_bytes: usize,
+}
+
+impl Gcm {
+    /// With the [default](Backend::default) backend and full 16-byte tags.
+    pub fn new(key: &Key) -> Gcm {
+        Gcm::with_backend(key, Backend::default())
+    }
+
+    /// Panics if the backend is not [available](Backend::is_available).
+    pub fn with_backend(key: &Key, backend: Backend) -> Gcm {
+        let cipher = BlockCipher::new(key, backend);
+        let h = cipher.encrypt_block([0; 16]);
+        Gcm { cipher, h, tag_bytes: 16 }
+    }
+
+    /// Truncates the tags to their first `bytes` bytes, one of [`TAG_BYTES`]. Short tags are
+    /// easier to forge; SP 800-38D appendix C limits how much data a 4 or 8-byte tag may protect.
+    pub fn truncate_tag(mut self, bytes: usize) -> Result<Gcm, GcmError> {
+        if !TAG_BYTES.contains(&bytes) {
+            return Err(GcmError::InvalidTagLength(bytes));
+        }
+        self.tag_bytes = bytes;
+        Ok(self)
+    }
+
+    pub fn tag_bytes(&self) -> usize {
+        self.tag_bytes
+    }
+
+    fn ghash(&self) -> Ghash {
+        Ghash::new(self.h, self.cipher.backend() == Backend::AesNi)
+    }
+
+    /// The pre-counter block J0: the nonce and a counter of 1 for the usual 96-bit nonces, the
+    /// GHASH of the nonce otherwise.
+    fn pre_counter(&self, nonce: &[u8]) -> Result<[u8; 16], GcmError> {
+        if nonce.is_empty() {
+            return Err(GcmError::EmptyNonce);
+        }
+        if nonce.len() == NONCE_BYTES {
+            let mut block = [0; 16];
+            block[..NONCE_BYTES].copy_from_slice(nonce);
+            block[15] = 1;
+            return Ok(block);
+        }
+        let mut ghash = self.ghash();
+        ghash.update(nonce);
+        ghash.update(&lengths(0, nonce.len()));
+        Ok(ghash.finalize())
+    }
+
+    fn tag(&self, pre_counter: [u8; 16], aad: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+        let mut ghash = self.ghash();
+        ghash.update(aad);
+        ghash.update(ciphertext);
+        ghash.update(&lengths(aad.len(), ciphertext.len()));
+        let mask = self.cipher.encrypt_block(pre_counter);
+        ghash.finalize().iter().zip(mask.iter()).take(self.tag_bytes).map(|(s, m)| s ^ m).collect()
+    }
+
+    /// Encrypts `plaintext` and authenticates it along with `aad`. Returns the ciphertext, as long
+    /// as the plaintext, followed by the tag.
+    ///
+    /// A nonce must never be used twice with the same key: that reveals the xor of the plaintexts
+    /// and lets anyone forge tags. Random nonces should be 96 bits.
+    pub fn encrypt(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, GcmError> {
+        if plaintext.len() as u64 > MAX_PLAINTEXT_BYTES {
+            return Err(GcmError::TooLong);
+        }
+        let mut counter = self.pre_counter(nonce)?;
+        let pre_counter = counter;
+        inc32(&mut counter);
+        let mut output = gctr(&self.cipher, counter, plaintext);
+        let tag = self.tag(pre_counter, aad, &output);
+        output.extend_from_slice(&tag);
+        Ok(output)
+    }
+
+    /// Checks the tag at the end of `input` and decrypts the ciphertext before it.
+    pub fn decrypt(&self, nonce: &[u8], aad: &[u8], input: &[u8]) -> Result<Vec<u8>, GcmError> {
+        if input.len() < self.tag_bytes {
+            return Err(GcmError::AuthenticationFailed);
+        }
+        let (ciphertext, tag) = input.split_at(input.len() - self.tag_bytes);
+        if ciphertext.len() as u64 > MAX_PLAINTEXT_BYTES {
+            return Err(GcmError::TooLong);
+        }
+        let mut counter = self.pre_counter(nonce)?;
+        if !constant_time_eq(&self.tag(counter, aad, ciphertext), tag) {
+            return Err(GcmError::AuthenticationFailed);
+        }
+        inc32(&mut counter);
+        Ok(gctr(&self.cipher, counter, ciphertext))
+    }
+}
@@ -0,0 +1,298 @@
+use rand::{thread_rng, Rng};
+
+use crate::{keygen_128, keygen_256, Backend};
+
+use super::*;
+
+/// Test cases 1 to 18 of the GCM specification, which NIST publishes with SP 800-38D: key,
+/// nonce, plaintext, associated data, ciphertext and tag.
+const VECTORS: [[&str; 6]; 18] = [
+    [
+        "00000000000000000000000000000000",
+        "000000000000000000000000",
+        "",
+        "",
+        "",
+        "58e2fccefa7e3061367f1d57a4e7455a",
+    ],
+    [
+        "00000000000000000000000000000000",
+        "000000000000000000000000",
+        "00000000000000000000000000000000",
+        "",
+        "0388dace60b6a392f328c2b971b2fe78",
+        "ab6e47d42cec13bdf53a67b21257bddf",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308",
+        "cafebabefacedbaddecaf888",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+        "",
+        "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+        "4d5c2af327cd64a62cf35abd2ba6fab4",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308",
+        "cafebabefacedbaddecaf888",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+        "5bc94fbc3221a5db94fae95ae7121a47",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308",
+        "cafebabefacedbad",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c742373806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+        "3612d2e79e3b0785561be14aaca2fccb",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308",
+        "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca701e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5",
+        "619cc5aefffe0bfa462af43c1699d050",
+    ],
+    [
+        "000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "",
+        "",
+        "",
+        "cd33b28ac773f74ba00ed1f312572435",
+    ],
+    [
+        "000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "00000000000000000000000000000000",
+        "",
+        "98e7247c07f0fe411c267e4384b0f600",
+        "2ff58d80033927ab8ef4d4587514f0fb",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c",
+        "cafebabefacedbaddecaf888",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+        "",
+        "3980ca0b3c00e841eb06fac4872a2757859e1ceaa6efd984628593b40ca1e19c7d773d00c144c525ac619d18c84a3f4718e2448b2fe324d9ccda2710acade256",
+        "9924a7c8587336bfb118024db8674a14",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c",
+        "cafebabefacedbaddecaf888",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "3980ca0b3c00e841eb06fac4872a2757859e1ceaa6efd984628593b40ca1e19c7d773d00c144c525ac619d18c84a3f4718e2448b2fe324d9ccda2710",
+        "2519498e80f1478f37ba55bd6d27618c",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c",
+        "cafebabefacedbad",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "0f10f599ae14a154ed24b36e25324db8c566632ef2bbb34f8347280fc4507057fddc29df9a471f75c66541d4d4dad1c9e93a19a58e8b473fa0f062f7",
+        "65dcc57fcf623a24094fcca40d3533f8",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c",
+        "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "d27e88681ce3243c4830165a8fdcf9ff1de9a1d8e6b447ef6ef7b79828666e4581e79012af34ddd9e2f037589b292db3e67c036745fa22e7e9b7373b",
+        "dcf566ff291c25bbb8568fc3d376a6d9",
+    ],
+    [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "",
+        "",
+        "",
+        "530f8afbc74536b9a963b4f1c4cb738b",
+    ],
+    [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "00000000000000000000000000000000",
+        "",
+        "cea7403d4d606b6e074ec5d3baf39d18",
+        "d0d1c8a799996bf0265b98b5d48ab919",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+        "cafebabefacedbaddecaf888",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+        "",
+        "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662898015ad",
+        "b094dac5d93471bdec1a502270e3cc6c",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+        "cafebabefacedbaddecaf888",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+        "76fc6ece0f4e1768cddf8853bb2d551b",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+        "cafebabefacedbad",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "c3762df1ca787d32ae47c13bf19844cbaf1ae14d0b976afac52ff7d79bba9de0feb582d33934a4f0954cc2363bc73f7862ac430e64abe499f47c9b1f",
+        "3a337dbf46a792c45e454913fe2ea8f2",
+    ],
+    [
+        "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+        "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+        "5a8def2f0c9e53f1f75d7853659e2a20eeb2b22aafde6419a058ab4f6f746bf40fc0c3b780f244452da3ebf1c5d82cdea2418997200ef82e44ae7e3f",
+        "a44a8266ee1c8eb0c8b5d4cf5ae9f19a",
+    ],
+];
+
+fn hex(value: &str) -> Vec<u8> {
+    (0..value.len()).step_by(2).map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap()).collect()
+}
+
+fn available_backends() -> impl Iterator<Item = Backend> {
+    Backend::ALL.iter().copied().filter(Backend::is_available)
+}
+
+/// Carry-less product, one bit at a time.
+fn clmul_bitwise(x: u64, y: u64) -> (u64, u64) {
+    let product = (0..64).filter(|i| y >> i & 1 != 0).fold(0u128, |product, i| product ^ ((x as u128) << i));
+    ((product >> 64) as u64, product as u64)
+}
+
+/// Algorithm 1 of SP 800-38D, one bit at a time.
+fn gf_mul_bitwise(x: [u8; 16], y: [u8; 16]) -> [u8; 16] {
+    let x = u128::from_be_bytes(x);
+    let mut v = u128::from_be_bytes(y);
+    let mut z = 0;
+    for i in 0..128 {
+        if x >> (127 - i) & 1 != 0 {
+            z ^= v;
+        }
+        v = if v & 1 != 0 { (v >> 1) ^ (0xe1 << 120) } else { v >> 1 };
+    }
+    z.to_be_bytes()
+}
+
+#[test]
+fn test_clmul() {
+    let mut rng = thread_rng();
+    for _ in 0..1000 {
+        let (x, y) = rng.gen();
+        assert_eq!(clmul_software(x, y), clmul_bitwise(x, y));
+        #[cfg(target_arch = "x86_64")]
+        if crate::aesni::pclmulqdq_detected() {
+            assert_eq!(clmul_hardware(x, y), clmul_bitwise(x, y));
+        }
+    }
+    assert_eq!(clmul_software(u64::MAX, u64::MAX), clmul_bitwise(u64::MAX, u64::MAX));
+}
+
+#[test]
+fn test_gf_mul() {
+    let mut rng = thread_rng();
+    for _ in 0..1000 {
+        let (x, y): ([u8; 16], [u8; 16]) = rng.gen();
+        let product = gf_mul(halves(&x), halves(&y), clmul_software);
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&product[0].to_be_bytes());
+        bytes[8..].copy_from_slice(&product[1].to_be_bytes());
+        assert_eq!(bytes, gf_mul_bitwise(x, y));
+    }
+}
+
+#[test]
+fn test_ghash() {
+    // Test case 2: H = 66e94bd4ef8a2c3b884cfa59ca342b2e and GHASH(H, {}, C) is f38cbb1ad69223dcc3457ae5b6b0f885.
+    let h = hex("66e94bd4ef8a2c3b884cfa59ca342b2e").try_into().unwrap();
+    for hardware in [false, true] {
+        let mut ghash = Ghash::new(h, hardware);
+        ghash.update(&hex("0388dace60b6a392f328c2b971b2fe78"));
+        ghash.update(&lengths(0, 16));
+        assert_eq!(ghash.finalize().to_vec(), hex("f38cbb1ad69223dcc3457ae5b6b0f885"));
+    }
+}
+
+#[test]
+fn test_vectors() {
+    for backend in available_backends() {
+        for [key, nonce, plaintext, aad, ciphertext, tag] in VECTORS.iter() {
+            let gcm = Gcm::with_backend(&crate::parse_key(key).unwrap(), backend);
+            let sealed = [hex(ciphertext), hex(tag)].concat();
+            assert_eq!(gcm.encrypt(&hex(nonce), &hex(aad), &hex(plaintext)), Ok(sealed.clone()), "{} {}", backend.name(), key);
+            assert_eq!(gcm.decrypt(&hex(nonce), &hex(aad), &sealed), Ok(hex(plaintext)));
+        }
+    }
+}
+
+#[test]
+fn test_truncated_tag() {
+    let [key, nonce, plaintext, aad, ciphertext, tag] = VECTORS[3];
+    for bytes in TAG_BYTES.iter().copied() {
+        let gcm = Gcm::new(&crate::parse_key(key).unwrap()).truncate_tag(bytes).unwrap();
+        assert_eq!(gcm.tag_bytes(), bytes);
+        let sealed = [hex(ciphertext), hex(tag)[..bytes].to_vec()].concat();
+        assert_eq!(gcm.encrypt(&hex(nonce), &hex(aad), &hex(plaintext)), Ok(sealed.clone()));
+        assert_eq!(gcm.decrypt(&hex(nonce), &hex(aad), &sealed), Ok(hex(plaintext)));
+    }
+    for bytes in [0, 3, 5, 9, 11, 17] {
+        assert_eq!(Gcm::new(&keygen_128()).truncate_tag(bytes).err(), Some(GcmError::InvalidTagLength(bytes)));
+    }
+}
+
+#[test]
+fn test_authentication() {
+    let gcm = Gcm::new(&keygen_256());
+    let nonce = [7; NONCE_BYTES];
+    let sealed = gcm.encrypt(&nonce, b"associated data", b"attack at dawn").unwrap();
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 0x80;
+        assert_eq!(gcm.decrypt(&nonce, b"associated data", &tampered), Err(GcmError::AuthenticationFailed));
+    }
+    assert_eq!(gcm.decrypt(&nonce, b"associated datA", &sealed), Err(GcmError::AuthenticationFailed));
+    assert_eq!(gcm.decrypt(&[8; NONCE_BYTES], b"associated data", &sealed), Err(GcmError::AuthenticationFailed));
+    assert_eq!(gcm.decrypt(&nonce, b"associated data", &sealed[..sealed.len() - 1]), Err(GcmError::AuthenticationFailed));
+    assert_eq!(gcm.decrypt(&nonce, b"associated data", &sealed[..15]), Err(GcmError::AuthenticationFailed));
+    assert_eq!(Gcm::new(&keygen_256()).decrypt(&nonce, b"associated data", &sealed), Err(GcmError::AuthenticationFailed));
+    assert_eq!(gcm.decrypt(&nonce, b"associated data", &sealed), Ok(b"attack at dawn".to_vec()));
+}
+
+#[test]
+fn test_empty_nonce() {
+    let gcm = Gcm::new(&keygen_128());
+    assert_eq!(gcm.encrypt(&[], &[], b"message"), Err(GcmError::EmptyNonce));
+    assert_eq!(gcm.decrypt(&[], &[], &[0; 16]), Err(GcmError::EmptyNonce));
+}
+
+#[test]
+fn test_backends() {
+    let mut rng = thread_rng();
+    let key = keygen_256();
+    for length in [0, 1, 15, 16, 17, 63, 64, 65, 511, 512, 513, 1000] {
+        let plaintext: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+        let aad: Vec<u8> = (0..rng.gen_range(0, 40)).map(|_| rng.gen()).collect();
+        let nonce: Vec<u8> = (0..rng.gen_range(1, 40)).map(|_| rng.gen()).collect();
+        let sealed = Gcm::with_backend(&key, Backend::Reference).encrypt(&nonce, &aad, &plaintext).unwrap();
+        for backend in available_backends() {
+            let gcm = Gcm::with_backend(&key, backend);
+            assert_eq!(gcm.encrypt(&nonce, &aad, &plaintext).as_ref(), Ok(&sealed), "{}", backend.name());
+            assert_eq!(gcm.decrypt(&nonce, &aad, &sealed), Ok(plaintext.clone()));
+        }
+    }
+}
+
+#[test]
+fn test_counter_wraps() {
+    // inc32 leaves the first 96 bits alone.
+    let mut block = [0xff; 16];
+    inc32(&mut block);
+    assert_eq!(block, [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+}
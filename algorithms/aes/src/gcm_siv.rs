@@ -0,0 +1,230 @@
+//! AES-GCM-SIV (RFC 8452), nonce-misuse-resistant authenticated encryption.
+//!
+//! Every nonce derives its own authentication and encryption keys from the key. The tag is the
+//! encryption of POLYVAL over the associated data, the plaintext and their lengths, mixed with the
+//! nonce, and it is also the initial counter for encrypting the plaintext. A repeated nonce only
+//! reveals whether two messages under it were equal.
+//!
+//! POLYVAL is GHASH with the bytes of each block reversed, so it runs on [`Ghash`] and multiplies
+//! in constant time, with PCLMULQDQ where available, in the same way.
+//!
+//! RFC 8452 authenticates a single associated data string. [`GcmSiv::encrypt_components`] takes
+//! several, as [`Siv`](crate::siv::Siv) does, by prefixing each with its length, a little-endian
+//! 64-bit number, and concatenating them into that string. This encoding is not part of the RFC,
+//! so other implementations only accept its output when given the same encoded string.
+
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::gcm::Ghash;
+use crate::{constant_time_eq, Backend, BlockCipher, Key};
+
+#[cfg(test)]
+mod test;
+
+pub const NONCE_BYTES: usize = 12;
+
+pub const TAG_BYTES: usize = 16;
+
+/// Longest plaintext and associated data: 2^36 bytes.
+pub const MAX_INPUT_BYTES: u64 = 1 << 36;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcmSivError {
+    /// Only AES-128 and AES-256 keys are defined.
+    InvalidKeyLength(usize),
+    InvalidNonceLength(usize),
+    TooLong,
+    /// The tag does not match: the ciphertext, the associated data, the nonce or the key is wrong.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for GcmSivError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GcmSivError::InvalidKeyLength(bytes) => write!(f, "key of {} bytes is not allowed, expected 16 or 32", bytes),
+            GcmSivError::InvalidNonceLength(bytes) => write!(f, "nonce of {} bytes is not allowed, expected {}", bytes, NONCE_BYTES),
+            GcmSivError::TooLong => write!(f, "input is too long"),
+            GcmSivError::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for GcmSivError {}
+
+/// The associated data string of a list of components: each one after its length in bytes, as a
+/// little-endian 64-bit number, so that no two lists give the same string.
+fn encode_components(associated_data: &[&[u8]]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for component in associated_data {
+        encoded.extend_from_slice(&(component.len() as u64).to_le_bytes());
+        encoded.extend_from_slice(component);
+    }
+    encoded
+}
+
+/// POLYVAL of RFC 8452 section 3 under one key: GHASH under mulX_GHASH(ByteReverse(H)) of the
+/// byte-reversed blocks, as appendix A shows.
+#[derive(Debug, Clone)]
+pub struct Polyval {
+    ghash: Ghash,
+}
+
+impl Polyval {
+    /// With PCLMULQDQ if `hardware` and the processor has it.
+    pub fn new(mut h: [u8; 16], hardware: bool) -> Polyval {
+        h.reverse();
+        // Multiplication by x with the bit order of GHASH, where x^127 is the lowest bit.
+        let h = u128::from_be_bytes(h);
+        let h = (h >> 1) ^ ((h & 1) * (0xe1 << 120));
+        Polyval { ghash: Ghash::new(h.to_be_bytes(), hardware) }
+    }
+
+    /// Hashes `data`, padded with zero bytes to a whole number of blocks.
+    pub fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            block.reverse();
+            self.ghash.update(&block);
+        }
+    }
+
+    pub fn finalize(&self) -> [u8; 16] {
+        let mut result = self.ghash.finalize();
+        result.reverse();
+        result
+    }
+}
+
+/// AES-GCM-SIV under one key.
+///
+/// ```
+/// use aes::gcm_siv::{GcmSiv, GcmSivError};
+/// use aes::parse_key;
+///
+/// let gcm_siv = GcmSiv::new(&parse_key("000102030405060708090a0b0c0d0e0f").unwrap()).unwrap();
+/// let nonce = [0; 12];
+/// let sealed = gcm_siv.encrypt(&nonce, b"header", b"message").unwrap();
+/// assert_eq!(sealed.len(), 7 + 16);
+/// assert_eq!(gcm_siv.decrypt(&nonce, b"header", &sealed), Ok(b"message".to_vec()));
+/// assert_eq!(gcm_siv.decrypt(&nonce, b"other header", &sealed), Err(GcmSivError::AuthenticationFailed));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GcmSiv {
+    cipher: BlockCipher,
+    key_bytes: usize,
+}
+
+impl GcmSiv {
+    /// With the [default](Backend::default) backend.
+    pub fn new(key: &Key) -> Result<GcmSiv, GcmSivError> {
+        GcmSiv::with_backend(key, Backend::default())
+    }
+
+    /// Panics if the backend is not [available](Backend::is_available).
+    pub fn with_backend(key: &Key, backend: Backend) -> Result<GcmSiv, GcmSivError> {
+        if let Key::AES192(_) = key {
+            return Err(GcmSivError::InvalidKeyLength(24));
+        }
+        Ok(GcmSiv { cipher: BlockCipher::new(key, backend), key_bytes: key.as_slice().len() })
+    }
+
+    /// The message authentication and encryption keys of `nonce`, RFC 8452 section 4: the first
+    /// halves of the encryptions of a little-endian counter followed by the nonce.
+    fn derive_keys(&self, nonce: &[u8]) -> ([u8; 16], BlockCipher) {
+        let mut blocks: Vec<[u8; 16]> = (0..(2 + self.key_bytes / 8) as u32).map(|i| {
+            let mut block = [0; 16];
+            block[..4].copy_from_slice(&i.to_le_bytes());
+            block[4..].copy_from_slice(nonce);
+            block
+        }).collect();
+        self.cipher.encrypt_blocks(&mut blocks);
+        let halves: Vec<u8> = blocks.iter().flat_map(|block| block[..8].iter().copied()).collect();
+        let (authentication, encryption) = halves.split_at(16);
+        let encryption = Key::from_slice(encryption).unwrap();
+        (authentication.try_into().unwrap(), BlockCipher::new(&encryption, self.cipher.backend()))
+    }
+
+    fn tag(&self, authentication: [u8; 16], encryption: &BlockCipher, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> [u8; 16] {
+        let mut polyval = Polyval::new(authentication, self.cipher.backend() == Backend::AesNi);
+        polyval.update(aad);
+        polyval.update(plaintext);
+        let mut lengths = [0; 16];
+        lengths[..8].copy_from_slice(&(8 * aad.len() as u64).to_le_bytes());
+        lengths[8..].copy_from_slice(&(8 * plaintext.len() as u64).to_le_bytes());
+        polyval.update(&lengths);
+        let mut s = polyval.finalize();
+        for (s, n) in s.iter_mut().zip(nonce.iter()) {
+            *s ^= n;
+        }
+        s[15] &= 0x7f;
+        encryption.encrypt_block(s)
+    }
+
+    /// Counter mode from the tag with its top bit set, the counter being the first 32 bits,
+    /// little-endian.
+    fn ctr(encryption: &BlockCipher, tag: [u8; 16], input: &[u8]) -> Vec<u8> {
+        let mut counter = tag;
+        counter[15] |= 0x80;
+        let start = u32::from_le_bytes(counter[..4].try_into().unwrap());
+        let mut blocks: Vec<[u8; 16]> = (0..input.len().div_ceil(16) as u32).map(|i| {
+            let mut block = counter;
+            block[..4].copy_from_slice(&start.wrapping_add(i).to_le_bytes());
+            block
+        }).collect();
+        encryption.encrypt_blocks(&mut blocks);
+        input.iter().zip(blocks.iter().flatten()).map(|(x, k)| x ^ k).collect()
+    }
+
+    fn check_lengths(nonce: &[u8], aad: &[u8], plaintext: usize) -> Result<(), GcmSivError> {
+        if nonce.len() != NONCE_BYTES {
+            return Err(GcmSivError::InvalidNonceLength(nonce.len()));
+        }
+        if aad.len() as u64 > MAX_INPUT_BYTES || plaintext as u64 > MAX_INPUT_BYTES {
+            return Err(GcmSivError::TooLong);
+        }
+        Ok(())
+    }
+
+    /// Encrypts `plaintext` and authenticates it along with `aad`. Returns the ciphertext, as long
+    /// as the plaintext, followed by the tag.
+    ///
+    /// Nonces should still be unique: a repeated one shows which messages are equal.
+    pub fn encrypt(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, GcmSivError> {
+        GcmSiv::check_lengths(nonce, aad, plaintext.len())?;
+        let (authentication, encryption) = self.derive_keys(nonce);
+        let tag = self.tag(authentication, &encryption, nonce, aad, plaintext);
+        let mut output = GcmSiv::ctr(&encryption, tag, plaintext);
+        output.extend_from_slice(&tag);
+        Ok(output)
+    }
+
+    /// Decrypts the ciphertext at the start of `input` and checks the tag at its end. No plaintext
+    /// is returned unless the tag matches.
+    pub fn decrypt(&self, nonce: &[u8], aad: &[u8], input: &[u8]) -> Result<Vec<u8>, GcmSivError> {
+        if input.len() < TAG_BYTES {
+            return Err(GcmSivError::AuthenticationFailed);
+        }
+        let (ciphertext, tag) = input.split_at(input.len() - TAG_BYTES);
+        GcmSiv::check_lengths(nonce, aad, ciphertext.len())?;
+        let (authentication, encryption) = self.derive_keys(nonce);
+        let plaintext = GcmSiv::ctr(&encryption, tag.try_into().unwrap(), ciphertext);
+        if !constant_time_eq(&self.tag(authentication, &encryption, nonce, aad, &plaintext), tag) {
+            return Err(GcmSivError::AuthenticationFailed);
+        }
+        Ok(plaintext)
+    }
+
+    /// [`encrypt`](GcmSiv::encrypt) with the components of `associated_data`, which may be empty,
+    /// encoded into one string. Moving bytes between components changes the tag.
+    pub fn encrypt_components(&self, nonce: &[u8], associated_data: &[&[u8]], plaintext: &[u8]) -> Result<Vec<u8>, GcmSivError> {
+        self.encrypt(nonce, &encode_components(associated_data), plaintext)
+    }
+
+    /// [`decrypt`](GcmSiv::decrypt) with the components of `associated_data` encoded as by
+    /// [`encrypt_components`](GcmSiv::encrypt_components).
+    pub fn decrypt_components(&self, nonce: &[u8], associated_data: &[&[u8]], input: &[u8]) -> Result<Vec<u8>, GcmSivError> {
+        self.decrypt(nonce, &encode_components(associated_data), input)
+    }
+}
@@ -0,0 +1,364 @@
+use rand::{thread_rng, Rng};
+
+use crate::{keygen_128, keygen_192, keygen_256, parse_key, Backend};
+
+use super::*;
+
+/// Key, nonce, associated data, plaintext and ciphertext followed by the tag, from RFC 8452
+/// appendix C: the AES-128 and AES-256 examples of C.1 and C.2, then the two of C.3 whose counters
+/// wrap around.
+const VECTORS: [[&str; 5]; 34] = [
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "",
+        "dc20e2d83f25705bb49e439eca56de25",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "0100000000000000",
+        "b5d839330ac7b786578782fff6013b815b287c22493a364c",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "010000000000000000000000",
+        "7323ea61d05932260047d942a4978db357391a0bc4fdec8b0d106639",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "01000000000000000000000000000000",
+        "743f7c8077ab25f8624e2e948579cf77303aaf90f6fe21199c6068577437a0c4",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "0100000000000000000000000000000002000000000000000000000000000000",
+        "84e07e62ba83a6585417245d7ec413a9fe427d6315c09b57ce45f2e3936a94451a8e45dcd4578c667cd86847bf6155ff",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "010000000000000000000000000000000200000000000000000000000000000003000000000000000000000000000000",
+        "3fd24ce1f5a67b75bf2351f181a475c7b800a5b4d3dcf70106b1eea82fa1d64df42bf7226122fa92e17a40eeaac1201b5e6e311dbf395d35b0fe39c2714388f8",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "01000000000000000000000000000000020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000",
+        "2433668f1058190f6d43e360f4f35cd8e475127cfca7028ea8ab5c20f7ab2af02516a2bdcbc08d521be37ff28c152bba36697f25b4cd169c6590d1dd39566d3f8a263dd317aa88d56bdf3936dba75bb8",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "0200000000000000",
+        "1e6daba35669f4273b0a1a2560969cdf790d99759abd1508",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "020000000000000000000000",
+        "296c7889fd99f41917f4462008299c5102745aaa3a0c469fad9e075a",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "02000000000000000000000000000000",
+        "e2b0c5da79a901c1745f700525cb335b8f8936ec039e4e4bb97ebd8c4457441f",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "0200000000000000000000000000000003000000000000000000000000000000",
+        "620048ef3c1e73e57e02bb8562c416a319e73e4caac8e96a1ecb2933145a1d71e6af6a7f87287da059a71684ed3498e1",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000",
+        "50c8303ea93925d64090d07bd109dfd9515a5a33431019c17d93465999a8b0053201d723120a8562b838cdff25bf9d1e6a8cc3865f76897c2e4b245cf31c51f2",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "02000000000000000000000000000000030000000000000000000000000000000400000000000000000000000000000005000000000000000000000000000000",
+        "2f5c64059db55ee0fb847ed513003746aca4e61c711b5de2e7a77ffd02da42feec601910d3467bb8b36ebbaebce5fba30d36c95f48a3e7980f0e7ac299332a80cdc46ae475563de037001ef84ae21744",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "010000000000000000000000",
+        "02000000",
+        "a8fe3e8707eb1f84fb28f8cb73de8e99e2f48a14",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "010000000000000000000000000000000000",
+        "0200000000000000000000000000000000000000",
+        "75570015527e9a114c71f09f6f041742532da58b661dc24f593ea183d7790f01074311c2",
+    ],
+    [
+        "01000000000000000000000000000000",
+        "030000000000000000000000",
+        "0100000000000000000000000000000000000000",
+        "020000000000000000000000000000000000",
+        "a02cb2e21e623fc478ba122b4046e97683138b5c82056622f3bf764061741289dfca",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "",
+        "07f5f4169bbf55a8400cd47ea6fd400f",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "0100000000000000",
+        "c2ef328e5c71c83b843122130f7364b761e0b97427e3df28",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "010000000000000000000000",
+        "9aab2aeb3faa0a34aea8e2b18ca50da9ae6559e48fd10f6e5c9ca17e",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "01000000000000000000000000000000",
+        "85a01b63025ba19b7fd3ddfc033b3e76c9eac6fa700942702e90862383c6c366",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "0100000000000000000000000000000002000000000000000000000000000000",
+        "4a6a9db4c8c6549201b9edb53006cba821ec9cf850948a7c86c68ac7539d027fe819e63abcd020b006a976397632eb5d",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "010000000000000000000000000000000200000000000000000000000000000003000000000000000000000000000000",
+        "c00d121893a9fa603f48ccc1ca3c57ce7499245ea0046db16c53c7c66fe717e39cf6c748837b61f6ee3adcee17534ed5790bc96880a99ba804bd12c0e6a22cc4",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "",
+        "01000000000000000000000000000000020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000",
+        "c2d5160a1f8683834910acdafc41fbb1632d4a353e8b905ec9a5499ac34f96c7e1049eb080883891a4db8caaa1f99dd004d80487540735234e3744512c6f90ce112864c269fc0d9d88c61fa47e39aa08",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "0200000000000000",
+        "1de22967237a813291213f267e3b452f02d01ae33e4ec854",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "020000000000000000000000",
+        "163d6f9cc1b346cd453a2e4cc1a4a19ae800941ccdc57cc8413c277f",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "02000000000000000000000000000000",
+        "c91545823cc24f17dbb0e9e807d5ec17b292d28ff61189e8e49f3875ef91aff7",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "0200000000000000000000000000000003000000000000000000000000000000",
+        "07dad364bfc2b9da89116d7bef6daaaf6f255510aa654f920ac81b94e8bad365aea1bad12702e1965604374aab96dbbc",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000",
+        "c67a1f0f567a5198aa1fcc8e3f21314336f7f51ca8b1af61feac35a86416fa47fbca3b5f749cdf564527f2314f42fe2503332742b228c647173616cfd44c54eb",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "01",
+        "02000000000000000000000000000000030000000000000000000000000000000400000000000000000000000000000005000000000000000000000000000000",
+        "67fd45e126bfb9a79930c43aad2d36967d3f0e4d217c1e551f59727870beefc98cb933a8fce9de887b1e40799988db1fc3f91880ed405b2dd298318858467c895bde0285037c5de81e5b570a049b62a0",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "010000000000000000000000",
+        "02000000",
+        "22b3f4cd1835e517741dfddccfa07fa4661b74cf",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "010000000000000000000000000000000000",
+        "0200000000000000000000000000000000000000",
+        "60b38293df6a8646d7af55c204170f53487bdd398e208c3637a015bfbf6c9522180ab5f5",
+    ],
+    [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "030000000000000000000000",
+        "0100000000000000000000000000000000000000",
+        "020000000000000000000000000000000000",
+        "f3d1fcbee3607b5f24c9462d8fd4c97505805b1f0ed3564a7a1b45183ec4215f1540",
+    ],
+    [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "",
+        "000000000000000000000000000000004db923dc793ee6497c76dcc03a98e108",
+        "f3f80f2cf0cb2dd9c5984fcda908456cc537703b5ba70324a6793a7bf218d3eaffffffff000000000000000000000000",
+    ],
+    [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "000000000000000000000000",
+        "",
+        "eb3640277c7ffd1303c7a542d02d3e4c0000000000000000",
+        "18ce4f0b8cb4d0cac65fea8f79257b20888e53e72299e56dffffffff000000000000000000000000",
+    ],
+];
+
+fn hex(value: &str) -> Vec<u8> {
+    (0..value.len()).step_by(2).map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap()).collect()
+}
+
+fn available_backends() -> impl Iterator<Item = Backend> {
+    Backend::ALL.iter().copied().filter(Backend::is_available)
+}
+
+#[test]
+fn test_polyval() {
+    // RFC 8452 appendix A.
+    let h = hex("25629347589242761d31f826ba4b757b").try_into().unwrap();
+    for hardware in [false, true] {
+        let mut polyval = Polyval::new(h, hardware);
+        polyval.update(&hex("4f4f95668c83dfb6401762bb2d01a262d1a24ddd2721d006bbe45f20d3c9f362"));
+        assert_eq!(polyval.finalize().to_vec(), hex("f7a3b47b846119fae5b7866cf5e5b77e"));
+    }
+}
+
+#[test]
+fn test_derive_keys() {
+    // The first example of RFC 8452 appendix C.1.
+    let gcm_siv = GcmSiv::new(&parse_key("01000000000000000000000000000000").unwrap()).unwrap();
+    let (authentication, encryption) = gcm_siv.derive_keys(&hex("030000000000000000000000"));
+    assert_eq!(authentication.to_vec(), hex("d9b360279694941ac5dbc6987ada7377"));
+    let expected = BlockCipher::new(&parse_key("4004a0dcd862f2a57360219d2d44ef6c").unwrap(), Backend::Reference);
+    assert_eq!(encryption.encrypt_block([0; 16]), expected.encrypt_block([0; 16]));
+}
+
+#[test]
+fn test_vectors() {
+    for backend in available_backends() {
+        for [key, nonce, aad, plaintext, sealed] in VECTORS.iter() {
+            let gcm_siv = GcmSiv::with_backend(&parse_key(key).unwrap(), backend).unwrap();
+            assert_eq!(gcm_siv.encrypt(&hex(nonce), &hex(aad), &hex(plaintext)), Ok(hex(sealed)), "{} {} {}", backend.name(), key, plaintext);
+            assert_eq!(gcm_siv.decrypt(&hex(nonce), &hex(aad), &hex(sealed)), Ok(hex(plaintext)));
+        }
+    }
+}
+
+#[test]
+fn test_parameters() {
+    assert_eq!(GcmSiv::new(&keygen_192()).err(), Some(GcmSivError::InvalidKeyLength(24)));
+    let gcm_siv = GcmSiv::new(&keygen_128()).unwrap();
+    for bytes in [0, 8, 11, 13, 16] {
+        assert_eq!(gcm_siv.encrypt(&vec![0; bytes], &[], b"message"), Err(GcmSivError::InvalidNonceLength(bytes)));
+        assert_eq!(gcm_siv.decrypt(&vec![0; bytes], &[], &[0; 16]), Err(GcmSivError::InvalidNonceLength(bytes)));
+    }
+}
+
+#[test]
+fn test_authentication() {
+    let gcm_siv = GcmSiv::new(&keygen_256()).unwrap();
+    let nonce = [7; NONCE_BYTES];
+    let sealed = gcm_siv.encrypt(&nonce, b"associated data", b"attack at dawn").unwrap();
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 0x01;
+        assert_eq!(gcm_siv.decrypt(&nonce, b"associated data", &tampered), Err(GcmSivError::AuthenticationFailed));
+    }
+    assert_eq!(gcm_siv.decrypt(&nonce, b"associated datA", &sealed), Err(GcmSivError::AuthenticationFailed));
+    assert_eq!(gcm_siv.decrypt(&[8; NONCE_BYTES], b"associated data", &sealed), Err(GcmSivError::AuthenticationFailed));
+    assert_eq!(gcm_siv.decrypt(&nonce, b"associated data", &sealed[..15]), Err(GcmSivError::AuthenticationFailed));
+    assert_eq!(gcm_siv.decrypt(&nonce, b"associated data", &sealed), Ok(b"attack at dawn".to_vec()));
+}
+
+/// Associated data components are encoded so that where one ends and the next starts counts.
+#[test]
+fn test_components() {
+    let gcm_siv = GcmSiv::new(&keygen_128()).unwrap();
+    let nonce = [7; NONCE_BYTES];
+    let sealed = gcm_siv.encrypt_components(&nonce, &[b"ab", b"c"], b"message").unwrap();
+    let other = gcm_siv.encrypt_components(&nonce, &[b"a", b"bc"], b"message").unwrap();
+    assert_ne!(sealed[sealed.len() - TAG_BYTES..], other[other.len() - TAG_BYTES..]);
+    assert_eq!(gcm_siv.decrypt_components(&nonce, &[b"a", b"bc"], &sealed), Err(GcmSivError::AuthenticationFailed));
+    for associated_data in [&[&b"abc"[..]][..], &[b"c", b"ab"], &[b"ab", b"c", b""], &[b"ab"], &[]] {
+        assert_eq!(gcm_siv.decrypt_components(&nonce, associated_data, &sealed), Err(GcmSivError::AuthenticationFailed));
+    }
+    assert_eq!(gcm_siv.decrypt_components(&nonce, &[b"ab", b"c"], &sealed), Ok(b"message".to_vec()));
+    assert_eq!(gcm_siv.decrypt(&nonce, b"abc", &sealed), Err(GcmSivError::AuthenticationFailed));
+    assert_eq!(gcm_siv.decrypt(&nonce, &encode_components(&[b"ab", b"c"]), &sealed), Ok(b"message".to_vec()));
+    assert_eq!(encode_components(&[b"ab", b"c"]), hex("02000000000000006162010000000000000063"));
+    assert_eq!(encode_components(&[]), Vec::<u8>::new());
+}
+
+/// A repeated nonce shows equal messages and nothing else.
+#[test]
+fn test_nonce_reuse() {
+    let gcm_siv = GcmSiv::new(&keygen_128()).unwrap();
+    let nonce = [0; NONCE_BYTES];
+    let a = gcm_siv.encrypt(&nonce, &[], b"attack at dawn").unwrap();
+    assert_eq!(gcm_siv.encrypt(&nonce, &[], b"attack at dawn"), Ok(a.clone()));
+    let b = gcm_siv.encrypt(&nonce, &[], b"attack at dusk").unwrap();
+    // Unlike GCM, the keystreams differ, so the xor of the ciphertexts is not that of the plaintexts.
+    let xor: Vec<u8> = a.iter().zip(b.iter()).map(|(a, b)| a ^ b).take(14).collect();
+    assert_ne!(xor[..11], [0; 11]);
+}
+
+#[test]
+fn test_backends() {
+    let mut rng = thread_rng();
+    let key = keygen_256();
+    for length in [0, 1, 15, 16, 17, 63, 64, 65, 300] {
+        let plaintext: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+        let aad: Vec<u8> = (0..rng.gen_range(0, 40)).map(|_| rng.gen()).collect();
+        let nonce: [u8; NONCE_BYTES] = rng.gen();
+        let sealed = GcmSiv::with_backend(&key, Backend::Reference).unwrap().encrypt(&nonce, &aad, &plaintext).unwrap();
+        for backend in available_backends() {
+            let gcm_siv = GcmSiv::with_backend(&key, backend).unwrap();
+            assert_eq!(gcm_siv.encrypt(&nonce, &aad, &plaintext).as_ref(), Ok(&sealed), "{}", backend.name());
+            assert_eq!(gcm_siv.decrypt(&nonce, &aad, &sealed), Ok(plaintext.clone()));
+        }
+    }
+}
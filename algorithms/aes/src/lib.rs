@@ -1,10 +1,36 @@
+//! AES block cipher (FIPS-197) with 128, 192 and 256-bit keys.
+//!
+//! ```
+//! use aes::{parse_key, Aes};
+//!
+//! let key = parse_key("000102030405060708090a0b0c0d0e0f").unwrap();
+//! let aes = Aes::new(&key);
+//! let plaintext = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+//! let ciphertext = aes.encrypt_block(plaintext);
+//! assert_eq!(ciphertext, [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a]);
+//! assert_eq!(aes.decrypt_block(ciphertext), plaintext);
+//! ```
+
 use std::convert::TryInto;
+use std::fmt;
 use std::ops::Mul;
+use std::str::FromStr;
+
 use regex::Regex;
-use rand::{thread_rng, Rng};
+
+#[cfg(target_arch = "x86_64")]
+pub mod aesni;
+pub mod bitslice;
+pub mod ccm;
+pub mod cmac;
+pub mod gcm;
+pub mod gcm_siv;
+pub mod siv;
+pub mod ttable;
 
 #[cfg(test)]
 mod test;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Key {
     AES128([u8; 16]),
@@ -13,7 +39,7 @@ pub enum Key {
 }
 
 impl Key {
-    pub(crate)fn as_slice(&self) -> &[u8] {
+    pub fn as_slice(&self) -> &[u8] {
         match self {
             Key::AES128(key) => key,
             Key::AES192(key) => key,
@@ -21,63 +47,49 @@ impl Key {
         }
     }
 
-    fn to_u32(&self) -> Vec<u32> {
-        let slice = self.as_slice();
-        let mut vec = Vec::with_capacity(slice.len() / 4);
-        vec.resize(slice.len() / 4, 0);
-        let mut slice_index = 0;
-        for i in 0..vec.len() {
-            for _ in 0..4 {
-                vec[i] <<= 1;
-                vec[i] |= slice[slice_index] as u32;
-                slice_index += 1;
-            }
+    /// A key of 16, 24 or 32 bytes, `None` for other lengths.
+    pub fn from_slice(key: &[u8]) -> Option<Key> {
+        match key.len() {
+            16 => Some(Key::AES128(key.try_into().unwrap())),
+            24 => Some(Key::AES192(key.try_into().unwrap())),
+            32 => Some(Key::AES256(key.try_into().unwrap())),
+            _ => None,
         }
-        vec
     }
+
+    /// The name of the cipher the key is for, as written in key files.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Key::AES128(_) => "AES-128",
+            Key::AES192(_) => "AES-192",
+            Key::AES256(_) => "AES-256",
+        }
+    }
+
+    /// The key as the words w\[0\]..w\[Nk - 1\] of FIPS-197, four bytes each, first byte most
+    /// significant.
+    fn to_u32(&self) -> Vec<u32> {
+        self.as_slice().chunks(4).map(|word| u32::from_be_bytes(word.try_into().unwrap())).collect()
+    }
+}
+
+/// `N` bytes from the random number generator of the operating system.
+fn random_key<const N: usize>() -> [u8; N] {
+    let mut key = [0; N];
+    getrandom::getrandom(&mut key).expect("failed to get random bytes from the operating system");
+    key
 }
 
 pub fn keygen_128() -> Key {
-    let mut rng = thread_rng();
-    let mut key: u128 = rng.gen();
-    let mut result = [0; 128 / 8];
-    for i in 0..16 {
-        result[i] = (key & 0xff) as u8;
-        key >>= 1;
-    }
-    Key::AES128(result)
+    Key::AES128(random_key())
 }
 
 pub fn keygen_192() -> Key {
-    let mut rng = thread_rng();
-    let mut key: u128 = rng.gen();
-    let mut result = [0; 196 / 8];
-    for i in 0..16 {
-        result[i] = (key & 0xff) as u8;
-        key >>= 1;
-    }
-    let mut key: u64 = rng.gen();
-    for i in 16..24 {
-        result[i] = (key & 0xff) as u8;
-        key >>= 1;
-    }
-    Key::AES192(result)
+    Key::AES192(random_key())
 }
 
 pub fn keygen_256() -> Key {
-    let mut rng = thread_rng();
-    let mut key: u128 = rng.gen();
-    let mut result = [0; 256 / 8];
-    for i in 0..16 {
-        result[i] = (key & 0xff) as u8;
-        key >>= 1;
-    }
-    let mut key: u128 = rng.gen();
-    for i in 16..32 {
-        result[i] = (key & 0xff) as u8;
-        key >>= 1;
-    }
-    Key::AES256(result)
+    Key::AES256(random_key())
 }
 
 #[derive(Debug, PartialEq)]
@@ -92,9 +104,9 @@ pub fn parse_key(key: &str) -> Result<Key, ParseKeyError> {
     let mut chars = key.chars();
     fn char_to_u8(input: char) -> u8 {
         match input {
-            c @ '0'..='9' => { c as u8 - '0' as u8 }
-            c @ 'a'..='f' => { c as u8 - 'a' as u8 + 10 }
-            c @ 'A'..='F' => { c as u8 - 'A' as u8 + 10 }
+            c @ '0'..='9' => { c as u8 - b'0' }
+            c @ 'a'..='f' => { c as u8 - b'a' + 10 }
+            c @ 'A'..='F' => { c as u8 - b'A' + 10 }
             _ => unreachable!()
         }
     }
@@ -103,12 +115,7 @@ pub fn parse_key(key: &str) -> Result<Key, ParseKeyError> {
         byte |= if let Some(c) = chars.next() { char_to_u8(c) } else { return Err(ParseKeyError::InvalidKeyStringFormat); };
         result.push(byte);
     }
-    match result.len() {
-        16 => { Ok(Key::AES128(result.try_into().unwrap())) }
-        24 => { Ok(Key::AES192(result.try_into().unwrap())) }
-        32 => { Ok(Key::AES256(result.try_into().unwrap())) }
-        _ => Err(ParseKeyError::InvalidKeyStringFormat)
-    }
+    Key::from_slice(&result).ok_or(ParseKeyError::InvalidKeyStringFormat)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -140,8 +147,9 @@ impl Mul for Polynomial {
             result |= (c as u32) << i;
             i += 1;
         }
-        let mut modulo = 0b100011011_0000000;
-        let mut mask = 0b100000000_0000000;
+        // x^8 + x^4 + x^3 + x + 1, aligned with the highest possible term x^14.
+        let mut modulo = 0x11b << 6;
+        let mut mask = 0x100 << 6;
         while result > 255 {
             if (result & mask) != 0 {
                 result ^= modulo;
@@ -154,109 +162,36 @@ impl Mul for Polynomial {
 }
 
 fn rot_word(input: u32) -> u32 {
-    input << 8 | input >> 24
+    input.rotate_left(8)
 }
 
+/// The S-box of FIPS-197 figure 7.
+pub(crate) const S_BOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
 fn sub_byte(input: u8) -> u8 {
-    const TABLE: [u8; 256] = [
-        0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
-        0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
-        0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
-        0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
-        0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
-        0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
-        0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
-        0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
-        0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
-        0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
-        0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
-        0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
-        0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
-        0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
-        0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
-        0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
-    ];
-    TABLE[input as usize]
+    S_BOX[input as usize]
 }
 
 fn sub_word(input: u32) -> u32 {
-    (sub_byte(((input >> 24) & 0xff) as u8) as u32) << 24 |
-        (sub_byte(((input >> 16) & 0xff) as u8) as u32) << 16 |
-        (sub_byte(((input >> 8) as u8) & 0xff) as u32) << 8 |
-        (sub_byte((input & 0xff) as u8) as u32)
-}
-
-// fn inner(key: &mut [u32], key_count: usize) -> Vec<[u32; 4]> {
-//     debug_assert!(key.len() == 4 || key.len() == 6 || key.len() == 8);
-//     debug_assert!(key_count == 11 || key_count == 13 || key_count == 15);
-//     let mut key_words = Vec::with_capacity((key_count * 4 + key.len() - 1) / key.len());
-//     let mut rcon = Polynomial(1);
-//     loop {
-//         for j in key.iter() {
-//             key_words.push(*j);
-//         }
-//         if key_words.len() >= key_count * 4 { break; }
-//         let mut tmp = sub_word(rot_word(key[3])) ^ ((rcon.0 as u32) << 24);
-//         rcon = rcon * Polynomial(0b10);
-//         if key.len() == 8 {
-//             for i in 0..4 {
-//                 key[i] ^= tmp;
-//                 tmp = key[i];
-//             }
-//             tmp = sub_word(tmp);
-//             for i in 4..8 {
-//                 key[i] ^= tmp;
-//                 tmp = key[i];
-//             }
-//         } else {
-//             for i in 0..key.len() {
-//                 key[i] ^= tmp;
-//                 tmp = key[i];
-//             }
-//         }
-//     }
-//     let mut sub_key = Vec::with_capacity(key_count);
-//     for i in 0..key_count {
-//         sub_key[i] = [
-//             key_words[i * 4],
-//             key_words[i * 4 + 1],
-//             key_words[i * 4 + 2],
-//             key_words[i * 4 + 3],
-//         ];
-//     }
-//     sub_key
-// }
-//
-// fn generate_sub_key_192(mut key: [u32; 6]) -> [[u32; 4]; 12] {
-//     let mut key_words = Vec::with_capacity(40);
-//     let mut rcon = Polynomial(1);
-//     loop {
-//         for j in 0..4 {
-//             key_words.push(key[j]);
-//         }
-//         if key_words.len() >= 40 { break; }
-//         let mut tmp = sub_word(rot_word(key[3])) ^ ((rcon.0 as u32) << 24);
-//         rcon = rcon * Polynomial(0b10);
-//         for i in 0..4 {
-//             key[i] ^= tmp;
-//             tmp = key[i];
-//         }
-//     }
-//     let mut sub_key = Vec::with_capacity(10);
-//     for i in 0..10 {
-//         sub_key[i] = [
-//             key_words[i * 4],
-//             key_words[i * 4 + 1],
-//             key_words[i * 4 + 2],
-//             key_words[i * 4 + 3],
-//         ];
-//     }
-//     sub_key.try_into().unwrap()
-// }
-//
-// fn generate_sub_key_256(key: [u32; 8]) -> [[u32; 4]; 14] {
-//     unimplemented!()
-// }
+    u32::from_be_bytes(input.to_be_bytes().map(sub_byte))
+}
 
 #[derive(Debug, Clone, PartialEq)]
 enum SubKey {
@@ -266,18 +201,24 @@ enum SubKey {
 }
 
 impl SubKey {
+    /// The round keys as the words w\[4i\]..w\[4i + 3\] of FIPS-197, one column each.
+    fn words(&self) -> &[[u32; 4]] {
+        match self {
+            SubKey::AES128(slice) => slice,
+            SubKey::AES192(slice) => slice,
+            SubKey::AES256(slice) => slice,
+        }
+    }
+
+    /// The round keys with one row of the state per word, as the reference code keeps the state.
     fn transpose(&self) -> Vec<[u32; 4]> {
-        let (slice, len): (&[[u32; 4]], _) = match self {
-            SubKey::AES128(slice) => (slice, 11),
-            SubKey::AES192(slice) => (slice, 13),
-            SubKey::AES256(slice) => (slice, 15),
-        };
-        let mut vec = Vec::with_capacity(len);
+        let slice = self.words();
+        let mut vec = Vec::with_capacity(slice.len());
         for x in slice {
             let mut matrix = [0; 4];
-            for r in 0..4 {
-                for c in 0..4 {
-                    matrix[r] |= ((x[c] >> ((3 - r) * 8)) & 0xff) << ((3 - c) * 8);
+            for (r, row) in matrix.iter_mut().enumerate() {
+                for (c, word) in x.iter().enumerate() {
+                    *row |= ((word >> ((3 - r) * 8)) & 0xff) << ((3 - c) * 8);
                 }
             }
             vec.push(matrix);
@@ -286,64 +227,55 @@ impl SubKey {
     }
 }
 
+/// KeyExpansion of FIPS-197 section 5.2: the round keys as groups of four words.
 fn generate_sub_key(key: &Key) -> SubKey {
-    fn inner(key: &mut [u32], key_count: usize) -> Vec<[u32; 4]> {
+    expand_key(key, sub_word)
+}
+
+/// KeyExpansion with SubWord computed by `sub_word`, so that engines which must not look up
+/// tables with the key can bring their own.
+fn expand_key(key: &Key, sub_word: fn(u32) -> u32) -> SubKey {
+    let inner = |key: &[u32], rounds: usize| -> Vec<[u32; 4]> {
         debug_assert!(key.len() == 4 || key.len() == 6 || key.len() == 8);
-        debug_assert!(key_count == 11 || key_count == 13 || key_count == 15);
-        let mut key_words = Vec::with_capacity((key_count * 4 + key.len() - 1) / key.len());
+        let nk = key.len();
+        let mut words = key.to_vec();
         let mut rcon = Polynomial(1);
-        loop {
-            for j in key.iter() {
-                key_words.push(*j);
-            }
-            if key_words.len() >= key_count * 4 { break; }
-            let mut tmp = sub_word(rot_word(key[3])) ^ ((rcon.0 as u32) << 24);
-            rcon = rcon * Polynomial(0b10);
-            if key.len() == 8 {
-                for i in 0..4 {
-                    key[i] ^= tmp;
-                    tmp = key[i];
-                }
-                tmp = sub_word(tmp);
-                for i in 4..8 {
-                    key[i] ^= tmp;
-                    tmp = key[i];
-                }
-            } else {
-                for i in 0..key.len() {
-                    key[i] ^= tmp;
-                    tmp = key[i];
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+            match i % nk {
+                0 => {
+                    temp = sub_word(rot_word(temp)) ^ (rcon.0 as u32) << 24;
+                    rcon = rcon * Polynomial(0b10);
                 }
+                4 if nk > 6 => temp = sub_word(temp),
+                _ => {}
             }
+            words.push(words[i - nk] ^ temp);
         }
-        let mut sub_key = Vec::with_capacity(key_count);
-        for i in 0..key_count {
-            sub_key.push([
-                key_words[i * 4],
-                key_words[i * 4 + 1],
-                key_words[i * 4 + 2],
-                key_words[i * 4 + 3],
-            ]);
-        }
-        sub_key
-    }
+        words.chunks(4).map(|round_key| round_key.try_into().unwrap()).collect()
+    };
     match key {
-        Key::AES128(_) => SubKey::AES128(inner(&mut key.to_u32(), 11).try_into().unwrap()),
-        Key::AES192(_) => SubKey::AES192(inner(&mut key.to_u32(), 13).try_into().unwrap()),
-        Key::AES256(_) => SubKey::AES256(inner(&mut key.to_u32(), 15).try_into().unwrap()),
+        Key::AES128(_) => SubKey::AES128(inner(&key.to_u32(), 10).try_into().unwrap()),
+        Key::AES192(_) => SubKey::AES192(inner(&key.to_u32(), 12).try_into().unwrap()),
+        Key::AES256(_) => SubKey::AES256(inner(&key.to_u32(), 14).try_into().unwrap()),
     }
 }
 
 fn sub_bytes(state: &mut [u32; 4]) {
-    for i in 0..4 {
-        state[i] = sub_word(state[i]);
+    for row in state.iter_mut() {
+        *row = sub_word(*row);
     }
 }
 
 fn shift_rows(state: &mut [u32; 4]) {
-    state[1] = state[1] << 08 | state[1] >> 24;
-    state[2] = state[2] << 16 | state[2] >> 16;
-    state[3] = state[3] << 24 | state[3] >> 08;
+    state[1] = state[1].rotate_left(8);
+    state[2] = state[2].rotate_left(16);
+    state[3] = state[3].rotate_left(24);
+}
+
+/// Multiplies every byte of the row `word` by `c`.
+fn mul_word(word: u32, c: Polynomial) -> u32 {
+    u32::from_be_bytes(word.to_be_bytes().map(|b| (Polynomial(b) * c).0))
 }
 
 fn mix_columns(state: &mut [u32; 4]) {
@@ -354,63 +286,58 @@ fn mix_columns(state: &mut [u32; 4]) {
         [Polynomial(0b11), Polynomial(0b01), Polynomial(0b01), Polynomial(0b10)],
     ];
     let mut result = [0; 4];
-    for r in 0..4 {
-        for d in 0..4 {
-            result[r] ^=
-                ((Polynomial(((state[d] >> 24) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 24 |
-                    ((Polynomial(((state[d] >> 16) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 16 |
-                    ((Polynomial(((state[d] >> 08) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 08 |
-                    ((Polynomial(((state[d] >> 00) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 00;
+    for (row, coefficients) in result.iter_mut().zip(MATRIX.iter()) {
+        for (&word, &c) in state.iter().zip(coefficients.iter()) {
+            *row ^= mul_word(word, c);
         }
     }
     *state = result;
 }
 
 fn add_round_key(state: &mut [u32; 4], key: &[u32; 4]) {
-    for i in 0..4 {
-        state[i] ^= key[i];
+    for (row, key) in state.iter_mut().zip(key.iter()) {
+        *row ^= key;
     }
 }
 
+/// The inverse S-box of FIPS-197 figure 14.
+pub(crate) const INV_S_BOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
 fn inv_sub_byte(input: u8) -> u8 {
-    const TABLE: [u8; 256] = [
-        0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
-        0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
-        0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
-        0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
-        0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
-        0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
-        0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
-        0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
-        0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
-        0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
-        0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
-        0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
-        0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
-        0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
-        0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
-        0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
-    ];
-    TABLE[input as usize]
+    INV_S_BOX[input as usize]
 }
 
 fn inv_sub_word(input: u32) -> u32 {
-    (inv_sub_byte(((input >> 24) & 0xff) as u8) as u32) << 24 |
-        (inv_sub_byte(((input >> 16) & 0xff) as u8) as u32) << 16 |
-        (inv_sub_byte(((input >> 8) as u8) & 0xff) as u32) << 8 |
-        (inv_sub_byte((input & 0xff) as u8) as u32)
+    u32::from_be_bytes(input.to_be_bytes().map(inv_sub_byte))
 }
 
 fn inv_sub_bytes(state: &mut [u32; 4]) {
-    for i in 0..4 {
-        state[i] = inv_sub_word(state[i]);
+    for row in state.iter_mut() {
+        *row = inv_sub_word(*row);
     }
 }
 
 fn inv_shift_rows(state: &mut [u32; 4]) {
-    state[1] = state[1] >> 08 | state[1] << 24;
-    state[2] = state[2] >> 16 | state[2] << 16;
-    state[3] = state[3] >> 24 | state[3] << 08;
+    state[1] = state[1].rotate_right(8);
+    state[2] = state[2].rotate_right(16);
+    state[3] = state[3].rotate_right(24);
 }
 
 fn inv_mix_columns(state: &mut [u32; 4]) {
@@ -421,13 +348,9 @@ fn inv_mix_columns(state: &mut [u32; 4]) {
         [Polynomial(0b1011), Polynomial(0b1101), Polynomial(0b1001), Polynomial(0b1110)],
     ];
     let mut result = [0; 4];
-    for r in 0..4 {
-        for d in 0..4 {
-            result[r] ^=
-                ((Polynomial(((state[d] >> 24) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 24 |
-                    ((Polynomial(((state[d] >> 16) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 16 |
-                    ((Polynomial(((state[d] >> 08) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 08 |
-                    ((Polynomial(((state[d] >> 00) & 0xff) as u8) * MATRIX[r][d]).0 as u32) << 00;
+    for (row, coefficients) in result.iter_mut().zip(MATRIX.iter()) {
+        for (&word, &c) in state.iter().zip(coefficients.iter()) {
+            *row ^= mul_word(word, c);
         }
     }
     *state = result;
@@ -443,7 +366,7 @@ fn encrypt_inner(mut state: [u32; 4], sub_key: &[[u32; 4]]) -> [u32; 4] {
     while let Some(key) = key_iter.next() {
         sub_bytes(&mut state);
         shift_rows(&mut state);
-        if let Some(_) = key_iter.peek() {
+        if key_iter.peek().is_some() {
             mix_columns(&mut state);
         }
         add_round_key(&mut state, key);
@@ -458,7 +381,7 @@ fn decrypt_inner(mut state: [u32; 4], sub_key: &[[u32; 4]]) -> [u32; 4] {
     inv_add_round_key(&mut state, key);
     inv_shift_rows(&mut state);
     inv_sub_bytes(&mut state);
-    while let Some(key) = key_iter.next() {
+    for key in key_iter {
         inv_add_round_key(&mut state, key);
         inv_mix_columns(&mut state);
         inv_shift_rows(&mut state);
@@ -468,12 +391,13 @@ fn decrypt_inner(mut state: [u32; 4], sub_key: &[[u32; 4]]) -> [u32; 4] {
     state
 }
 
+/// The state of FIPS-197 section 3.4 for a block, one row per word. Missing bytes are zero.
 fn pick_state_from_slice(slice: &[u8]) -> [u32; 4] {
     let mut state = [0; 4];
     for i in 0..4 {
-        for j in 0..4 {
-            state[j] <<= 8;
-            state[j] |= slice.get(i * 4 + j).map(|v| *v as u32).unwrap_or(0);
+        for (j, row) in state.iter_mut().enumerate() {
+            *row <<= 8;
+            *row |= slice.get(i * 4 + j).map(|v| *v as u32).unwrap_or(0);
         }
     }
     state
@@ -481,26 +405,260 @@ fn pick_state_from_slice(slice: &[u8]) -> [u32; 4] {
 
 fn push_state(vec: &mut Vec<u8>, state: [u32; 4]) {
     for i in 0..4 {
-        for j in 0..4 {
-            vec.push(((state[j] >> (3 - i) * 8) & 0xff) as u8);
+        for row in state.iter() {
+            vec.push(((row >> ((3 - i) * 8)) & 0xff) as u8);
         }
     }
 }
 
-pub fn encrypt(input: &[u8], key: &Key) -> Vec<u8> {
-    let sub_key = generate_sub_key(key).transpose();
-    let mut result = Vec::with_capacity((input.len() + 15) & !15);
-    for i in 0..(input.len() + 15) / 16 {
-        push_state(&mut result, encrypt_inner(pick_state_from_slice(&input[i * 16..]), &sub_key));
+/// AES with the round keys of one key computed up front.
+///
+/// This is FIPS-197 section 5 step by step on a state of four rows and meant to be read;
+/// [`ttable::TTableAes`] computes the same function faster.
+#[derive(Debug, Clone)]
+pub struct Aes {
+    sub_key: Vec<[u32; 4]>,
+}
+
+impl Aes {
+    pub fn new(key: &Key) -> Aes {
+        Aes { sub_key: generate_sub_key(key).transpose() }
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let mut result = Vec::with_capacity(16);
+        push_state(&mut result, encrypt_inner(pick_state_from_slice(&block), &self.sub_key));
+        result.try_into().unwrap()
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let mut result = Vec::with_capacity(16);
+        push_state(&mut result, decrypt_inner(pick_state_from_slice(&block), &self.sub_key));
+        result.try_into().unwrap()
     }
-    result
 }
 
-pub fn decrypt(input: &[u8], key: &Key) -> Vec<u8> {
-    let sub_key = generate_sub_key(key).transpose();
-    let mut result = Vec::with_capacity((input.len() + 15) & !15);
-    for i in 0..(input.len() + 15) / 16 {
-        push_state(&mut result, decrypt_inner(pick_state_from_slice(&input[i * 16..]), &sub_key));
+fn ecb(input: &[u8], mut crypt: impl FnMut([u8; 16]) -> [u8; 16]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(input.len().div_ceil(16) * 16);
+    for chunk in input.chunks(16) {
+        let mut block = [0; 16];
+        block[..chunk.len()].copy_from_slice(chunk);
+        result.extend_from_slice(&crypt(block));
     }
     result
 }
+
+/// The engines computing AES. They all give the same results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// [`Aes`], FIPS-197 step by step. Slow, and its S-box lookups depend on the data.
+    Reference,
+    /// [`ttable::TTableAes`]. Fast, but the time of its lookups leaks the key through the cache,
+    /// so it is for teaching and benchmarks only.
+    TTable,
+    /// [`bitslice::BitslicedAes`], constant time.
+    Bitsliced,
+    /// `aesni::AesNi`, the AES instructions of x86-64 processors. Constant time and by far the
+    /// fastest, where [`Backend::is_available`].
+    AesNi,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 4] = [Backend::Reference, Backend::TTable, Backend::Bitsliced, Backend::AesNi];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Reference => "reference",
+            Backend::TTable => "ttable",
+            Backend::Bitsliced => "bitsliced",
+            Backend::AesNi => "aesni",
+        }
+    }
+
+    /// Whether this processor can run the backend. Only [`Backend::AesNi`] needs hardware
+    /// support, detected at run time.
+    pub fn is_available(&self) -> bool {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Backend::AesNi => aesni::detected(),
+            #[cfg(not(target_arch = "x86_64"))]
+            Backend::AesNi => false,
+            _ => true,
+        }
+    }
+}
+
+/// AES-NI when the processor has it, the constant-time software backend otherwise.
+impl Default for Backend {
+    fn default() -> Backend {
+        if Backend::AesNi.is_available() {
+            Backend::AesNi
+        } else {
+            Backend::Bitsliced
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownBackend(pub String);
+
+impl fmt::Display for UnknownBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = Backend::ALL.iter().map(Backend::name).collect();
+        write!(f, "unknown backend '{}', expected one of {}", self.0, names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownBackend {}
+
+impl FromStr for Backend {
+    type Err = UnknownBackend;
+
+    fn from_str(s: &str) -> Result<Backend, UnknownBackend> {
+        Backend::ALL.iter().copied().find(|backend| backend.name() == s).ok_or_else(|| UnknownBackend(s.to_string()))
+    }
+}
+
+/// One key scheduled for one backend: the block function the modes of operation are built on.
+#[derive(Debug, Clone)]
+pub enum BlockCipher {
+    Reference(Aes),
+    TTable(ttable::TTableAes),
+    Bitsliced(bitslice::BitslicedAes),
+    #[cfg(target_arch = "x86_64")]
+    AesNi(aesni::AesNi),
+}
+
+impl BlockCipher {
+    /// Panics if the backend is not [available](Backend::is_available).
+    pub fn new(key: &Key, backend: Backend) -> BlockCipher {
+        match backend {
+            Backend::Reference => BlockCipher::Reference(Aes::new(key)),
+            Backend::TTable => BlockCipher::TTable(ttable::TTableAes::new(key)),
+            Backend::Bitsliced => BlockCipher::Bitsliced(bitslice::BitslicedAes::new(key)),
+            #[cfg(target_arch = "x86_64")]
+            Backend::AesNi => BlockCipher::AesNi(aesni::AesNi::new(key).expect("AES-NI is not available on this processor")),
+            #[cfg(not(target_arch = "x86_64"))]
+            Backend::AesNi => panic!("AES-NI is not available on this processor"),
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self {
+            BlockCipher::Reference(_) => Backend::Reference,
+            BlockCipher::TTable(_) => Backend::TTable,
+            BlockCipher::Bitsliced(_) => Backend::Bitsliced,
+            #[cfg(target_arch = "x86_64")]
+            BlockCipher::AesNi(_) => Backend::AesNi,
+        }
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        match self {
+            BlockCipher::Reference(aes) => aes.encrypt_block(block),
+            BlockCipher::TTable(aes) => aes.encrypt_block(block),
+            BlockCipher::Bitsliced(aes) => aes.encrypt_block(block),
+            #[cfg(target_arch = "x86_64")]
+            BlockCipher::AesNi(aes) => aes.encrypt_block(block),
+        }
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        match self {
+            BlockCipher::Reference(aes) => aes.decrypt_block(block),
+            BlockCipher::TTable(aes) => aes.decrypt_block(block),
+            BlockCipher::Bitsliced(aes) => aes.decrypt_block(block),
+            #[cfg(target_arch = "x86_64")]
+            BlockCipher::AesNi(aes) => aes.decrypt_block(block),
+        }
+    }
+
+    /// Encrypts `blocks` in place, as many at once as the backend runs in parallel.
+    pub fn encrypt_blocks(&self, blocks: &mut [[u8; 16]]) {
+        match self {
+            BlockCipher::Bitsliced(aes) => {
+                for chunk in blocks.chunks_mut(bitslice::BLOCKS) {
+                    let mut batch = [[0; 16]; bitslice::BLOCKS];
+                    batch[..chunk.len()].copy_from_slice(chunk);
+                    let batch = aes.encrypt_blocks(&batch);
+                    chunk.copy_from_slice(&batch[..chunk.len()]);
+                }
+            }
+            #[cfg(target_arch = "x86_64")]
+            BlockCipher::AesNi(aes) => {
+                let mut chunks = blocks.chunks_exact_mut(aesni::PARALLEL);
+                for chunk in &mut chunks {
+                    aes.encrypt_blocks(chunk.try_into().unwrap());
+                }
+                for block in chunks.into_remainder() {
+                    *block = aes.encrypt_block(*block);
+                }
+            }
+            _ => {
+                for block in blocks {
+                    *block = self.encrypt_block(*block);
+                }
+            }
+        }
+    }
+}
+
+/// Whether `a` and `b` are equal, in a time that depends on their lengths only.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0, |difference, (x, y)| difference | (x ^ y)) == 0
+}
+
+/// Encrypts `input` in ECB mode with `backend`. The last block is padded with zero bytes.
+///
+/// Panics if the backend is not [available](Backend::is_available).
+pub fn encrypt_with(input: &[u8], key: &Key, backend: Backend) -> Vec<u8> {
+    match backend {
+        Backend::Reference => {
+            let aes = Aes::new(key);
+            ecb(input, |block| aes.encrypt_block(block))
+        }
+        Backend::TTable => {
+            let aes = ttable::TTableAes::new(key);
+            ecb(input, |block| aes.encrypt_block(block))
+        }
+        Backend::Bitsliced => bitslice::encrypt(input, key),
+        #[cfg(target_arch = "x86_64")]
+        Backend::AesNi => aesni::encrypt(input, key).expect("AES-NI is not available on this processor"),
+        #[cfg(not(target_arch = "x86_64"))]
+        Backend::AesNi => panic!("AES-NI is not available on this processor"),
+    }
+}
+
+/// Decrypts `input` in ECB mode with `backend`. A trailing partial block is padded with zero
+/// bytes first.
+///
+/// Panics if the backend is not [available](Backend::is_available).
+pub fn decrypt_with(input: &[u8], key: &Key, backend: Backend) -> Vec<u8> {
+    match backend {
+        Backend::Reference => {
+            let aes = Aes::new(key);
+            ecb(input, |block| aes.decrypt_block(block))
+        }
+        Backend::TTable => {
+            let aes = ttable::TTableAes::new(key);
+            ecb(input, |block| aes.decrypt_block(block))
+        }
+        Backend::Bitsliced => bitslice::decrypt(input, key),
+        #[cfg(target_arch = "x86_64")]
+        Backend::AesNi => aesni::decrypt(input, key).expect("AES-NI is not available on this processor"),
+        #[cfg(not(target_arch = "x86_64"))]
+        Backend::AesNi => panic!("AES-NI is not available on this processor"),
+    }
+}
+
+/// Encrypts `input` in ECB mode with the [default](Backend::default) backend. The last block is
+/// padded with zero bytes.
+pub fn encrypt(input: &[u8], key: &Key) -> Vec<u8> {
+    encrypt_with(input, key, Backend::default())
+}
+
+/// Decrypts `input` in ECB mode with the [default](Backend::default) backend. A trailing partial
+/// block is padded with zero bytes first.
+pub fn decrypt(input: &[u8], key: &Key) -> Vec<u8> {
+    decrypt_with(input, key, Backend::default())
+}
@@ -2,9 +2,11 @@ use std::io::Read;
 
 use clap::{App, Arg, SubCommand};
 
-use crate::lib::{decrypt, encrypt, keygen_128, keygen_192, keygen_256, parse_key};
-
-mod lib;
+use aes::ccm::Ccm;
+use aes::gcm::Gcm;
+use aes::gcm_siv::GcmSiv;
+use aes::{decrypt_with, encrypt, encrypt_with, keygen_128, keygen_192, keygen_256, parse_key, Backend, Key};
+use keyfile::{path_of_argument, KeyFile};
 
 fn main() {
     let matches = App::new("aes")
@@ -15,6 +17,11 @@ fn main() {
                 .short("l")
                 .long("length")
                 .help("length of key(either 128 192 or 256)")
+                .takes_value(true))
+            .arg(Arg::with_name("out")
+                .short("o")
+                .long("out")
+                .help("write the key to a new key file instead of printing it")
                 .takes_value(true)))
         .arg(Arg::with_name("decrypt")
             .short("d")
@@ -23,12 +30,38 @@ fn main() {
         .arg(Arg::with_name("key")
             .short("k")
             .long("key")
-            .help("Key for encrypt or decrypt")
+            .help("Key for encrypt or decrypt, or @FILE to read it from a key file")
             .takes_value(true))
         .arg(Arg::with_name("hex")
             .short("x")
             .long("hex")
             .help("flag to encrypt input as hex value(when decrypt this flag is ignored)"))
+        .arg(Arg::with_name("backend")
+            .short("b")
+            .long("backend")
+            .help("AES implementation to use, AES-NI when the processor has it and bitsliced otherwise by default")
+            .possible_values(&["reference", "ttable", "bitsliced", "aesni"])
+            .takes_value(true))
+        .arg(Arg::with_name("mode")
+            .short("m")
+            .long("mode")
+            .help("mode of operation, gcm ccm and gcm-siv encrypt and authenticate")
+            .possible_values(&["ecb", "gcm", "ccm", "gcm-siv"])
+            .default_value("ecb"))
+        .arg(Arg::with_name("aad")
+            .long("aad")
+            .help("associated data authenticated along with the input in the authenticated modes")
+            .takes_value(true))
+        .arg(Arg::with_name("nonce")
+            .short("n")
+            .long("nonce")
+            .help("nonce as hex value in the authenticated modes, required to decrypt and random when encrypting without it")
+            .takes_value(true))
+        .arg(Arg::with_name("tag-bits")
+            .long("tag-bits")
+            .help("length of the tag, gcm allows 128 120 112 104 96 64 or 32 and ccm 128 112 96 80 64 48 or 32 and gcm-siv 128 only")
+            .possible_values(&["128", "120", "112", "104", "96", "80", "64", "48", "32"])
+            .default_value("128"))
         .arg(Arg::with_name("input")
             .help("input value to encrypt or decrypt"))
         .get_matches();
@@ -42,9 +75,28 @@ fn main() {
                 return;
             }
         };
+        if let Some(path) = matches.value_of("out") {
+            match KeyFile::new(key.algorithm(), key.as_slice(), &encrypt(&[0; 16], &key)).write(path) {
+                Ok(()) => println!("generated {} key written to {}", key.algorithm(), path),
+                Err(e) => eprintln!("error in writing key file: {}", e),
+            }
+            return;
+        }
         let key = binary_to_hex_string(key.as_slice());
         println!("generated key: {}", key);
     } else {
+        let backend = match matches.value_of("backend").map(str::parse::<Backend>) {
+            Some(Ok(backend)) => backend,
+            Some(Err(e)) => {
+                eprintln!("{}", e);
+                return;
+            }
+            None => Backend::default(),
+        };
+        if !backend.is_available() {
+            eprintln!("backend {} is not available on this processor", backend.name());
+            return;
+        }
         let input = matches.value_of("input").map(str::to_string).unwrap_or_else(|| {
             let mut s = String::new();
             std::io::stdin().read_to_string(&mut s).expect("failed to read standard input");
@@ -62,10 +114,73 @@ fn main() {
                 return;
             }
         };
-        match parse_key(matches.value_of("key").unwrap()) {
+        let key = match matches.value_of("key") {
+            Some(key) => key,
+            None => {
+                eprintln!("argument 'key' is required");
+                return;
+            }
+        };
+        let key = match path_of_argument(key) {
+            Some(path) => match read_key_file(path) {
+                Ok(key) => Ok(key),
+                Err(e) => {
+                    eprintln!("error in reading key file: {}", e);
+                    return;
+                }
+            },
+            None => parse_key(key),
+        };
+        match key {
+            Ok(key) if matches.value_of("mode") != Some("ecb") => {
+                let tag_bytes = matches.value_of("tag-bits").unwrap().parse::<usize>().unwrap() / 8;
+                let aead = match Aead::new(matches.value_of("mode").unwrap(), &key, backend, tag_bytes) {
+                    Ok(aead) => aead,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return;
+                    }
+                };
+                let aad = matches.value_of("aad").unwrap_or("").as_bytes();
+                let nonce = match matches.value_of("nonce").map(hex_string_to_binary) {
+                    Some(Ok(nonce)) => nonce,
+                    Some(Err(e)) => {
+                        eprintln!("error in parsing nonce: {:?}", e);
+                        return;
+                    }
+                    None if matches.is_present("decrypt") => {
+                        eprintln!("argument 'nonce' is required to decrypt");
+                        return;
+                    }
+                    None => {
+                        let mut nonce = vec![0; aead.nonce_bytes()];
+                        getrandom::getrandom(&mut nonce).expect("failed to get random bytes from the OS");
+                        eprintln!("nonce: {}", binary_to_hex_string(&nonce));
+                        nonce
+                    }
+                };
+                if matches.is_present("decrypt") {
+                    match aead.decrypt(&nonce, aad, &input) {
+                        Ok(result) if !matches.is_present("hex") => {
+                            let string = String::from_utf8(result).expect("failed to encode to utf8 decrypt result.");
+                            println!("{}", string);
+                        }
+                        Ok(result) => println!("{}", binary_to_hex_string(&result)),
+                        Err(e) => {
+                            eprintln!("error in decrypting: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                } else {
+                    match aead.encrypt(&nonce, aad, &input) {
+                        Ok(result) => println!("{}", binary_to_hex_string(&result)),
+                        Err(e) => eprintln!("error in encrypting: {}", e),
+                    }
+                }
+            }
             Ok(key) => {
                 if matches.is_present("decrypt") {
-                    let result = encrypt(&input, &key);
+                    let result = decrypt_with(&input, &key, backend);
                     if !matches.is_present("hex") {
                         let string = String::from_utf8(result).expect("failed to encode to utf8 decrypt result.");
                         println!("{}", string);
@@ -73,7 +188,7 @@ fn main() {
                         println!("{}", binary_to_hex_string(&result));
                     }
                 } else {
-                    let result = decrypt(&input, &key);
+                    let result = encrypt_with(&input, &key, backend);
                     println!("{}", binary_to_hex_string(&result));
                 }
             }
@@ -84,6 +199,57 @@ fn main() {
     }
 }
 
+/// The authenticated modes, which take a nonce and associated data and append a tag.
+enum Aead {
+    Gcm(Gcm),
+    Ccm(Ccm),
+    GcmSiv(GcmSiv),
+}
+
+impl Aead {
+    fn new(mode: &str, key: &Key, backend: Backend, tag_bytes: usize) -> Result<Aead, Box<dyn std::error::Error>> {
+        match mode {
+            "gcm" => Ok(Aead::Gcm(Gcm::with_backend(key, backend).truncate_tag(tag_bytes)?)),
+            "ccm" => Ok(Aead::Ccm(Ccm::with_backend(key, backend).truncate_tag(tag_bytes)?)),
+            "gcm-siv" if tag_bytes != aes::gcm_siv::TAG_BYTES => Err("gcm-siv tags are 128 bits".into()),
+            "gcm-siv" => Ok(Aead::GcmSiv(GcmSiv::with_backend(key, backend)?)),
+            _ => Err(format!("{} is not an authenticated mode", mode).into()),
+        }
+    }
+
+    /// Length of the random nonces generated for encryption.
+    fn nonce_bytes(&self) -> usize {
+        match self {
+            Aead::Gcm(_) => aes::gcm::NONCE_BYTES,
+            Aead::Ccm(_) => aes::ccm::MAX_NONCE_BYTES,
+            Aead::GcmSiv(_) => aes::gcm_siv::NONCE_BYTES,
+        }
+    }
+
+    fn encrypt(&self, nonce: &[u8], aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match self {
+            Aead::Gcm(gcm) => Ok(gcm.encrypt(nonce, aad, input)?),
+            Aead::Ccm(ccm) => Ok(ccm.encrypt(nonce, aad, input)?),
+            Aead::GcmSiv(gcm_siv) => Ok(gcm_siv.encrypt(nonce, aad, input)?),
+        }
+    }
+
+    fn decrypt(&self, nonce: &[u8], aad: &[u8], input: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match self {
+            Aead::Gcm(gcm) => Ok(gcm.decrypt(nonce, aad, input)?),
+            Aead::Ccm(ccm) => Ok(ccm.decrypt(nonce, aad, input)?),
+            Aead::GcmSiv(gcm_siv) => Ok(gcm_siv.decrypt(nonce, aad, input)?),
+        }
+    }
+}
+
+/// Loads an AES key of any size from a key file, checking its check value.
+fn read_key_file(path: &str) -> Result<Key, Box<dyn std::error::Error>> {
+    let file = KeyFile::read(path)?;
+    let key = parse_key(&file.key_hex()).map_err(|_| format!("{} is not an AES key", file.algorithm))?;
+    file.verify(key.algorithm(), &encrypt(&[0; 16], &key))?;
+    Ok(key)
+}
 
 #[derive(Debug, PartialEq)]
 enum InputToBinaryError {
@@ -98,9 +264,9 @@ fn hex_string_to_binary(value: &str) -> Result<Vec<u8>, InputToBinaryError> {
     let mut result = Vec::with_capacity(value.len() >> 1);
     let mut chars = value.chars();
     let char_to_int = |c| match c {
-        c @ '0'..='9' => Some(c as u8 - '0' as u8),
-        c @ 'a'..='f' => Some(c as u8 - 'a' as u8 + 10),
-        c @ 'A'..='F' => Some(c as u8 - 'A' as u8 + 10),
+        c @ '0'..='9' => Some(c as u8 - b'0'),
+        c @ 'a'..='f' => Some(c as u8 - b'a' + 10),
+        c @ 'A'..='F' => Some(c as u8 - b'A' + 10),
         _ => None
     };
     while let Some(c) = chars.next() {
@@ -0,0 +1,149 @@
+//! AES-SIV (RFC 5297), deterministic authenticated encryption.
+//!
+//! The synthetic IV is S2V, a CMAC over any number of associated data components and the
+//! plaintext, each chained with a doubling in GF(2^128) so that the components stay apart. It
+//! serves both as the tag and as the initial counter for encrypting the plaintext with a second
+//! key. Equal inputs give equal outputs and nothing more leaks when a nonce repeats or is left out.
+//! A nonce, if any, is the last associated data component.
+
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::cmac::{double, Cmac};
+use crate::{constant_time_eq, Backend, BlockCipher, Key};
+
+#[cfg(test)]
+mod test;
+
+/// Most associated data components: S2V takes at most 127 strings, the plaintext being the last.
+pub const MAX_COMPONENTS: usize = 126;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SivError {
+    /// Keys are two AES keys of the same size: 32, 48 or 64 bytes.
+    InvalidKeyLength(usize),
+    TooManyComponents(usize),
+    /// The synthetic IV does not match: the ciphertext, the associated data or the key is wrong.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for SivError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SivError::InvalidKeyLength(bytes) => write!(f, "key of {} bytes is not allowed, expected 32 48 or 64", bytes),
+            SivError::TooManyComponents(count) => write!(f, "{} associated data components, at most {} are allowed", count, MAX_COMPONENTS),
+            SivError::AuthenticationFailed => write!(f, "authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for SivError {}
+
+/// AES-SIV under one key.
+///
+/// ```
+/// use aes::siv::{Siv, SivError};
+///
+/// let siv = Siv::new(&[0x42; 32]).unwrap();
+/// let associated_data: [&[u8]; 2] = [b"table users", b"record 17"];
+/// let sealed = siv.encrypt(&associated_data, b"message").unwrap();
+/// assert_eq!(sealed.len(), 16 + 7);
+/// assert_eq!(siv.encrypt(&associated_data, b"message"), Ok(sealed.clone()));
+/// assert_eq!(siv.decrypt(&associated_data, &sealed), Ok(b"message".to_vec()));
+/// assert_eq!(siv.decrypt(&[b"table users", b"record 18"], &sealed), Err(SivError::AuthenticationFailed));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Siv {
+    mac: Cmac,
+    ctr: BlockCipher,
+}
+
+impl Siv {
+    /// With the [default](Backend::default) backend. The first half of `key` is the CMAC key, the
+    /// second half the counter mode key.
+    pub fn new(key: &[u8]) -> Result<Siv, SivError> {
+        Siv::with_backend(key, Backend::default())
+    }
+
+    /// Panics if the backend is not [available](Backend::is_available).
+    pub fn with_backend(key: &[u8], backend: Backend) -> Result<Siv, SivError> {
+        let (mac, ctr) = key.split_at(key.len() / 2);
+        match (Key::from_slice(mac), Key::from_slice(ctr)) {
+            (Some(mac), Some(ctr)) => Ok(Siv { mac: Cmac::with_backend(&mac, backend), ctr: BlockCipher::new(&ctr, backend) }),
+            _ => Err(SivError::InvalidKeyLength(key.len())),
+        }
+    }
+
+    /// S2V of RFC 5297 section 2.4 over the associated data components and the plaintext.
+    fn s2v(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> [u8; 16] {
+        let mut d = self.mac.mac(&[0; 16]);
+        for component in associated_data {
+            d = xor(double(d), self.mac.mac(component));
+        }
+        if plaintext.len() >= 16 {
+            let mut t = plaintext.to_vec();
+            let end = t.len() - 16;
+            for (t, d) in t[end..].iter_mut().zip(d.iter()) {
+                *t ^= d;
+            }
+            self.mac.mac(&t)
+        } else {
+            let mut padded = [0; 16];
+            padded[..plaintext.len()].copy_from_slice(plaintext);
+            padded[plaintext.len()] = 0x80;
+            self.mac.mac(&xor(double(d), padded))
+        }
+    }
+
+    /// Counter mode from the synthetic IV with two bits cleared, which lets implementations add
+    /// to the last 64 bits only.
+    fn ctr(&self, v: [u8; 16], input: &[u8]) -> Vec<u8> {
+        let mut q = v;
+        q[8] &= 0x7f;
+        q[12] &= 0x7f;
+        let q = u128::from_be_bytes(q);
+        let mut blocks: Vec<[u8; 16]> = (0..input.len().div_ceil(16) as u128).map(|i| q.wrapping_add(i).to_be_bytes()).collect();
+        self.ctr.encrypt_blocks(&mut blocks);
+        input.iter().zip(blocks.iter().flatten()).map(|(x, k)| x ^ k).collect()
+    }
+
+    fn check_components(associated_data: &[&[u8]]) -> Result<(), SivError> {
+        if associated_data.len() > MAX_COMPONENTS {
+            return Err(SivError::TooManyComponents(associated_data.len()));
+        }
+        Ok(())
+    }
+
+    /// Encrypts `plaintext` and authenticates it along with the components of `associated_data`,
+    /// which may be empty. Returns the synthetic IV followed by the ciphertext.
+    pub fn encrypt(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> Result<Vec<u8>, SivError> {
+        Siv::check_components(associated_data)?;
+        let v = self.s2v(associated_data, plaintext);
+        let mut output = v.to_vec();
+        output.extend(self.ctr(v, plaintext));
+        Ok(output)
+    }
+
+    /// Decrypts the ciphertext after the synthetic IV at the start of `input` and checks the IV. No
+    /// plaintext is returned unless it matches.
+    pub fn decrypt(&self, associated_data: &[&[u8]], input: &[u8]) -> Result<Vec<u8>, SivError> {
+        Siv::check_components(associated_data)?;
+        if input.len() < 16 {
+            return Err(SivError::AuthenticationFailed);
+        }
+        let (v, ciphertext) = input.split_at(16);
+        let plaintext = self.ctr(v.try_into().unwrap(), ciphertext);
+        if !constant_time_eq(&self.s2v(associated_data, &plaintext), v) {
+            return Err(SivError::AuthenticationFailed);
+        }
+        Ok(plaintext)
+    }
+}
+
+fn xor(a: [u8; 16], b: [u8; 16]) -> [u8; 16] {
+    let mut result = a;
+    for (r, b) in result.iter_mut().zip(b.iter()) {
+        *r ^= b;
+    }
+    result
+}
@@ -0,0 +1,125 @@
+use rand::{thread_rng, Rng};
+
+use crate::Backend;
+
+use super::*;
+
+/// Key, associated data components, plaintext and output: the examples of RFC 5297 appendix A,
+/// deterministic and with a nonce as the last component, then random ones for the other key sizes
+/// and for empty inputs.
+const VECTORS: [(&str, &[&str], &str, &str); 7] = [
+    (
+        "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+        &["101112131415161718191a1b1c1d1e1f2021222324252627"],
+        "112233445566778899aabbccddee",
+        "85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c",
+    ),
+    (
+        "7f7e7d7c7b7a79787776757473727170404142434445464748494a4b4c4d4e4f",
+        &["00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa99887766554433221100", "102030405060708090a0", "09f911029d74e35bd84156c5635688c0"],
+        "7468697320697320736f6d6520706c61696e7465787420746f20656e6372797074207573696e67205349562d414553",
+        "7bdb6e3b432667eb06f4d14bff2fbd0fcb900f2fddbe404326601965c889bf17dba77ceb094fa663b7a3f748ba8af829ea64ad544a272e9c485b62a3fd5c0d",
+    ),
+    (
+        "13ae936de7a2626e9b1561380aa61e82f92f5bf232920c2baef7e8ce60c0d6af66dfbf941319d6ea7ff5839288b9cac7",
+        &["ac4b3d13f64929b1d03635ec86036b0e1db57c6e", "e70035"],
+        "5888d88883b81ef4becea9d9a643ed8309fd0fcfa5287b99989e757a8b1c364b981657d83c7f8cfc",
+        "51128db18dd239e94280ddb45f979f221c62d97b17d62053ce2474cd550801a060dcf4716f5868fcacbb5209302fd40109020bcacb9c2074",
+    ),
+    (
+        "a0f150c567c7fcb8fce9749f37198320ec6d2b1757e92486b4c32df5f131a254848fb05aada2e14d2dfa7961cd7d121060d12e3267829d342da87f3e5250a138",
+        &["23fa3898563a332d6dbdefc17c1f7a03"],
+        "7ce86ed133ad23701bc0aee85677dca5dc62107feb39e5ffc1d9f9d01165e085a3",
+        "931c48e9d5909653a4fd988ab35286aadedd680261250dfed5624d2204ae196e65a0847f14bf9d2dad86906dec69588c2f",
+    ),
+    ("fa92043f43a4c9bc2039c270bb4cfc71e5d52fd3040483e07e29d351d7e4547c", &[], "", "894ae7bbb0ba6f711caa1e8759c82051"),
+    (
+        "c7d36d9673e9157296366e915928b4aaf2c7b1d11b092dfe50a9006ccd1a6045956b58e416bbd86ab487a8eb999bb503",
+        &[""],
+        "83f518a484b6f76c3b354e3b37755ee0",
+        "2a2431452802a6feda2f9cd5e483ad3bb242dcd5d19b9c3c90c028ecdfe37a02",
+    ),
+    (
+        "f7ef52476be7fed764bff735964c75ea59e0819522fdf33bb23611dea8021ca9c64307f27889bdd2590de55b075d83676352f8113ddb7561b35d4249231e65b5",
+        &["ad2b5156d4", "", "fa28319051d7cec96494f63881251ddfdd"],
+        "1e2e6cca54e4c9eff3c0de574f6b85",
+        "c60c8fb48743f05968f54c73b6f8af0092fcf9173c91c8043cc1833b35ac86",
+    ),
+];
+
+fn hex(value: &str) -> Vec<u8> {
+    (0..value.len()).step_by(2).map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn test_vectors() {
+    for backend in Backend::ALL.iter().copied().filter(Backend::is_available) {
+        for &(key, associated_data, plaintext, sealed) in VECTORS.iter() {
+            let siv = Siv::with_backend(&hex(key), backend).unwrap();
+            let associated_data: Vec<Vec<u8>> = associated_data.iter().map(|component| hex(component)).collect();
+            let associated_data: Vec<&[u8]> = associated_data.iter().map(Vec::as_slice).collect();
+            assert_eq!(siv.encrypt(&associated_data, &hex(plaintext)), Ok(hex(sealed)), "{} {}", backend.name(), key);
+            assert_eq!(siv.decrypt(&associated_data, &hex(sealed)), Ok(hex(plaintext)));
+        }
+    }
+}
+
+#[test]
+fn test_s2v() {
+    // The intermediate value of RFC 5297 appendix A.1.
+    let siv = Siv::new(&hex(VECTORS[0].0)).unwrap();
+    assert_eq!(siv.s2v(&[&hex(VECTORS[0].1[0])], &hex(VECTORS[0].2)).to_vec(), hex("85632d07c6e8f37f950acd320a2ecc93"));
+}
+
+#[test]
+fn test_key_length() {
+    for bytes in [0, 16, 24, 31, 33, 40, 63, 65, 96] {
+        assert_eq!(Siv::new(&vec![0; bytes]).err(), Some(SivError::InvalidKeyLength(bytes)));
+    }
+}
+
+/// Components are authenticated apart from each other and in order.
+#[test]
+fn test_components() {
+    let siv = Siv::new(&[7; 32]).unwrap();
+    let sealed = siv.encrypt(&[b"ab", b"c"], b"message").unwrap();
+    for associated_data in [&[&b"a"[..], b"bc"][..], &[b"abc"], &[b"c", b"ab"], &[b"ab", b"c", b""], &[b"ab"], &[]] {
+        assert_eq!(siv.decrypt(associated_data, &sealed), Err(SivError::AuthenticationFailed));
+    }
+    assert_eq!(siv.decrypt(&[b"ab", b"c"], &sealed), Ok(b"message".to_vec()));
+    let many = vec![&b""[..]; MAX_COMPONENTS + 1];
+    assert_eq!(siv.encrypt(&many, b"message"), Err(SivError::TooManyComponents(MAX_COMPONENTS + 1)));
+    assert!(siv.encrypt(&many[1..], b"message").is_ok());
+}
+
+#[test]
+fn test_authentication() {
+    let mut rng = thread_rng();
+    let key: [u8; 32] = rng.gen();
+    let siv = Siv::new(&key).unwrap();
+    let sealed = siv.encrypt(&[b"nonce"], b"attack at dawn").unwrap();
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 0x01;
+        assert_eq!(siv.decrypt(&[b"nonce"], &tampered), Err(SivError::AuthenticationFailed));
+    }
+    assert_eq!(siv.decrypt(&[b"nonce"], &sealed[..15]), Err(SivError::AuthenticationFailed));
+    assert_eq!(Siv::new(&[0; 32]).unwrap().decrypt(&[b"nonce"], &sealed), Err(SivError::AuthenticationFailed));
+}
+
+#[test]
+fn test_backends() {
+    let mut rng = thread_rng();
+    let mut key = [0; 64];
+    rng.fill(&mut key[..]);
+    for length in [0, 1, 15, 16, 17, 63, 64, 65, 300] {
+        let plaintext: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+        let component: Vec<u8> = (0..rng.gen_range(0, 40)).map(|_| rng.gen()).collect();
+        let sealed = Siv::with_backend(&key, Backend::Reference).unwrap().encrypt(&[&component], &plaintext).unwrap();
+        for backend in Backend::ALL.iter().copied().filter(Backend::is_available) {
+            let siv = Siv::with_backend(&key, backend).unwrap();
+            assert_eq!(siv.encrypt(&[&component], &plaintext).as_ref(), Ok(&sealed), "{}", backend.name());
+            assert_eq!(siv.decrypt(&[&component], &sealed), Ok(plaintext.clone()));
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use rand::{thread_rng, Rng};
+
+use super::*;
+
+mod aesavs;
+
+#[test]
+fn test_key_as_slice() {
+    assert_eq!(Key::AES128([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]).as_slice(),
+               &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+    assert_eq!(Key::AES192([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23]).as_slice(),
+               &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23]);
+    assert_eq!(Key::AES256([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31]).as_slice(),
+               &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31]);
+}
+
+#[test]
+fn test_test_keygen() {
+    assert_eq!(keygen_128().as_slice().len(), 128 / 8);
+    assert_eq!(keygen_192().as_slice().len(), 192 / 8);
+    assert_eq!(keygen_256().as_slice().len(), 256 / 8);
+
+    assert_ne!(keygen_128(), keygen_128());
+    assert_ne!(keygen_192(), keygen_192());
+    assert_ne!(keygen_256(), keygen_256());
+}
+
+#[test]
+fn test_parse_key() {
+    assert_eq!(parse_key("000102030405060708090a0b0c0d0e0f"),
+               Ok(Key::AES128([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15])));
+    assert_eq!(parse_key("000102030405060708090a0b0c0d0e0f1011121314151617"),
+               Ok(Key::AES192([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23])));
+    assert_eq!(parse_key("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"),
+               Ok(Key::AES256([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31])));
+
+    assert!(parse_key("000102030405060708090a0b0c0d0e0f1").is_err());
+    assert!(parse_key("000102030405060708090a0b0c0d0e0").is_err());
+    assert!(parse_key("000102030405060708090a0b0c0d0e0f10111213141516171").is_err());
+    assert!(parse_key("000102030405060708090a0b0c0d0e0f101112131415161").is_err());
+    assert!(parse_key("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f0").is_err());
+    assert!(parse_key("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1").is_err());
+
+    assert!(parse_key("g000102030405060708090a0b0c0d0e0").is_err());
+}
+
+#[test]
+fn test_key_from_slice() {
+    for key in [keygen_128(), keygen_192(), keygen_256()] {
+        assert_eq!(Key::from_slice(key.as_slice()), Some(key));
+    }
+    assert_eq!(Key::from_slice(&[0; 0]), None);
+    assert_eq!(Key::from_slice(&[0; 20]), None);
+    assert_eq!(Key::from_slice(&[0; 64]), None);
+}
+
+#[test]
+fn test_polynomial_mul() {
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b0000_0001), Polynomial(0b0000_0001));
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b0000_0010), Polynomial(0b0000_0010));
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b0000_0100), Polynomial(0b0000_0100));
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b0000_1000), Polynomial(0b0000_1000));
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b0001_0000), Polynomial(0b0001_0000));
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b0010_0000), Polynomial(0b0010_0000));
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b0100_0000), Polynomial(0b0100_0000));
+    assert_eq!(Polynomial(0b0000_0001) * Polynomial(0b1000_0000), Polynomial(0b1000_0000));
+
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b0000_0001), Polynomial(0b0000_0010));
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b0000_0010), Polynomial(0b0000_0100));
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b0000_0100), Polynomial(0b0000_1000));
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b0000_1000), Polynomial(0b0001_0000));
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b0001_0000), Polynomial(0b0010_0000));
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b0010_0000), Polynomial(0b0100_0000));
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b0100_0000), Polynomial(0b1000_0000));
+    assert_eq!(Polynomial(0b0000_0010) * Polynomial(0b1000_0000), Polynomial(0b0001_1011));
+
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b0000_0001), Polynomial(0b0000_0100));
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b0000_0010), Polynomial(0b0000_1000));
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b0000_0100), Polynomial(0b0001_0000));
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b0000_1000), Polynomial(0b0010_0000));
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b0001_0000), Polynomial(0b0100_0000));
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b0010_0000), Polynomial(0b1000_0000));
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b0100_0000), Polynomial(0b0001_1011));
+    assert_eq!(Polynomial(0b0000_0100) * Polynomial(0b1000_0000), Polynomial(0b0011_0110));
+
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b0000_0001), Polynomial(0b0000_1000));
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b0000_0010), Polynomial(0b0001_0000));
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b0000_0100), Polynomial(0b0010_0000));
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b0000_1000), Polynomial(0b0100_0000));
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b0001_0000), Polynomial(0b1000_0000));
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b0010_0000), Polynomial(0b0001_1011));
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b0100_0000), Polynomial(0b0011_0110));
+    assert_eq!(Polynomial(0b0000_1000) * Polynomial(0b1000_0000), Polynomial(0b0110_1100));
+
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b0000_0001), Polynomial(0b0001_0000));
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b0000_0010), Polynomial(0b0010_0000));
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b0000_0100), Polynomial(0b0100_0000));
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b0000_1000), Polynomial(0b1000_0000));
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b0001_0000), Polynomial(0b0001_1011));
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b0010_0000), Polynomial(0b0011_0110));
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b0100_0000), Polynomial(0b0110_1100));
+    assert_eq!(Polynomial(0b0001_0000) * Polynomial(0b1000_0000), Polynomial(0b1101_1000));
+
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b0000_0001), Polynomial(0b0010_0000));
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b0000_0010), Polynomial(0b0100_0000));
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b0000_0100), Polynomial(0b1000_0000));
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b0000_1000), Polynomial(0b0001_1011));
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b0001_0000), Polynomial(0b0011_0110));
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b0010_0000), Polynomial(0b0110_1100));
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b0100_0000), Polynomial(0b1101_1000));
+    assert_eq!(Polynomial(0b0010_0000) * Polynomial(0b1000_0000), Polynomial(0b1010_1011));
+
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b0000_0001), Polynomial(0b0100_0000));
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b0000_0010), Polynomial(0b1000_0000));
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b0000_0100), Polynomial(0b0001_1011));
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b0000_1000), Polynomial(0b0011_0110));
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b0001_0000), Polynomial(0b0110_1100));
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b0010_0000), Polynomial(0b1101_1000));
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b0100_0000), Polynomial(0b1010_1011));
+    assert_eq!(Polynomial(0b0100_0000) * Polynomial(0b1000_0000), Polynomial(0b0100_1101));
+
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b0000_0001), Polynomial(0b1000_0000));
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b0000_0010), Polynomial(0b0001_1011));
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b0000_0100), Polynomial(0b0011_0110));
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b0000_1000), Polynomial(0b0110_1100));
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b0001_0000), Polynomial(0b1101_1000));
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b0010_0000), Polynomial(0b1010_1011));
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b0100_0000), Polynomial(0b0100_1101));
+    assert_eq!(Polynomial(0b1000_0000) * Polynomial(0b1000_0000), Polynomial(0b1001_1010));
+
+    assert_eq!(Polynomial(0b1000_0011) * Polynomial(0b0010_1100), Polynomial(0b1000_0101)); //"(+x^0+x^1+x^7)*(+x^2+x^3+x^5)"
+    assert_eq!(Polynomial(0b1011_0110) * Polynomial(0b1000_1000), Polynomial(0b0000_0011)); //"(+x^1+x^2+x^4+x^5+x^7)*(+x^3+x^7)"
+    assert_eq!(Polynomial(0b0001_0101) * Polynomial(0b0000_1011), Polynomial(0b1001_0111)); //"(+x^0+x^2+x^4)*(+x^0+x^1+x^3)"
+    assert_eq!(Polynomial(0b1111_1110) * Polynomial(0b1011_1000), Polynomial(0b1101_1111)); //"(+x^1+x^2+x^3+x^4+x^5+x^6+x^7)*(+x^3+x^4+x^5+x^7)"
+    assert_eq!(Polynomial(0b0110_1001) * Polynomial(0b0000_1100), Polynomial(0b1101_1010)); //"(+x^0+x^3+x^5+x^6)*(+x^2+x^3)";
+    assert_eq!(Polynomial(0b1011_0001) * Polynomial(0b0010_0010), Polynomial(0b1010_1000)); //"(+x^0+x^4+x^5+x^7)*(+x^1+x^5)";
+    assert_eq!(Polynomial(0b0010_1010) * Polynomial(0b0110_1101), Polynomial(0b1001_0000)); //"(+x^1+x^3+x^5)*(+x^0+x^2+x^3+x^5+x^6)";
+    assert_eq!(Polynomial(0b0011_0011) * Polynomial(0b0110_0010), Polynomial(0b0010_1000)); //"(+x^0+x^1+x^4+x^5)*(+x^1+x^5+x^6)";
+    assert_eq!(Polynomial(0b1110_1011) * Polynomial(0b1011_0011), Polynomial(0b1111_1011)); //"(+x^0+x^1+x^3+x^5+x^6+x^7)*(+x^0+x^1+x^4+x^5+x^7)";
+    assert_eq!(Polynomial(0b0100_1000) * Polynomial(0b0011_1101), Polynomial(0b0010_1010)); //"(+x^3+x^6)*(+x^0+x^2+x^3+x^4+x^5)";
+    assert_eq!(Polynomial(0b0011_0010) * Polynomial(0b1011_0001), Polynomial(0b0100_1101)); //"(+x^1+x^4+x^5)*(+x^0+x^4+x^5+x^7)";
+    assert_eq!(Polynomial(0b1011_1010) * Polynomial(0b0110_1100), Polynomial(0b1001_1100)); //"(+x^1+x^3+x^4+x^5+x^7)*(+x^2+x^3+x^5+x^6)";
+    assert_eq!(Polynomial(0b1111_0011) * Polynomial(0b1111_1101), Polynomial(0b1000_0110)); //"(+x^0+x^1+x^4+x^5+x^6+x^7)*(+x^0+x^2+x^3+x^4+x^5+x^6+x^7)";
+    assert_eq!(Polynomial(0b0101_0101) * Polynomial(0b1000_1001), Polynomial(0b1110_1000)); //"(+x^0+x^2+x^4+x^6)*(+x^0+x^3+x^7)";
+    assert_eq!(Polynomial(0b0110_1111) * Polynomial(0b1000_0100), Polynomial(0b1000_0000)); //"(+x^0+x^1+x^2+x^3+x^5+x^6)*(+x^2+x^7)";
+    assert_eq!(Polynomial(0b0011_1100) * Polynomial(0b0000_1101), Polynomial(0b0011_0111)); //"(+x^2+x^3+x^4+x^5)*(+x^0+x^2+x^3)";
+
+    assert_eq!(Polynomial(0b1111_1111) * Polynomial(0b1111_1111), Polynomial(0b0001_0011)); //"(+x^0+x^1+x^2+x^3+x^4+x^5+x^6+x^7)*(+x^0+x^1+x^2+x^3+x^4+x^5+x^6+x^7)"
+}
+
+#[test]
+fn test_rot_word() {
+    assert_eq!(rot_word(0x01020304), 0x02030401);
+}
+
+#[test]
+fn test_sub_key_transpose() {
+    assert_eq!(
+        SubKey::AES128([
+            [0x00010203, 0x04050607, 0x08090a0b, 0x0c0d0e0f],
+            [0x00102030, 0x40506070, 0x8090a0b0, 0xc0d0e0f0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0]
+        ]).transpose(),
+        vec![
+            [0x0004080c, 0x0105090d, 0x02060a0e, 0x03070b0f],
+            [0x004080c0, 0x105090d0, 0x2060a0e0, 0x3070b0f0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0]
+        ]);
+}
+
+#[test]
+fn test_sub_byte() {
+    assert_eq!(sub_byte(0x00), 0x63);
+    assert_eq!(sub_byte(0x31), 0xc7);
+    assert_eq!(sub_byte(0x12), 0xc9);
+    assert_eq!(sub_byte(0x9a), 0xb8);
+}
+
+#[test]
+fn test_sub_bytes() {
+    let mut state = [
+        0x00_31_12_9a,
+        0x31_12_9a_00,
+        0x12_9a_00_31,
+        0x9a_00_31_12,
+    ];
+    sub_bytes(&mut state);
+    assert_eq!(state, [
+        0x63_c7_c9_b8,
+        0xc7_c9_b8_63,
+        0xc9_b8_63_c7,
+        0xb8_63_c7_c9,
+    ])
+}
+
+#[test]
+fn test_shift_rows() {
+    let mut state = [
+        0x00_01_02_03,
+        0x04_05_06_07,
+        0x08_09_0a_0b,
+        0x0c_0d_0e_0f,
+    ];
+    shift_rows(&mut state);
+    assert_eq!(state, [
+        0x00_01_02_03,
+        0x05_06_07_04,
+        0x0a_0b_08_09,
+        0x0f_0c_0d_0e,
+    ]);
+
+    let mut state = [
+        0x00_10_20_30,
+        0x40_50_60_70,
+        0x80_90_a0_b0,
+        0xc0_d0_e0_f0,
+    ];
+    shift_rows(&mut state);
+    assert_eq!(state, [
+        0x00_10_20_30,
+        0x50_60_70_40,
+        0xa0_b0_80_90,
+        0xf0_c0_d0_e0,
+    ]);
+}
+
+#[test]
+fn test_mix_columns() {
+    let mut state = [
+        0x01_01_10_01,
+        0x01_02_20_01,
+        0x01_03_40_01,
+        0x01_04_80_01];
+    mix_columns(&mut state);
+    assert_eq!(state, [
+        0x01_03_80_01,
+        0x01_04_10_01,
+        0x01_09_2b_01,
+        0x01_0a_4b_01
+    ]);
+}
+
+#[test]
+fn test_add_round_key() {
+    let mut state = [
+        0x00_01_02_03,
+        0x04_05_06_07,
+        0x08_09_0a_0b,
+        0x0c_0d_0e_0f,
+    ];
+    add_round_key(&mut state, &[0x33333333, 0xcccccccc, 0x55555555, 0xaaaaaaaa]);
+    assert_eq!(state, [
+        0x00_01_02_03 ^ 0x33333333,
+        0x04_05_06_07 ^ 0xcccccccc,
+        0x08_09_0a_0b ^ 0x55555555,
+        0x0c_0d_0e_0f ^ 0xaaaaaaaa,
+    ]);
+}
+
+#[test]
+fn test_inv_add_round_key() {
+    let mut state = [
+        0x00_01_02_03,
+        0x04_05_06_07,
+        0x08_09_0a_0b,
+        0x0c_0d_0e_0f,
+    ];
+    inv_add_round_key(&mut state, &[0x33333333, 0xcccccccc, 0x55555555, 0xaaaaaaaa]);
+    assert_eq!(state, [
+        0x00_01_02_03 ^ 0x33333333,
+        0x04_05_06_07 ^ 0xcccccccc,
+        0x08_09_0a_0b ^ 0x55555555,
+        0x0c_0d_0e_0f ^ 0xaaaaaaaa,
+    ]);
+}
+
+#[test]
+fn test_inv_mix_columns() {
+    let mut state = [
+        0x01_03_80_01,
+        0x01_04_10_01,
+        0x01_09_2b_01,
+        0x01_0a_4b_01];
+    inv_mix_columns(&mut state);
+    assert_eq!(state, [
+        0x01_01_10_01,
+        0x01_02_20_01,
+        0x01_03_40_01,
+        0x01_04_80_01
+    ])
+}
+
+#[test]
+fn test_inv_shift_rows() {
+    let mut state = [
+        0x00_01_02_03,
+        0x05_06_07_04,
+        0x0a_0b_08_09,
+        0x0f_0c_0d_0e,
+    ];
+    inv_shift_rows(&mut state);
+    assert_eq!(state, [
+        0x00_01_02_03,
+        0x04_05_06_07,
+        0x08_09_0a_0b,
+        0x0c_0d_0e_0f,
+    ]);
+
+    let mut state = [
+        0x00_10_20_30,
+        0x50_60_70_40,
+        0xa0_b0_80_90,
+        0xf0_c0_d0_e0,
+    ];
+    inv_shift_rows(&mut state);
+    assert_eq!(state, [
+        0x00_10_20_30,
+        0x40_50_60_70,
+        0x80_90_a0_b0,
+        0xc0_d0_e0_f0,
+    ]);
+}
+
+#[test]
+fn test_inv_sub_bytes() {
+    let mut state = [
+        0x63_c7_c9_b8,
+        0xc7_c9_b8_63,
+        0xc9_b8_63_c7,
+        0xb8_63_c7_c9,
+    ];
+    inv_sub_bytes(&mut state);
+    assert_eq!(state, [
+        0x00_31_12_9a,
+        0x31_12_9a_00,
+        0x12_9a_00_31,
+        0x9a_00_31_12,
+    ])
+}
+
+#[test]
+fn test_pick_state_from_slice() {
+    assert_eq!(pick_state_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]),
+               [
+                   0x00_04_08_0c,
+                   0x01_05_09_0d,
+                   0x02_06_0a_0e,
+                   0x03_07_0b_0f]);
+
+    assert_eq!(pick_state_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+               [
+                   0x00_04_08_00,
+                   0x01_05_09_00,
+                   0x02_06_00_00,
+                   0x03_07_00_00]);
+}
+
+#[test]
+fn test_push_state() {
+    let mut vec = Vec::new();
+    push_state(&mut vec, [0x00_04_08_0c,
+        0x01_05_09_0d,
+        0x02_06_0a_0e,
+        0x03_07_0b_0f]);
+    assert_eq!(vec, vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+}
+
+#[test]
+fn test_encrypt_decrypt() {
+    fn test(key: Key) {
+        let mut rng = thread_rng();
+        let length = rng.gen_range(1, 128);
+        let mut input = Vec::with_capacity(length);
+        for _ in 0..length {
+            input.push(rng.gen());
+        }
+        let encrypted = encrypt(&input.clone(), &key);
+        input.resize((input.len() + 15) & !15, 0);
+        assert_eq!(decrypt(&encrypted, &key), input);
+    }
+    for _ in 0..32 {
+        test(keygen_128());
+    }
+    for _ in 0..32 {
+        test(keygen_192());
+    }
+    for _ in 0..32 {
+        test(keygen_256());
+    }
+}
+
+#[test]
+fn test_key_to_u32() {
+    assert_eq!(Key::AES128([0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c]).to_u32(),
+               vec![0x2b7e1516, 0x28aed2a6, 0xabf71588, 0x09cf4f3c]);
+}
+
+/// The last round key of the expansions in FIPS-197 Appendix A, as the words w\[i\].
+#[test]
+fn test_generate_sub_key() {
+    fn last(key: &str) -> [u32; 4] {
+        let sub_key = match generate_sub_key(&parse_key(key).unwrap()) {
+            SubKey::AES128(sub_key) => sub_key.to_vec(),
+            SubKey::AES192(sub_key) => sub_key.to_vec(),
+            SubKey::AES256(sub_key) => sub_key.to_vec(),
+        };
+        *sub_key.last().unwrap()
+    }
+    assert_eq!(last("2b7e151628aed2a6abf7158809cf4f3c"), [0xd014f9a8, 0xc9ee2589, 0xe13f0cc8, 0xb6630ca6]);
+    assert_eq!(last("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b"), [0xe98ba06f, 0x448c773c, 0x8ecc7204, 0x01002202]);
+    assert_eq!(last("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"), [0xfe4890d1, 0xe6188d0b, 0x046df344, 0x706c631e]);
+}
+
+/// The cipher example of FIPS-197 Appendix B and the examples of Appendix C.
+#[test]
+fn test_fips_197() {
+    fn check(key: &str, plaintext: [u8; 16], ciphertext: [u8; 16]) {
+        let key = parse_key(key).unwrap();
+        assert_eq!(encrypt(&plaintext, &key), ciphertext);
+        assert_eq!(decrypt(&ciphertext, &key), plaintext);
+    }
+    check("2b7e151628aed2a6abf7158809cf4f3c",
+          [0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37, 0x07, 0x34],
+          [0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11, 0x85, 0x97, 0x19, 0x6a, 0x0b, 0x32]);
+    let plaintext = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+    check("000102030405060708090a0b0c0d0e0f", plaintext,
+          [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a]);
+    check("000102030405060708090a0b0c0d0e0f1011121314151617", plaintext,
+          [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91]);
+    check("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f", plaintext,
+          [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89]);
+}
+
+#[test]
+fn test_backends() {
+    let mut rng = thread_rng();
+    for key in [keygen_128(), keygen_192(), keygen_256()] {
+        let input: Vec<u8> = (0..100).map(|_| rng.gen()).collect();
+        let encrypted = encrypt(&input, &key);
+        for backend in Backend::ALL.iter().copied().filter(Backend::is_available) {
+            assert_eq!(encrypt_with(&input, &key, backend), encrypted);
+            assert_eq!(decrypt_with(&encrypted, &key, backend), decrypt(&encrypted, &key));
+        }
+    }
+    assert_eq!("ttable".parse(), Ok(Backend::TTable));
+    assert_eq!("bitsliced".parse(), Ok(Backend::Bitsliced));
+    assert_eq!("tables".parse::<Backend>(), Err(UnknownBackend("tables".to_string())));
+    assert_eq!("aesni".parse(), Ok(Backend::AesNi));
+    assert!(Backend::default().is_available());
+    assert_ne!(Backend::default(), Backend::TTable);
+}
@@ -0,0 +1,142 @@
+//! Table-driven AES engine.
+//!
+//! The reference code in the crate root multiplies every byte of MixColumns bit by bit and keeps
+//! the state as rows. This module computes the same function on the columns, as in the Rijndael
+//! proposal: SubBytes, ShiftRows and MixColumns of a round become four lookups per column in the
+//! 256-entry tables `TE`, whose entries are the S-box output already multiplied by a column of
+//! the MixColumns matrix. Decryption runs the equivalent inverse cipher of FIPS-197 section 5.3.5
+//! with the tables `TD`, so its round keys go through InvMixColumns once when the key is set.
+//!
+//! Lookups are indexed by secret data, so the time taken depends on the cache and leaks it.
+
+use std::convert::TryInto;
+
+use crate::{generate_sub_key, Key, INV_S_BOX, S_BOX};
+
+#[cfg(test)]
+mod test;
+
+/// Multiplication by x in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1.
+const fn xtime(x: u8) -> u8 {
+    (x << 1) ^ ((x >> 7) * 0x1b)
+}
+
+const fn mul(mut x: u8, mut y: u8) -> u8 {
+    let mut result = 0;
+    while y != 0 {
+        if y & 1 != 0 {
+            result ^= x;
+        }
+        x = xtime(x);
+        y >>= 1;
+    }
+    result
+}
+
+/// `table[0][x]` is the column `coefficients` times `sbox[x]`, first row in the most significant
+/// byte, and `table[i]` is `table[0]` rotated right by `i` bytes.
+const fn round_tables(sbox: &[u8; 256], coefficients: [u8; 4]) -> [[u32; 256]; 4] {
+    let mut result = [[0; 256]; 4];
+    let mut x = 0;
+    while x < 256 {
+        let s = sbox[x];
+        let word = u32::from_be_bytes([
+            mul(s, coefficients[0]),
+            mul(s, coefficients[1]),
+            mul(s, coefficients[2]),
+            mul(s, coefficients[3]),
+        ]);
+        let mut i = 0;
+        while i < 4 {
+            result[i][x] = word.rotate_right(8 * i as u32);
+            i += 1;
+        }
+        x += 1;
+    }
+    result
+}
+
+/// Te0 to Te3: SubBytes followed by MixColumns, for one byte of each row.
+static TE: [[u32; 256]; 4] = round_tables(&S_BOX, [2, 1, 1, 3]);
+
+/// Td0 to Td3: InvSubBytes followed by InvMixColumns.
+static TD: [[u32; 256]; 4] = round_tables(&INV_S_BOX, [14, 9, 13, 11]);
+
+/// InvMixColumns of one column, for the round keys of the equivalent inverse cipher.
+fn inv_mix_column(word: u32) -> u32 {
+    // Td without InvSubBytes: S-box the bytes first so that the table undoes it.
+    let [a, b, c, d] = word.to_be_bytes().map(|x| S_BOX[x as usize] as usize);
+    TD[0][a] ^ TD[1][b] ^ TD[2][c] ^ TD[3][d]
+}
+
+#[inline(always)]
+fn byte(word: u32, i: u32) -> usize {
+    (word >> (24 - 8 * i)) as u8 as usize
+}
+
+/// AES with the encryption and decryption round keys of one key computed up front.
+#[derive(Debug, Clone)]
+pub struct TTableAes {
+    /// The words w\[i\] of FIPS-197, one round key per entry.
+    encryption: Vec<[u32; 4]>,
+    /// The words dw\[i\] of the equivalent inverse cipher, in the order decryption uses them.
+    decryption: Vec<[u32; 4]>,
+}
+
+impl TTableAes {
+    pub fn new(key: &Key) -> TTableAes {
+        let round_keys = generate_sub_key(key);
+        let round_keys = round_keys.words();
+        let encryption = round_keys.to_vec();
+        let last = round_keys.len() - 1;
+        let decryption = round_keys.iter().rev().enumerate().map(|(i, round_key)| {
+            round_key.map(|word| if i == 0 || i == last { word } else { inv_mix_column(word) })
+        }).collect();
+        TTableAes { encryption, decryption }
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let (first, rest) = self.encryption.split_first().unwrap();
+        let (k, middle) = rest.split_last().unwrap();
+        let mut s = load(block, first);
+        for k in middle {
+            s = [0, 1, 2, 3].map(|c| {
+                TE[0][byte(s[c], 0)] ^ TE[1][byte(s[(c + 1) & 3], 1)] ^ TE[2][byte(s[(c + 2) & 3], 2)] ^ TE[3][byte(s[(c + 3) & 3], 3)] ^ k[c]
+            });
+        }
+        let s = [0, 1, 2, 3].map(|c| {
+            u32::from_be_bytes([0, 1, 2, 3].map(|r| S_BOX[byte(s[(c + r) & 3], r as u32)])) ^ k[c]
+        });
+        store(s)
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 16]) -> [u8; 16] {
+        let (first, rest) = self.decryption.split_first().unwrap();
+        let (k, middle) = rest.split_last().unwrap();
+        let mut s = load(block, first);
+        for k in middle {
+            s = [0, 1, 2, 3].map(|c| {
+                TD[0][byte(s[c], 0)] ^ TD[1][byte(s[(c + 3) & 3], 1)] ^ TD[2][byte(s[(c + 2) & 3], 2)] ^ TD[3][byte(s[(c + 1) & 3], 3)] ^ k[c]
+            });
+        }
+        let s = [0, 1, 2, 3].map(|c| {
+            u32::from_be_bytes([0, 1, 2, 3].map(|r| INV_S_BOX[byte(s[(c + 4 - r) & 3], r as u32)])) ^ k[c]
+        });
+        store(s)
+    }
+}
+
+/// The columns of `block` with the first round key added.
+#[inline(always)]
+fn load(block: [u8; 16], key: &[u32; 4]) -> [u32; 4] {
+    [0, 1, 2, 3].map(|c| u32::from_be_bytes(block[4 * c..4 * c + 4].try_into().unwrap()) ^ key[c])
+}
+
+#[inline(always)]
+fn store(state: [u32; 4]) -> [u8; 16] {
+    let mut block = [0; 16];
+    for (chunk, column) in block.chunks_mut(4).zip(state.iter()) {
+        chunk.copy_from_slice(&column.to_be_bytes());
+    }
+    block
+}
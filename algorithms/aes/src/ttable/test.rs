@@ -0,0 +1,38 @@
+use rand::{thread_rng, Rng};
+
+use crate::{keygen_128, keygen_192, keygen_256, parse_key, Aes};
+
+use super::*;
+
+#[test]
+fn test_tables() {
+    assert_eq!(TE[0][0x00], 0xc66363a5);
+    assert_eq!(TE[1][0x00], 0xa5c66363);
+    assert_eq!(TE[3][0xff], 0x16163a2c);
+    assert_eq!(TD[0][0x00], 0x51f4a750);
+    assert_eq!(TD[2][0x00], 0xa75051f4);
+    assert_eq!(inv_mix_column(0x8e4da1bc), 0xdb135345);
+}
+
+#[test]
+fn test_known_answer() {
+    let plaintext = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+    let aes = TTableAes::new(&parse_key("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").unwrap());
+    let ciphertext = [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89];
+    assert_eq!(aes.encrypt_block(plaintext), ciphertext);
+    assert_eq!(aes.decrypt_block(ciphertext), plaintext);
+}
+
+#[test]
+fn test_cross_check() {
+    let mut rng = thread_rng();
+    for _ in 0..100 {
+        for key in [keygen_128(), keygen_192(), keygen_256()] {
+            let block: [u8; 16] = rng.gen();
+            let reference = Aes::new(&key);
+            let aes = TTableAes::new(&key);
+            assert_eq!(aes.encrypt_block(block), reference.encrypt_block(block));
+            assert_eq!(aes.decrypt_block(block), reference.decrypt_block(block));
+        }
+    }
+}
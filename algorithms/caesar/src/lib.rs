@@ -0,0 +1,114 @@
+//! Caesar cipher over the lowercase latin alphabet.
+//!
+//! Every letter is rotated forward by the key to encrypt and backward to decrypt. Only the
+//! lowercase letters `a`–`z` are accepted; anything else is rejected with [`Error::InputValueError`].
+//!
+//! ```
+//! use caesar::{decrypt, encrypt, Key};
+//!
+//! let key = Key::new(3);
+//! assert_eq!(encrypt("attack", key).unwrap(), "dwwdfn");
+//! assert_eq!(decrypt("dwwdfn", key).unwrap(), "attack");
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+pub const ALPHABET_COUNT: u8 = 26;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The input contains a character other than a lowercase latin letter.
+    InputValueError(&'static str),
+    /// The key is not a number.
+    KeyValueError(&'static str),
+    /// The output buffer given to one of the `_into` functions is shorter than the input.
+    BufferTooSmall { required: usize, actual: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InputValueError(message) => write!(f, "invalid input: {}", message),
+            Error::KeyValueError(message) => write!(f, "invalid key: {}", message),
+            Error::BufferTooSmall { required, actual } => write!(f, "output buffer too small: {} bytes required, {} given", required, actual),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Amount of rotation, always kept in `0..26`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key(u8);
+
+impl Key {
+    /// Creates a key rotating by `shift`, reduced modulo 26.
+    pub fn new(shift: usize) -> Key {
+        Key((shift % ALPHABET_COUNT as usize) as u8)
+    }
+
+    pub fn shift(self) -> u8 {
+        self.0
+    }
+
+    /// The key which undoes this one, so that encrypting with it decrypts.
+    pub fn inverse(self) -> Key {
+        Key((ALPHABET_COUNT - self.0) % ALPHABET_COUNT)
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Key, Error> {
+        s.trim().parse().map(Key::new).map_err(|_| Error::KeyValueError("key should be number"))
+    }
+}
+
+fn check_input(input: &[u8]) -> Result<(), Error> {
+    if input.iter().any(|c| !c.is_ascii_lowercase()) {
+        return Err(Error::InputValueError("All input characters should be lowercase alphabet."));
+    }
+    Ok(())
+}
+
+fn rotate_into(input: &[u8], shift: u8, output: &mut [u8]) -> Result<usize, Error> {
+    check_input(input)?;
+    if output.len() < input.len() {
+        return Err(Error::BufferTooSmall { required: input.len(), actual: output.len() });
+    }
+    for (o, &c) in output.iter_mut().zip(input.iter()) {
+        *o = (c - b'a' + shift) % ALPHABET_COUNT + b'a';
+    }
+    Ok(input.len())
+}
+
+/// Encrypts lowercase ASCII `input` into the front of `output` without allocating.
+///
+/// Returns the number of bytes written, which is always `input.len()`.
+pub fn encrypt_into(input: &[u8], key: Key, output: &mut [u8]) -> Result<usize, Error> {
+    rotate_into(input, key.0, output)
+}
+
+/// Decrypts lowercase ASCII `input` into the front of `output` without allocating.
+///
+/// Returns the number of bytes written, which is always `input.len()`.
+pub fn decrypt_into(input: &[u8], key: Key, output: &mut [u8]) -> Result<usize, Error> {
+    rotate_into(input, key.inverse().0, output)
+}
+
+pub fn encrypt(input: &str, key: Key) -> Result<String, Error> {
+    let mut result = vec![0; input.len()];
+    encrypt_into(input.as_bytes(), key, &mut result)?;
+    Ok(String::from_utf8(result).unwrap())
+}
+
+pub fn decrypt(input: &str, key: Key) -> Result<String, Error> {
+    let mut result = vec![0; input.len()];
+    decrypt_into(input.as_bytes(), key, &mut result)?;
+    Ok(String::from_utf8(result).unwrap())
+}
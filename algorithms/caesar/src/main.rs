@@ -2,13 +2,7 @@ use std::io::Read;
 
 use clap::{App, Arg};
 
-#[cfg(test)]
-mod test;
-
-#[derive(Debug, PartialEq)]
-enum Error {
-    InputValueError(&'static str)
-}
+use caesar::{decrypt, encrypt, Key};
 
 fn main() {
     let matches = App::new("caesar")
@@ -29,45 +23,18 @@ fn main() {
         std::io::stdin().read_to_string(&mut s).expect("failed to read standard input");
         s
     });
-    if let Ok(key) = matches.value_of("key").unwrap().parse() {
-        if matches.is_present("decrypt") {
-            match decrypt(&input, key) {
-                Ok(result) => println!("{}", result),
-                Err(e) => eprintln!("error:{:?}", e),
-            }
-        } else {
-            match encrypt(&input, key) {
+    match matches.value_of("key").unwrap().parse::<Key>() {
+        Ok(key) => {
+            let result = if matches.is_present("decrypt") {
+                decrypt(&input, key)
+            } else {
+                encrypt(&input, key)
+            };
+            match result {
                 Ok(result) => println!("{}", result),
-                Err(e) => eprintln!("error:{:?}", e),
+                Err(e) => eprintln!("error: {}", e),
             }
         }
-    } else {
-        eprintln!("argument 'key' should be number");
-    }
-}
-
-const ALPHABET_COUNT: u8 = 26;
-
-fn encrypt(input: &str, key: usize) -> Result<String, Error> {
-    if input.chars().any(|c| !c.is_alphabetic() || !c.is_lowercase()) {
-        return Err(Error::InputValueError("All input characters should be lowercase alphabet."));
-    }
-    let mut result = String::with_capacity(input.chars().count());
-    let key = (key % ALPHABET_COUNT as usize) as u8;
-    for c in input.chars() {
-        result.push(((c as u8 - 'a' as u8 + key) % ALPHABET_COUNT + 'a' as u8) as char);
-    }
-    Ok(result)
-}
-
-fn decrypt(input: &str, key: usize) -> Result<String, Error> {
-    if input.chars().any(|c| !c.is_alphabetic() || !c.is_lowercase()) {
-        return Err(Error::InputValueError("All input characters should be lowercase alphabet."));
-    }
-    let mut result = String::with_capacity(input.chars().count());
-    let key = (key % ALPHABET_COUNT as usize) as u8;
-    for c in input.chars() {
-        result.push(((c as u8 - 'a' as u8 + ALPHABET_COUNT - key) % ALPHABET_COUNT + 'a' as u8) as char);
+        Err(e) => eprintln!("error in parsing key: {}", e),
     }
-    Ok(result)
 }
@@ -1,23 +1,52 @@
-use super::{encrypt, decrypt};
+use super::*;
+
+#[test]
+fn test_key() {
+    assert_eq!(Key::new(3).shift(), 3);
+    assert_eq!(Key::new(29).shift(), 3);
+    assert_eq!(Key::new(26).shift(), 0);
+    assert_eq!(Key::new(3).inverse(), Key::new(23));
+    assert_eq!(Key::new(0).inverse(), Key::new(0));
+    assert_eq!("5".parse(), Ok(Key::new(5)));
+    assert_eq!("a".parse::<Key>(), Err(Error::KeyValueError("key should be number")));
+}
 
 #[test]
 fn test_encrypt() {
-    assert_eq!(encrypt("abcdefghijklmnopqrstuvwxyz", 3), Ok("defghijklmnopqrstuvwxyzabc".to_string()));
-    assert_eq!(encrypt("abcdefghijklmnopqrstuvwxyz", 5), Ok("fghijklmnopqrstuvwxyzabcde".to_string()));
-    encrypt("A", 5).unwrap_err();
-    encrypt("0", 5).unwrap_err();
-    encrypt("+", 5).unwrap_err();
-    encrypt("/", 5).unwrap_err();
-    encrypt("!", 5).unwrap_err();
+    assert_eq!(encrypt("abcdefghijklmnopqrstuvwxyz", Key::new(3)), Ok("defghijklmnopqrstuvwxyzabc".to_string()));
+    assert_eq!(encrypt("abcdefghijklmnopqrstuvwxyz", Key::new(5)), Ok("fghijklmnopqrstuvwxyzabcde".to_string()));
+    encrypt("A", Key::new(5)).unwrap_err();
+    encrypt("0", Key::new(5)).unwrap_err();
+    encrypt("+", Key::new(5)).unwrap_err();
+    encrypt("/", Key::new(5)).unwrap_err();
+    encrypt("!", Key::new(5)).unwrap_err();
+    encrypt("é", Key::new(5)).unwrap_err();
+}
+
+#[test]
+fn test_decrypt() {
+    assert_eq!(decrypt("defghijklmnopqrstuvwxyzabc", Key::new(3)), Ok("abcdefghijklmnopqrstuvwxyz".to_string()));
+    assert_eq!(decrypt("fghijklmnopqrstuvwxyzabcde", Key::new(5)), Ok("abcdefghijklmnopqrstuvwxyz".to_string()));
+    decrypt("A", Key::new(5)).unwrap_err();
+    decrypt("0", Key::new(5)).unwrap_err();
+    decrypt("+", Key::new(5)).unwrap_err();
+    decrypt("/", Key::new(5)).unwrap_err();
+    decrypt("!", Key::new(5)).unwrap_err();
 }
 
 #[test]
-fn test_decrypt(){
-    assert_eq!(decrypt("defghijklmnopqrstuvwxyzabc", 3), Ok("abcdefghijklmnopqrstuvwxyz".to_string()));
-    assert_eq!(decrypt("fghijklmnopqrstuvwxyzabcde", 5), Ok("abcdefghijklmnopqrstuvwxyz".to_string()));
-    decrypt("A", 5).unwrap_err();
-    decrypt("0", 5).unwrap_err();
-    decrypt("+", 5).unwrap_err();
-    decrypt("/", 5).unwrap_err();
-    decrypt("!", 5).unwrap_err();
-}
\ No newline at end of file
+fn test_encrypt_decrypt_into() {
+    let mut buffer = [0; 8];
+    assert_eq!(encrypt_into(b"xyz", Key::new(3), &mut buffer), Ok(3));
+    assert_eq!(&buffer[..3], b"abc");
+    assert_eq!(decrypt_into(b"abc", Key::new(3), &mut buffer[3..]), Ok(3));
+    assert_eq!(&buffer[..6], b"abcxyz");
+    assert_eq!(encrypt_into(b"abc", Key::new(1), &mut buffer[..2]), Err(Error::BufferTooSmall { required: 3, actual: 2 }));
+    assert!(encrypt_into(b"aBc", Key::new(1), &mut buffer).is_err());
+}
+
+#[test]
+fn test_error_display() {
+    assert_eq!(Error::BufferTooSmall { required: 3, actual: 2 }.to_string(), "output buffer too small: 3 bytes required, 2 given");
+    assert_eq!(Error::InputValueError("bad").to_string(), "invalid input: bad");
+}
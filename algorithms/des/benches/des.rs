@@ -0,0 +1,41 @@
+use std::convert::TryInto;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use des::bitslice;
+use des::fast::FastDes;
+use des::{Des, DesKey};
+
+const SIZES: [usize; 2] = [1 << 10, 1 << 16];
+
+fn bench_block(c: &mut Criterion) {
+    let key = DesKey::from(0x133457799bbcdff1);
+    let mut group = c.benchmark_group("block");
+    group.throughput(Throughput::Bytes(8));
+    let des = Des::new(&key);
+    group.bench_function("bool", |b| b.iter(|| des.encrypt_block(black_box([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]))));
+    let fast = FastDes::new(&key);
+    group.bench_function("fast", |b| b.iter(|| fast.encrypt_block(black_box([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]))));
+    group.finish();
+}
+
+fn bench_ecb(c: &mut Criterion) {
+    let key = DesKey::from(0x133457799bbcdff1);
+    let mut group = c.benchmark_group("ecb");
+    for &size in SIZES.iter() {
+        let input: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        group.throughput(Throughput::Bytes(size as u64));
+        if size <= 1 << 10 {
+            let des = Des::new(&key);
+            group.bench_with_input(BenchmarkId::new("bool", size), &input, |b, input| b.iter(|| {
+                input.chunks(8).map(|chunk| des.encrypt_block(chunk.try_into().unwrap())).collect::<Vec<_>>()
+            }));
+        }
+        group.bench_with_input(BenchmarkId::new("fast", size), &input, |b, input| b.iter(|| des::encrypt(input, &key)));
+        group.bench_with_input(BenchmarkId::new("bitsliced", size), &input, |b, input| b.iter(|| bitslice::encrypt(input, &key)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_block, bench_ecb);
+criterion_main!(benches);
@@ -0,0 +1,292 @@
+//! Bitsliced DES, 64 blocks at a time.
+//!
+//! Word `i` of the state holds bit `i + 1` (FIPS numbering) of 64 independent blocks, one block
+//! per bit of the word, called a lane. Permutations and E then only rename words, and every S-box
+//! is a Boolean circuit on six words built from its algebraic normal form: all products of the
+//! inputs, and for each output bit the XOR of the products of its ANF. Lanes may use different
+//! keys, which is what exhaustive key search needs.
+//!
+//! There are no table lookups indexed by data and no branches on it, so this is also a
+//! constant-time DES to compare [`crate::fast`] against.
+
+use std::convert::TryInto;
+
+use crate::provenance::sub_key_sources;
+use crate::{DesKey, E, IP, IP_INVERSE, P, S_BOX};
+
+#[cfg(test)]
+mod test;
+
+/// Blocks processed at once.
+pub const LANES: usize = 64;
+
+/// `LANE_PATTERNS[j]` has the bit of lane `l` set when bit `j` of `l` is set, the slice of a key
+/// bit which counts through the lanes.
+pub const LANE_PATTERNS: [u64; 6] = [
+    0xaaaaaaaaaaaaaaaa,
+    0xcccccccccccccccc,
+    0xf0f0f0f0f0f0f0f0,
+    0xff00ff00ff00ff00,
+    0xffff0000ffff0000,
+    0xffffffff00000000,
+];
+
+/// `ANF[i][j]` has bit `m` set when the product of the inputs selected by `m` is a term of output
+/// bit `j` (0 is the most significant) of S-box `i`. Bit 5 of `m` stands for the first of the six
+/// input bits.
+const ANF: [[u64; 4]; 8] = anf_table();
+
+const fn anf_table() -> [[u64; 4]; 8] {
+    let mut result = [[0; 4]; 8];
+    let mut i = 0;
+    while i < 8 {
+        let mut j = 0;
+        while j < 4 {
+            let mut truth = 0u64;
+            let mut x = 0;
+            while x < 64 {
+                let row = (x >> 4 & 0b10) | (x & 1);
+                let column = x >> 1 & 0b1111;
+                if S_BOX[i][row][column] >> (3 - j) & 1 != 0 {
+                    truth |= 1 << x;
+                }
+                x += 1;
+            }
+            // Möbius transform: XOR every coefficient into those of the monomials containing it.
+            let mut k = 0;
+            while k < 6 {
+                truth ^= (truth & !LANE_PATTERNS[k]) << (1 << k);
+                k += 1;
+            }
+            result[i][j] = truth;
+            j += 1;
+        }
+        i += 1;
+    }
+    result
+}
+
+/// `P_INVERSE[i]` is the position (0-based) the S-box output bit `i` is moved to by P.
+const P_INVERSE: [usize; 32] = p_inverse();
+
+const fn p_inverse() -> [usize; 32] {
+    let mut result = [0; 32];
+    let mut i = 0;
+    while i < 32 {
+        result[P[i] - 1] = i;
+        i += 1;
+    }
+    result
+}
+
+/// Expands to one statement per monomial `m` of the 64, so that every index and every test on a
+/// constant ANF is known at compile time and only the ANDs and XORs remain.
+macro_rules! for_each_monomial {
+    ($m:ident => $body:block) => {
+        for_each_monomial!(@ $m $body; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63)
+    };
+    (@ $m:ident $body:block; $($value:literal)*) => {
+        $({
+            const $m: usize = $value;
+            $body
+        })*
+    };
+}
+
+/// S-box `I` as a circuit: the 63 products of two or more inputs, then one XOR per term.
+#[inline(always)]
+fn sbox<const I: usize>(input: &[u64; 6]) -> [u64; 4] {
+    let mut products = [!0u64; 64];
+    for_each_monomial!(M => {
+        if M != 0 {
+            products[M] = products[M & M.wrapping_sub(1)] & input[M.trailing_zeros() as usize];
+        }
+    });
+    let mut output = [0; 4];
+    for_each_monomial!(M => {
+        for (o, terms) in output.iter_mut().zip(ANF[I].iter()) {
+            if terms >> M & 1 != 0 {
+                *o ^= products[M];
+            }
+        }
+    });
+    output
+}
+
+/// XORs the part of f(`r`, `k`) coming from S-box `I` into `l`.
+#[inline(always)]
+fn sbox_round<const I: usize>(l: &mut [u64; 32], r: &[u64; 32], k: &[u64; 48]) {
+    let mut input = [0; 6];
+    for (b, x) in input.iter_mut().rev().enumerate() {
+        *x = r[E[6 * I + b] - 1] ^ k[6 * I + b];
+    }
+    for (j, y) in sbox::<I>(&input).iter().enumerate() {
+        l[P_INVERSE[4 * I + j]] ^= y;
+    }
+}
+
+/// XORs f(`r`, `k`) into `l`.
+#[inline(always)]
+fn round(l: &mut [u64; 32], r: &[u64; 32], k: &[u64; 48]) {
+    sbox_round::<0>(l, r, k);
+    sbox_round::<1>(l, r, k);
+    sbox_round::<2>(l, r, k);
+    sbox_round::<3>(l, r, k);
+    sbox_round::<4>(l, r, k);
+    sbox_round::<5>(l, r, k);
+    sbox_round::<6>(l, r, k);
+    sbox_round::<7>(l, r, k);
+}
+
+/// The 64x64 bit matrix transpose: bit `j` of word `i` becomes bit `i` of word `j`.
+fn transpose(mut a: [u64; 64]) -> [u64; 64] {
+    let mut width = 32;
+    let mut mask = 0x00000000ffffffffu64;
+    while width != 0 {
+        for k in (0..64).filter(|k| k & width == 0) {
+            let t = (a[k] >> width ^ a[k + width]) & mask;
+            a[k + width] ^= t;
+            a[k] ^= t << width;
+        }
+        width >>= 1;
+        mask ^= mask << width;
+    }
+    a
+}
+
+/// Slices of 64 blocks: bit `l` of word `i` is bit `i + 1` (most significant first) of `blocks[l]`.
+pub fn to_slices(blocks: &[u64; 64]) -> [u64; 64] {
+    let mut slices = transpose(*blocks);
+    slices.reverse();
+    slices
+}
+
+/// The inverse of [`to_slices`].
+pub fn from_slices(slices: &[u64; 64]) -> [u64; 64] {
+    let mut words = *slices;
+    words.reverse();
+    transpose(words)
+}
+
+/// DES on 64 lanes, each lane with its own key.
+#[derive(Debug, Clone)]
+pub struct BitslicedDes {
+    sub_key: [[u64; 48]; 16],
+}
+
+impl BitslicedDes {
+    /// The same key in every lane.
+    pub fn new(key: &DesKey) -> BitslicedDes {
+        let key = key.to_u64();
+        let mut slices = [0; 64];
+        for (i, slice) in slices.iter_mut().enumerate() {
+            *slice = 0u64.wrapping_sub(key >> (63 - i) & 1);
+        }
+        BitslicedDes::from_key_slices(&slices)
+    }
+
+    /// Key `l` in lane `l`. Lanes without a key get the key 0.
+    pub fn from_keys(keys: &[DesKey]) -> BitslicedDes {
+        assert!(keys.len() <= LANES, "at most {} keys", LANES);
+        let mut words = [0; 64];
+        for (word, key) in words.iter_mut().zip(keys.iter()) {
+            *word = key.to_u64();
+        }
+        BitslicedDes::from_key_slices(&to_slices(&words))
+    }
+
+    /// Word `i` of `key` is bit `i + 1` of the keys of all lanes, as [`to_slices`] gives.
+    pub fn from_key_slices(key: &[u64; 64]) -> BitslicedDes {
+        let mut sub_key = [[0; 48]; 16];
+        for (k, sources) in sub_key.iter_mut().zip(sub_key_sources().iter()) {
+            for (k, &source) in k.iter_mut().zip(sources.iter()) {
+                *k = key[source as usize - 1];
+            }
+        }
+        BitslicedDes { sub_key }
+    }
+
+    fn crypt<'a>(block: &[u64; 64], sub_keys: impl Iterator<Item=&'a [u64; 48]>) -> [u64; 64] {
+        let mut l = [0; 32];
+        let mut r = [0; 32];
+        for (i, &source) in IP.iter().enumerate() {
+            if i < 32 {
+                l[i] = block[source - 1];
+            } else {
+                r[i - 32] = block[source - 1];
+            }
+        }
+        for k in sub_keys {
+            round(&mut l, &r, k);
+            std::mem::swap(&mut l, &mut r);
+        }
+        let mut output = [0; 64];
+        for (o, &source) in output.iter_mut().zip(IP_INVERSE.iter()) {
+            *o = if source <= 32 { r[source - 1] } else { l[source - 33] };
+        }
+        output
+    }
+
+    /// Encrypts the blocks of all lanes given as slices.
+    pub fn encrypt_slices(&self, block: &[u64; 64]) -> [u64; 64] {
+        BitslicedDes::crypt(block, self.sub_key.iter())
+    }
+
+    pub fn decrypt_slices(&self, block: &[u64; 64]) -> [u64; 64] {
+        BitslicedDes::crypt(block, self.sub_key.iter().rev())
+    }
+
+    /// Encrypts `blocks[l]` in lane `l`.
+    pub fn encrypt_blocks(&self, blocks: &[u64; 64]) -> [u64; 64] {
+        from_slices(&self.encrypt_slices(&to_slices(blocks)))
+    }
+
+    pub fn decrypt_blocks(&self, blocks: &[u64; 64]) -> [u64; 64] {
+        from_slices(&self.decrypt_slices(&to_slices(blocks)))
+    }
+}
+
+/// Runs `crypt` on the 8-byte blocks of `input`, 64 at a time, and writes the results to
+/// `output`. A trailing partial block is padded with zero bytes.
+fn batches(input: &[u8], output: &mut Vec<u8>, mut crypt: impl FnMut(usize, &[u64; 64]) -> [u64; 64]) {
+    for (n, batch) in input.chunks(8 * LANES).enumerate() {
+        let mut blocks = [0; 64];
+        for (block, chunk) in blocks.iter_mut().zip(batch.chunks(8)) {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *block = u64::from_be_bytes(bytes);
+        }
+        let count = batch.len().div_ceil(8);
+        for block in crypt(n * LANES, &blocks)[..count].iter() {
+            output.extend_from_slice(&block.to_be_bytes());
+        }
+    }
+}
+
+/// Encrypts `input` in ECB mode like [`crate::encrypt`]: the last block is padded with zero bytes.
+pub fn encrypt(input: &[u8], key: &DesKey) -> Vec<u8> {
+    let des = BitslicedDes::new(key);
+    let mut output = Vec::with_capacity(input.len().div_ceil(8) * 8);
+    batches(input, &mut output, |_, blocks| des.encrypt_blocks(blocks));
+    output
+}
+
+/// Decrypts `input` in ECB mode like [`crate::decrypt`].
+pub fn decrypt(input: &[u8], key: &DesKey) -> Vec<u8> {
+    let des = BitslicedDes::new(key);
+    let mut output = Vec::with_capacity(input.len().div_ceil(8) * 8);
+    batches(input, &mut output, |_, blocks| des.decrypt_blocks(blocks));
+    output
+}
+
+/// Encrypts or decrypts `input` in counter mode: block `i` is XORed with the encryption of
+/// `counter + i`, wrapping around. The output is as long as the input.
+pub fn ctr(input: &[u8], key: &DesKey, counter: u64) -> Vec<u8> {
+    let des = BitslicedDes::new(key);
+    let mut keystream = Vec::with_capacity(input.len().div_ceil(8) * 8);
+    batches(input, &mut keystream, |first, _| {
+        let counters: Vec<_> = (0..LANES as u64).map(|i| counter.wrapping_add(first as u64 + i)).collect();
+        des.encrypt_blocks(&counters[..].try_into().unwrap())
+    });
+    input.iter().zip(keystream.iter()).map(|(x, k)| x ^ k).collect()
+}
@@ -0,0 +1,88 @@
+use rand::{thread_rng, Rng};
+
+use crate::fast::FastDes;
+use crate::{enc, generate_sub_key, keygen, pick_64bit_from_slice, to_block};
+
+use super::*;
+
+fn reference(key: &DesKey, block: u64) -> u64 {
+    let sub_key = generate_sub_key(&key.bits());
+    u64::from_be_bytes(to_block(enc(&pick_64bit_from_slice(&block.to_be_bytes()), &sub_key)))
+}
+
+#[test]
+fn test_transpose() {
+    let mut rng = thread_rng();
+    let mut blocks = [0u64; 64];
+    rng.fill(&mut blocks[..]);
+    let t = transpose(blocks);
+    for (i, word) in t.iter().enumerate() {
+        for (j, block) in blocks.iter().enumerate() {
+            assert_eq!(word >> j & 1, block >> i & 1);
+        }
+    }
+    assert_eq!(from_slices(&to_slices(&blocks)), blocks);
+    assert_eq!(to_slices(&blocks)[0] & 1, blocks[0] >> 63);
+}
+
+#[test]
+fn test_sbox() {
+    let outputs = [
+        sbox::<0>(&LANE_PATTERNS), sbox::<1>(&LANE_PATTERNS), sbox::<2>(&LANE_PATTERNS), sbox::<3>(&LANE_PATTERNS),
+        sbox::<4>(&LANE_PATTERNS), sbox::<5>(&LANE_PATTERNS), sbox::<6>(&LANE_PATTERNS), sbox::<7>(&LANE_PATTERNS),
+    ];
+    for (i, output) in outputs.iter().enumerate() {
+        for x in 0..64 {
+            let expected = S_BOX[i][(x >> 4 & 0b10) | (x & 1)][x >> 1 & 0b1111];
+            let actual = output.iter().fold(0, |acc, y| acc << 1 | (y >> x & 1) as u8);
+            assert_eq!(actual, expected, "S{}({:06b})", i + 1, x);
+        }
+    }
+}
+
+#[test]
+fn test_against_reference() {
+    let mut rng = thread_rng();
+    let keys: Vec<_> = (0..LANES).map(|_| keygen()).collect();
+    let mut blocks = [0u64; 64];
+    rng.fill(&mut blocks[..]);
+    let des = BitslicedDes::from_keys(&keys);
+    let encrypted = des.encrypt_blocks(&blocks);
+    for ((key, &block), &c) in keys.iter().zip(blocks.iter()).zip(encrypted.iter()) {
+        assert_eq!(c, reference(key, block));
+    }
+    assert_eq!(des.decrypt_blocks(&encrypted), blocks);
+
+    let key = DesKey::from(0x133457799bbcdff1);
+    let encrypted = BitslicedDes::new(&key).encrypt_blocks(&[0x0123456789abcdef; 64]);
+    assert!(encrypted.iter().all(|&c| c == 0x85e813540f0ab405));
+}
+
+#[test]
+fn test_ecb() {
+    let mut rng = thread_rng();
+    let key = keygen();
+    for &len in [0, 1, 8, 100, 512, 513, 1500].iter() {
+        let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        let encrypted = encrypt(&input, &key);
+        assert_eq!(encrypted, crate::encrypt(&input, &key));
+        assert_eq!(decrypt(&encrypted, &key), crate::decrypt(&encrypted, &key));
+    }
+}
+
+#[test]
+fn test_ctr() {
+    let mut rng = thread_rng();
+    let key = keygen();
+    let input: Vec<u8> = (0..1000).map(|_| rng.gen()).collect();
+    let counter = u64::MAX - 70;
+    let encrypted = ctr(&input, &key, counter);
+    assert_eq!(encrypted.len(), input.len());
+    let des = FastDes::new(&key);
+    for (i, (chunk, block)) in input.chunks(8).zip(encrypted.chunks(8)).enumerate() {
+        let keystream = des.encrypt_u64(counter.wrapping_add(i as u64)).to_be_bytes();
+        let expected: Vec<_> = chunk.iter().zip(keystream.iter()).map(|(x, k)| x ^ k).collect();
+        assert_eq!(block, &expected[..]);
+    }
+    assert_eq!(ctr(&encrypted, &key, counter), input);
+}
@@ -0,0 +1,255 @@
+//! Exhaustive key search with a known plaintext, the attack the EFF DES cracker ran in 1998.
+//!
+//! The candidate keys are the keys of a [`KeySpace`], split into chunks that worker threads take
+//! in turn. Parity bits are never enumerated. Keys are tried 64 at a time with [`BitslicedDes`],
+//! one key per lane: the lowest six free bits count through the lanes and the plaintext is the
+//! same in all of them, so a batch needs neither a key schedule nor a transposition, and the
+//! comparison with the ciphertext gives the matching lanes directly.
+//!
+//! DES has the complementation property `E_~k(~P) = ~E_k(P)`. Given also the ciphertext `C'` of
+//! the complemented plaintext, one encryption `E_k(P)` tests two keys: `k` is the key if it equals
+//! `C`, and `~k` is if it equals `~C'`.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bitslice::{BitslicedDes, LANES, LANE_PATTERNS};
+use crate::fast::FastDes;
+use crate::keyspace::{KeySpace, PARITY_MASK};
+use crate::DesKey;
+
+#[cfg(test)]
+mod test;
+
+/// Encryptions per chunk handed to a worker, and between two updates of the shared counter.
+const CHUNK_BITS: u32 = 16;
+
+#[derive(Debug, Clone)]
+pub struct BruteForce {
+    plaintext: u64,
+    ciphertext: u64,
+    complement_ciphertext: Option<u64>,
+    space: KeySpace,
+    threads: usize,
+}
+
+impl BruteForce {
+    /// Searches `space` for a key encrypting `plaintext` to `ciphertext`, on as many threads as
+    /// the machine runs in parallel.
+    pub fn new(plaintext: u64, ciphertext: u64, space: KeySpace) -> BruteForce {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        BruteForce { plaintext, ciphertext, complement_ciphertext: None, space, threads }
+    }
+
+    /// Uses the encryption of `!plaintext` under the same key to test two keys per encryption.
+    ///
+    /// When the space holds every key, only the half with its highest free bit cleared is
+    /// encrypted. Otherwise the complements lie outside the space, with every known bit flipped,
+    /// and the search covers the complemented space as well at no extra cost.
+    pub fn with_complement(mut self, complement_ciphertext: u64) -> BruteForce {
+        self.complement_ciphertext = Some(complement_ciphertext);
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> BruteForce {
+        assert!(threads > 0, "at least one thread is needed");
+        self.threads = threads;
+        self
+    }
+
+    pub fn space(&self) -> &KeySpace {
+        &self.space
+    }
+
+    /// Whether complementing a key of the space gives a key of the space.
+    fn closed_under_complement(&self) -> bool {
+        self.space.mask() == !PARITY_MASK
+    }
+
+    /// Number of keys of the space enumerated, one encryption each.
+    pub fn encryptions(&self) -> u64 {
+        if self.complement_ciphertext.is_some() && self.closed_under_complement() {
+            self.space.size() / 2
+        } else {
+            self.space.size()
+        }
+    }
+
+    /// Number of distinct keys the search covers.
+    pub fn keys(&self) -> u64 {
+        match self.complement_ciphertext {
+            Some(_) => self.encryptions() * 2,
+            None => self.encryptions(),
+        }
+    }
+
+    fn keys_per_encryption(&self) -> u64 {
+        if self.complement_ciphertext.is_some() { 2 } else { 1 }
+    }
+
+    /// Runs the search without reporting progress.
+    pub fn run(&self) -> Outcome {
+        self.run_with_progress(Duration::from_secs(u64::MAX), |_| {})
+    }
+
+    /// Runs the search, calling `report` from the calling thread about every `interval`.
+    pub fn run_with_progress(&self, interval: Duration, mut report: impl FnMut(&Progress)) -> Outcome {
+        let start = Instant::now();
+        let total = self.encryptions();
+        let chunks = (total + (1 << CHUNK_BITS) - 1) >> CHUNK_BITS;
+        let next_chunk = AtomicU64::new(0);
+        let done = AtomicU64::new(0);
+        let stop = AtomicBool::new(false);
+        let found = Mutex::new(None);
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads).map(|_| scope.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+                    if chunk >= chunks {
+                        break;
+                    }
+                    let from = chunk << CHUNK_BITS;
+                    let to = total.min(from + (1 << CHUNK_BITS));
+                    if let Some(key) = self.search(from, to) {
+                        *found.lock().unwrap() = Some(key);
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    done.fetch_add(to - from, Ordering::Relaxed);
+                }
+            })).collect();
+            let mut last = Instant::now();
+            while !workers.iter().all(|worker| worker.is_finished()) {
+                thread::sleep(interval.min(Duration::from_millis(20)));
+                if last.elapsed() >= interval {
+                    last = Instant::now();
+                    report(&Progress {
+                        encryptions: done.load(Ordering::Relaxed),
+                        total,
+                        keys_per_encryption: self.keys_per_encryption(),
+                        elapsed: start.elapsed(),
+                    });
+                }
+            }
+        });
+        Outcome {
+            key: found.into_inner().unwrap(),
+            encryptions: done.into_inner(),
+            keys_per_encryption: self.keys_per_encryption(),
+            threads: self.threads,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    /// Tries the keys numbered `from..to`, 64 at a time.
+    fn search(&self, from: u64, to: u64) -> Option<DesKey> {
+        let broadcast = |block: u64| {
+            let mut slices = [0; 64];
+            for (i, slice) in slices.iter_mut().enumerate() {
+                *slice = 0u64.wrapping_sub(block >> (63 - i) & 1);
+            }
+            slices
+        };
+        let plaintext = broadcast(self.plaintext);
+        let ciphertext = broadcast(self.ciphertext);
+        let complement = self.complement_ciphertext.map(|c| broadcast(!c));
+        let mut key = broadcast(self.space.key(0).to_u64());
+        let free = self.space.free_bits();
+        for (&bit, &pattern) in free.iter().zip(LANE_PATTERNS.iter()) {
+            key[63 - bit as usize] = pattern;
+        }
+        for batch in (from..to).step_by(LANES) {
+            for (j, &bit) in free.iter().enumerate().skip(LANE_PATTERNS.len()) {
+                key[63 - bit as usize] = 0u64.wrapping_sub(batch >> j & 1);
+            }
+            let c = BitslicedDes::from_key_slices(&key).encrypt_slices(&plaintext);
+            let lanes = if to - batch < LANES as u64 { (1 << (to - batch)) - 1 } else { !0 };
+            let matching = |expected: &[u64; 64]| c.iter().zip(expected.iter()).fold(lanes, |acc, (c, e)| acc & !(c ^ e));
+            let found = matching(&ciphertext);
+            if found != 0 {
+                return Some(self.space.key(batch + found.trailing_zeros() as u64));
+            }
+            if let Some(complement) = &complement {
+                let mut found = matching(complement);
+                while found != 0 {
+                    let key = DesKey::from(!self.space.key(batch + found.trailing_zeros() as u64).to_u64());
+                    // The complement pair alone may be a false alarm; the other pair settles it.
+                    if FastDes::new(&key).encrypt_u64(self.plaintext) == self.ciphertext {
+                        return Some(key);
+                    }
+                    found &= found - 1;
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A snapshot of a running search.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    pub encryptions: u64,
+    pub total: u64,
+    keys_per_encryption: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f64 {
+        self.encryptions as f64 / self.total as f64
+    }
+
+    pub fn keys_per_second(&self) -> f64 {
+        (self.encryptions * self.keys_per_encryption) as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Time to the end of the space at the current rate.
+    pub fn remaining(&self) -> Duration {
+        let rate = self.encryptions as f64 / self.elapsed.as_secs_f64();
+        Duration::from_secs_f64((self.total - self.encryptions) as f64 / rate.max(1.0))
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:5.1}% {:>14} keys, {:.2} Mkeys/s, {:.0} s left",
+               self.fraction() * 100.0,
+               self.encryptions * self.keys_per_encryption,
+               self.keys_per_second() / 1e6,
+               self.remaining().as_secs_f64())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub key: Option<DesKey>,
+    /// Encryptions done, including those after the key was found by another thread.
+    pub encryptions: u64,
+    keys_per_encryption: u64,
+    pub threads: usize,
+    pub elapsed: Duration,
+}
+
+impl Outcome {
+    pub fn keys_tested(&self) -> u64 {
+        self.encryptions * self.keys_per_encryption
+    }
+
+    pub fn keys_per_second(&self) -> f64 {
+        self.keys_tested() as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.key {
+            Some(key) => writeln!(f, "found key: {}", key)?,
+            None => writeln!(f, "no key found")?,
+        }
+        writeln!(f, "encryptions: {}, keys tested: {}", self.encryptions, self.keys_tested())?;
+        let plural = if self.threads == 1 { "" } else { "s" };
+        write!(f, "{:.3} s on {} thread{}, {:.2} Mkeys/s", self.elapsed.as_secs_f64(), self.threads, plural, self.keys_per_second() / 1e6)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::keygen;
+
+use super::*;
+
+fn pair(key: &DesKey, plaintext: u64) -> (u64, u64) {
+    let des = FastDes::new(key);
+    (des.encrypt_u64(plaintext), des.encrypt_u64(!plaintext))
+}
+
+#[test]
+fn test_search() {
+    let key = keygen();
+    let (c, _) = pair(&key, 0x0123456789abcdef);
+    let search = BruteForce::new(0x0123456789abcdef, c, KeySpace::from_mask(key, 0x0000000010207efe)).threads(3);
+    assert_eq!(search.encryptions(), 1 << 15);
+    let outcome = search.run();
+    assert_eq!(outcome.key, Some(key));
+    assert!(outcome.encryptions <= 1 << 15);
+}
+
+#[test]
+fn test_not_found() {
+    let key = keygen();
+    let (c, _) = pair(&key, 0);
+    let wrong = DesKey::from(key.to_u64() ^ 0x8000000000000000);
+    let outcome = BruteForce::new(0, c, KeySpace::lowest(wrong, 12)).threads(2).run();
+    assert_eq!(outcome.key, None);
+    assert_eq!(outcome.encryptions, 1 << 12);
+}
+
+#[test]
+fn test_complement() {
+    let key = keygen();
+    let (c, complement) = pair(&key, 0x0123456789abcdef);
+    let space = KeySpace::lowest(key, 14);
+    let search = BruteForce::new(0x0123456789abcdef, c, space.clone()).with_complement(complement);
+    assert_eq!(search.encryptions(), 1 << 14);
+    assert_eq!(search.keys(), 1 << 15);
+    assert_eq!(search.run().key, Some(key));
+
+    // Searching the complemented space finds the key through the complements.
+    let complemented = KeySpace::lowest(DesKey::from(!key.to_u64()), 14);
+    let outcome = BruteForce::new(0x0123456789abcdef, c, complemented).with_complement(complement).threads(2).run();
+    assert_eq!(outcome.key, Some(key));
+}
+
+#[test]
+fn test_closed_under_complement() {
+    let space = KeySpace::lowest(DesKey::from(0), 56);
+    let search = BruteForce::new(0, 0, space.clone()).with_complement(0);
+    assert_eq!(search.encryptions(), 1 << 55);
+    assert_eq!(search.keys(), 1 << 56);
+    assert_eq!(BruteForce::new(0, 0, space).encryptions(), 1 << 56);
+}
+
+#[test]
+fn test_progress() {
+    let key = keygen();
+    let (c, complement) = pair(&key, 0);
+    let mut reports = Vec::new();
+    let search = BruteForce::new(0, c, KeySpace::lowest(DesKey::from(key.to_u64() ^ 1 << 63), 17))
+        .with_complement(complement)
+        .threads(2);
+    let outcome = search.run_with_progress(Duration::from_millis(1), |progress| reports.push(*progress));
+    assert_eq!(outcome.key, None);
+    assert_eq!(outcome.keys_tested(), 1 << 18);
+    assert!(!reports.is_empty());
+    assert!(reports.windows(2).all(|w| w[0].encryptions <= w[1].encryptions));
+    assert!(reports.iter().all(|p| p.total == 1 << 17 && p.fraction() <= 1.0));
+}
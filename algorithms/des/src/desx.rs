@@ -0,0 +1,103 @@
+//! DES-X: DES with 64-bit pre- and post-whitening keys, `C = K2 ⊕ E_K(P ⊕ K1)`.
+//!
+//! The key is 56 + 64 + 64 = 184 bits. Written in hex it is 48 digits, K (with its parity bits)
+//! followed by K1 and K2, the same layout OpenSSL uses for `desx-cbc`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::{Rng, thread_rng};
+
+use crate::fast::FastDes;
+use crate::{keygen as des_keygen, DesKey, ParseKeyError};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DesXKey {
+    /// The DES key.
+    pub k: DesKey,
+    /// Pre-whitening key, XORed into the plaintext.
+    pub k1: u64,
+    /// Post-whitening key, XORed into the DES output.
+    pub k2: u64,
+}
+
+impl DesXKey {
+    pub fn new(k: DesKey, k1: u64, k2: u64) -> DesXKey {
+        DesXKey { k, k1, k2 }
+    }
+
+    /// Parses 48 hexadecimal digits as K ‖ K1 ‖ K2. Only K has parity bits to check.
+    pub fn from_hex(key: &str) -> Result<DesXKey, ParseKeyError> {
+        if !key.is_ascii() || key.len() != 48 {
+            return Err(ParseKeyError::InvalidKeyStringFormat);
+        }
+        let k = DesKey::from_hex(&key[..16])?;
+        let k1 = DesKey::from_hex_ignoring_parity(&key[16..32])?.to_u64();
+        let k2 = DesKey::from_hex_ignoring_parity(&key[32..])?.to_u64();
+        Ok(DesXKey { k, k1, k2 })
+    }
+}
+
+impl FromStr for DesXKey {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<DesXKey, ParseKeyError> {
+        DesXKey::from_hex(s)
+    }
+}
+
+impl fmt::Display for DesXKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:016x}{:016x}", self.k, self.k1, self.k2)
+    }
+}
+
+/// Generates a DES key as [`crate::keygen`] does and two uniformly random whitening keys.
+pub fn keygen() -> DesXKey {
+    let mut rng = thread_rng();
+    DesXKey { k: des_keygen(), k1: rng.gen(), k2: rng.gen() }
+}
+
+#[derive(Debug, Clone)]
+pub struct DesX {
+    des: FastDes,
+    k1: u64,
+    k2: u64,
+}
+
+impl DesX {
+    pub fn new(key: &DesXKey) -> DesX {
+        DesX { des: FastDes::new(&key.k), k1: key.k1, k2: key.k2 }
+    }
+
+    pub fn encrypt_u64(&self, block: u64) -> u64 {
+        self.des.encrypt_u64(block ^ self.k1) ^ self.k2
+    }
+
+    pub fn decrypt_u64(&self, block: u64) -> u64 {
+        self.des.decrypt_u64(block ^ self.k2) ^ self.k1
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        self.encrypt_u64(u64::from_be_bytes(block)).to_be_bytes()
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        self.decrypt_u64(u64::from_be_bytes(block)).to_be_bytes()
+    }
+}
+
+/// Encrypts `input` in ECB mode. The last block is padded with zero bytes.
+pub fn encrypt(input: &[u8], key: &DesXKey) -> Vec<u8> {
+    let desx = DesX::new(key);
+    crate::ecb(input, |block| desx.encrypt_u64(block))
+}
+
+/// Decrypts `input` in ECB mode. A trailing partial block is padded with zero bytes first.
+pub fn decrypt(input: &[u8], key: &DesXKey) -> Vec<u8> {
+    let desx = DesX::new(key);
+    crate::ecb(input, |block| desx.decrypt_u64(block))
+}
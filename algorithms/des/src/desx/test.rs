@@ -0,0 +1,79 @@
+use crate::Des;
+
+use super::*;
+
+#[test]
+fn test_parse_key() {
+    let key: DesXKey = "0123456789abcdeff1e0d3c2b5a49786fedcba9876543210".parse().unwrap();
+    assert_eq!(key, DesXKey::new(DesKey::from(0x0123456789abcdef), 0xf1e0d3c2b5a49786, 0xfedcba9876543210));
+    assert_eq!(key.to_string(), "0123456789abcdeff1e0d3c2b5a49786fedcba9876543210");
+    assert_eq!(DesXKey::from_hex("0123456789abcdef"), Err(ParseKeyError::InvalidKeyStringFormat));
+    assert_eq!(DesXKey::from_hex("0123456789abcdeef1e0d3c2b5a49786fedcba9876543210"), Err(ParseKeyError::CheckSumError));
+    assert_eq!(DesXKey::from_hex("0123456789abcdeff1e0d3c2b5a49786fedcba987654321g"), Err(ParseKeyError::InvalidKeyStringFormat));
+}
+
+#[test]
+fn test_vectors() {
+    // Values checked against OpenSSL's desx-cbc with a zero IV, which is ECB for a single block.
+    let key: DesXKey = "0123456789abcdeff1e0d3c2b5a49786fedcba9876543210".parse().unwrap();
+    let desx = DesX::new(&key);
+    assert_eq!(desx.encrypt_u64(0x4e6f772069732074), 0xc327092461e40219);
+    assert_eq!(desx.decrypt_u64(0xc327092461e40219), 0x4e6f772069732074);
+
+    // K1 moves the plaintext onto the FIPS 46 example block and K2 cancels its ciphertext.
+    let key: DesXKey = "133457799bbcdff10123456789abcdef85e813540f0ab405".parse().unwrap();
+    let desx = DesX::new(&key);
+    assert_eq!(desx.encrypt_u64(0), 0);
+    assert_eq!(desx.encrypt_u64(0x0123456789abcdef), 0x116250ad8589fb7b);
+
+    // Without whitening DES-X is DES.
+    let key = DesKey::from(0x133457799bbcdff1);
+    assert_eq!(DesX::new(&DesXKey::new(key, 0, 0)).encrypt_block([0x87; 8]), Des::new(&key).encrypt_block([0x87; 8]));
+}
+
+#[test]
+fn test_keygen() {
+    for _ in 0..10 {
+        let key = keygen();
+        assert!(key.k.has_odd_parity());
+        assert_eq!(key.to_string().parse(), Ok(key));
+        let input: Vec<u8> = (0..20).collect();
+        let mut padded = input.clone();
+        padded.resize(24, 0);
+        assert_eq!(decrypt(&encrypt(&input, &key), &key), padded);
+    }
+}
+
+/// Effective key length on a reduced keyspace.
+///
+/// K is restricted to 8 free bits and both whitening keys to their low 8 bits, so the whole key
+/// space has 2^24 elements. Exhaustive search against DES-X cannot take K2 from its range like the
+/// other keys: for every guess of (K, K1) one known pair determines K2 = C ⊕ E_K(P ⊕ K1), and a
+/// second pair confirms the guess. The search therefore costs 2^(8+8) trials, twice the bit length
+/// plain DES would cost on the same K, but not the 2^24 of the naive count; this matches the
+/// κ + n − log2(m) bound of Kilian and Rogaway, where the post-whitening key adds nothing against
+/// an attacker holding known pairs.
+#[test]
+fn test_reduced_keyspace_search() {
+    const BITS: u32 = 8;
+    let des_key = |x: u64| DesKey::from(0x133457799bbc0000 | (x & 0x7f) << 1 | (x >> 7) << 9);
+    let key = DesXKey::new(des_key(0xa5), 0x3c, 0xd2);
+    let desx = DesX::new(&key);
+    let pairs: Vec<_> = [0x0123456789abcdef_u64, 0xfedcba9876543210].iter().map(|&p| (p, desx.encrypt_u64(p))).collect();
+
+    let mut trials = 0u64;
+    let mut found = Vec::new();
+    for x in 0..1 << BITS {
+        let des = FastDes::new(&des_key(x));
+        for k1 in 0..1 << BITS {
+            trials += 1;
+            let k2 = des.encrypt_u64(pairs[0].0 ^ k1) ^ pairs[0].1;
+            if pairs[1..].iter().all(|&(p, c)| des.encrypt_u64(p ^ k1) ^ k2 == c) {
+                found.push(DesXKey::new(des_key(x), k1, k2));
+            }
+        }
+    }
+    assert_eq!(found, vec![key]);
+    assert_eq!(trials, 1 << (2 * BITS));
+    assert!(trials < 1 << (3 * BITS));
+}
@@ -0,0 +1,411 @@
+//! Differential cryptanalysis of DES reduced to a few rounds, after Biham and Shamir.
+//!
+//! A difference is the XOR of the two values of a pair. All differences here are taken between IP
+//! and IP⁻¹: chosen plaintexts are built by applying IP⁻¹ to the wanted (L0, R0) and ciphertexts
+//! are read back through IP, so the fixed permutations do not get in the way. The attacks ask an
+//! [`Oracle`] for encryptions under an unknown key, recover the six bits of the last round key
+//! entering each S-box they can reach, and finish with an exhaustive search over the key bits still
+//! unknown.
+
+use std::fmt::{self, Write};
+
+use rand::{thread_rng, Rng};
+
+use crate::fast::{expand_chunk, final_permutation, initial_permutation, FastDes};
+use crate::provenance::sub_key_sources;
+use crate::{s, DesKey, P};
+
+#[cfg(test)]
+mod test;
+
+/// Output of S-box `index` (0-based) for the 6-bit input `x`, computed by the same `s` that
+/// [`crate::Des`] uses.
+pub fn sbox(index: usize, x: u8) -> u8 {
+    let mut input = [false; 6];
+    for (i, bit) in input.iter_mut().enumerate() {
+        *bit = x >> (5 - i) & 1 != 0;
+    }
+    let mut output = [false; 4];
+    s(index, &input, &mut output);
+    output.iter().fold(0, |acc, &b| acc << 1 | b as u8)
+}
+
+/// The eight S-boxes as lookup tables, `sbox_table()[i][x] == sbox(i, x)`.
+pub fn sbox_table() -> [[u8; 64]; 8] {
+    let mut result = [[0; 64]; 8];
+    for (i, table) in result.iter_mut().enumerate() {
+        for (x, y) in table.iter_mut().enumerate() {
+            *y = sbox(i, x as u8);
+        }
+    }
+    result
+}
+
+/// P on a 32-bit word.
+pub fn permute_p(x: u32) -> u32 {
+    P.iter().fold(0, |acc, &t| acc << 1 | (x >> (32 - t) & 1))
+}
+
+/// P⁻¹ on a 32-bit word: turns a difference of f into the differences of the S-box outputs.
+pub fn inverse_p(x: u32) -> u32 {
+    let mut result = 0;
+    for (i, &t) in P.iter().enumerate() {
+        result |= (x >> (31 - i) & 1) << (32 - t);
+    }
+    result
+}
+
+/// The 4-bit output of S-box `index` in a word before P.
+pub fn nibble(x: u32, index: usize) -> u8 {
+    (x >> (28 - 4 * index) & 0xf) as u8
+}
+
+/// `difference_distribution_table(i)[dx][dy]` is the number of inputs `x` of S-box `i` with
+/// `S(x) ⊕ S(x ⊕ dx) = dy`.
+pub fn difference_distribution_table(index: usize) -> [[u8; 16]; 64] {
+    let mut result = [[0; 16]; 64];
+    for (dx, row) in result.iter_mut().enumerate() {
+        for x in 0..64u8 {
+            row[(sbox(index, x) ^ sbox(index, x ^ dx as u8)) as usize] += 1;
+        }
+    }
+    result
+}
+
+/// A difference distribution table as 64 rows of 16 counts.
+pub fn ddt_matrix(table: &[[u8; 16]; 64]) -> String {
+    let mut result = String::from("  dx\\dy");
+    for dy in 0..16 {
+        write!(result, " {:>2x}", dy).unwrap();
+    }
+    result.push('\n');
+    for (dx, row) in table.iter().enumerate() {
+        write!(result, "{:>7x}", dx).unwrap();
+        for count in row.iter() {
+            write!(result, " {:>2}", count).unwrap();
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// The difference distribution tables of all eight S-boxes.
+#[derive(Debug, Clone)]
+pub struct DifferenceTables {
+    tables: [[[u8; 16]; 64]; 8],
+}
+
+impl Default for DifferenceTables {
+    fn default() -> DifferenceTables {
+        DifferenceTables::new()
+    }
+}
+
+impl DifferenceTables {
+    pub fn new() -> DifferenceTables {
+        let mut tables = [[[0; 16]; 64]; 8];
+        for (i, table) in tables.iter_mut().enumerate() {
+            *table = difference_distribution_table(i);
+        }
+        DifferenceTables { tables }
+    }
+
+    pub fn table(&self, index: usize) -> &[[u8; 16]; 64] {
+        &self.tables[index]
+    }
+
+    /// Probability over the round key that f turns input difference `dx` into output difference
+    /// `dy`.
+    pub fn round_probability(&self, dx: u32, dy: u32) -> f64 {
+        let dy = inverse_p(dy);
+        (0..8).map(|i| self.tables[i][expand_chunk(dx, i) as usize][nibble(dy, i) as usize] as f64 / 64.0).product()
+    }
+
+    /// The most likely output difference of f for input difference `dx`, with its probability.
+    pub fn best_output(&self, dx: u32) -> (u32, f64) {
+        let mut dy = 0;
+        let mut probability = 1.0;
+        for (i, table) in self.tables.iter().enumerate() {
+            let row = &table[expand_chunk(dx, i) as usize];
+            let (out, &count) = row.iter().enumerate().rev().max_by_key(|(_, &count)| count).unwrap();
+            dy |= (out as u32) << (28 - 4 * i);
+            probability *= count as f64 / 64.0;
+        }
+        (permute_p(dy), probability)
+    }
+
+    /// Follows (ΔL0, ΔR0) through `rounds` rounds, taking the most likely output difference of f in
+    /// every round.
+    pub fn greedy_characteristic(&self, input: (u32, u32), rounds: usize) -> Characteristic {
+        let (mut l, mut r) = input;
+        let mut steps = Vec::with_capacity(rounds);
+        let mut probability = 1.0;
+        for _ in 0..rounds {
+            let (dy, p) = self.best_output(r);
+            steps.push((r, dy));
+            probability *= p;
+            let next_r = l ^ dy;
+            l = r;
+            r = next_r;
+        }
+        Characteristic { input, rounds: steps, probability }
+    }
+
+    /// The input differences of f which become zero with the highest probability, best first.
+    ///
+    /// Such a difference `dx` gives the two-round iterative characteristic (dx, 0) → (0, dx) →
+    /// (dx, 0). Only differences touching three neighbouring S-boxes are searched; the best ones,
+    /// 0x19600000 and 0x1b600000 at about 1/234, are of that form.
+    pub fn iterative_characteristics(&self, limit: usize) -> Vec<(u32, f64)> {
+        let mut candidates = Vec::new();
+        for window in 0..8 {
+            for bits in 1..1u32 << 14 {
+                let dx = (bits << 18).rotate_left(1).rotate_right(4 * window);
+                let probability = self.round_probability(dx, 0);
+                if probability > 0.0 {
+                    candidates.push((dx, probability));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+        candidates.dedup_by_key(|c| c.0);
+        candidates.truncate(limit);
+        candidates
+    }
+}
+
+/// A characteristic over several rounds: the difference entering the first round and the input and
+/// output differences of f in each round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Characteristic {
+    /// (ΔL0, ΔR0).
+    pub input: (u32, u32),
+    /// (ΔR, Δf) of each round.
+    pub rounds: Vec<(u32, u32)>,
+    pub probability: f64,
+}
+
+impl Characteristic {
+    /// (ΔL, ΔR) after the last round.
+    pub fn output(&self) -> (u32, u32) {
+        self.rounds.iter().fold(self.input, |(l, r), &(_, dy)| (r, l ^ dy))
+    }
+}
+
+impl fmt::Display for Characteristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mut l, mut r) = self.input;
+        writeln!(f, "        dL       dR        f in     f out")?;
+        writeln!(f, "  0  {:08x} {:08x}", l, r)?;
+        for (i, &(dx, dy)) in self.rounds.iter().enumerate() {
+            let next_r = l ^ dy;
+            l = r;
+            r = next_r;
+            writeln!(f, "{:>3}  {:08x} {:08x}    {:08x} {:08x}", i + 1, l, r, dx, dy)?;
+        }
+        write!(f, "probability 1/{:.1}", 1.0 / self.probability)
+    }
+}
+
+/// Encrypts chosen plaintexts under a key the attacker does not see, counting the queries.
+#[derive(Debug, Clone)]
+pub struct Oracle {
+    des: FastDes,
+    queries: usize,
+}
+
+impl Oracle {
+    /// An oracle for `rounds`-round DES under `key`.
+    pub fn new(key: &DesKey, rounds: usize) -> Oracle {
+        Oracle { des: FastDes::with_rounds(key, rounds), queries: 0 }
+    }
+
+    pub fn encrypt(&mut self, plaintext: u64) -> u64 {
+        self.queries += 1;
+        self.des.encrypt_u64(plaintext)
+    }
+
+    /// Number of plaintexts encrypted so far.
+    pub fn queries(&self) -> usize {
+        self.queries
+    }
+}
+
+/// Result of a key recovery attack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    /// Rounds of the attacked cipher. The recovered round key is the one of the last round.
+    pub rounds: usize,
+    /// The six bits of the last round key entering each S-box, where the attack determined them.
+    pub sub_key: [Option<u8>; 8],
+    /// Chosen plaintext pairs used, including the ones discarded as wrong pairs.
+    pub pairs: usize,
+    /// The key, if the exhaustive search over the remaining key bits found one.
+    pub key: Option<DesKey>,
+}
+
+impl Recovery {
+    /// Number of round key bits recovered.
+    pub fn recovered_bits(&self) -> usize {
+        self.sub_key.iter().flatten().count() * 6
+    }
+}
+
+impl fmt::Display for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}-round DES, {} pairs", self.rounds, self.pairs)?;
+        write!(f, "K{}:", self.rounds)?;
+        for chunk in self.sub_key.iter() {
+            match chunk {
+                Some(chunk) => write!(f, " {:06b}", chunk)?,
+                None => write!(f, " ??????")?,
+            }
+        }
+        writeln!(f, " ({} bits)", self.recovered_bits())?;
+        match self.key {
+            Some(key) => write!(f, "key: {}", key),
+            None => write!(f, "key: not found"),
+        }
+    }
+}
+
+/// (L, R) of the last round for a plaintext given as (L0, R0) after IP.
+fn query(oracle: &mut Oracle, l: u32, r: u32) -> (u32, u32, u64, u64) {
+    let plaintext = final_permutation(l, r);
+    let ciphertext = oracle.encrypt(plaintext);
+    let (r, l) = initial_permutation(ciphertext);
+    (l, r, plaintext, ciphertext)
+}
+
+/// Adds one to the count of every key chunk of S-box `index` under which inputs `x` and `x_star`
+/// give the output difference `dy`.
+fn count(counts: &mut [u32; 64], table: &[u8; 64], x: u8, x_star: u8, dy: u8) {
+    for (k, count) in counts.iter_mut().enumerate() {
+        if table[(x ^ k as u8) as usize] ^ table[(x_star ^ k as u8) as usize] == dy {
+            *count += 1;
+        }
+    }
+}
+
+/// Recovers 42 bits of K4 of 4-round DES, then the key.
+///
+/// The pairs have (ΔL0, ΔR0) = (0x20000000, 0). The difference goes through the first round
+/// unchanged and only S1 is active in the second, so with probability 1 the difference entering
+/// the fourth round's f differs from ΔR4 only in the output bits of S1. Candidates for the other
+/// seven S-boxes are intersected pair by pair until one is left for each, at most `max_pairs`.
+pub fn attack_four_rounds(oracle: &mut Oracle, max_pairs: usize) -> Recovery {
+    let mut rng = thread_rng();
+    let table = sbox_table();
+    let mut counts = [[0u32; 64]; 8];
+    let mut pairs = 0;
+    let mut samples = Vec::new();
+    while pairs < max_pairs {
+        let (l0, r0) = (rng.gen(), rng.gen());
+        let (l, r, p, c) = query(oracle, l0, r0);
+        let (l_star, r_star, _, _) = query(oracle, l0 ^ 0x20000000, r0);
+        samples.push((p, c));
+        pairs += 1;
+        let dy = inverse_p(r ^ r_star);
+        for (i, counts) in counts.iter_mut().enumerate().skip(1) {
+            count(counts, &table[i], expand_chunk(l, i), expand_chunk(l_star, i), nibble(dy, i));
+        }
+        if counts[1..].iter().all(|c| c.iter().filter(|&&n| n == pairs as u32).count() == 1) {
+            break;
+        }
+    }
+    let mut sub_key = [None; 8];
+    for (i, chunk) in sub_key.iter_mut().enumerate().skip(1) {
+        let mut candidates = (0..64u8).filter(|&k| counts[i][k as usize] == pairs as u32);
+        if let (Some(k), None) = (candidates.next(), candidates.next()) {
+            *chunk = Some(k);
+        }
+    }
+    let key = if sub_key[1..].iter().all(Option::is_some) {
+        complete_key(4, &sub_key, &samples[..2.min(samples.len())])
+    } else {
+        None
+    };
+    Recovery { rounds: 4, sub_key, pairs, key }
+}
+
+/// The two three-round characteristics of Biham and Shamir's 6-round attack, as (ΔL0, ΔR0). Both
+/// hold with probability 1/16; each leaves five S-boxes without input difference in round 4.
+pub const SIX_ROUND_CHARACTERISTICS: [(u32, u32); 2] = [(0x40080000, 0x04000000), (0x00200008, 0x00000400)];
+
+/// Recovers 42 bits of K6 of 6-round DES, then the key.
+///
+/// For each of [`SIX_ROUND_CHARACTERISTICS`], `pairs` pairs are encrypted. In a right pair the
+/// difference (ΔL3, ΔR3) is known, so the output difference of f in round 6 is known for every
+/// S-box without input difference in round 4. Pairs impossible for some S-box are discarded, the
+/// rest vote for the key chunks consistent with them, and the most voted chunk wins.
+pub fn attack_six_rounds(oracle: &mut Oracle, pairs: usize) -> Recovery {
+    let mut rng = thread_rng();
+    let table = sbox_table();
+    let tables = DifferenceTables::new();
+    let mut counts = [[0u32; 64]; 8];
+    let mut reached = [false; 8];
+    let mut samples = Vec::new();
+    for &input in SIX_ROUND_CHARACTERISTICS.iter() {
+        let (l3, r3) = tables.greedy_characteristic(input, 3).output();
+        let sboxes: Vec<_> = (0..8).filter(|&i| expand_chunk(r3, i) == 0).collect();
+        for &i in &sboxes {
+            reached[i] = true;
+        }
+        for _ in 0..pairs {
+            let (l0, r0) = (rng.gen(), rng.gen());
+            let (l, r, p, c) = query(oracle, l0, r0);
+            let (l_star, r_star, _, _) = query(oracle, l0 ^ input.0, r0 ^ input.1);
+            samples.push((p, c));
+            let dx = l ^ l_star;
+            let dy = inverse_p(r ^ r_star ^ l3);
+            if sboxes.iter().any(|&i| tables.table(i)[expand_chunk(dx, i) as usize][nibble(dy, i) as usize] == 0) {
+                continue;
+            }
+            for &i in &sboxes {
+                count(&mut counts[i], &table[i], expand_chunk(l, i), expand_chunk(l_star, i), nibble(dy, i));
+            }
+        }
+    }
+    let mut sub_key = [None; 8];
+    for (i, chunk) in sub_key.iter_mut().enumerate() {
+        if reached[i] {
+            *chunk = (0..64u8).max_by_key(|&k| counts[i][k as usize]);
+        }
+    }
+    let key = complete_key(6, &sub_key, &samples[..2.min(samples.len())]);
+    Recovery { rounds: 6, sub_key, pairs: pairs * SIX_ROUND_CHARACTERISTICS.len(), key }
+}
+
+/// Most key bits [`complete_key`] leaves to exhaustive search: 2^24 trials take a few seconds.
+pub const MAX_FREE_BITS: usize = 24;
+
+/// Finds the key of `round`-round DES from the known chunks of its last round key by trying every
+/// value of the key bits they do not fix, checking each against the known (plaintext, ciphertext)
+/// `samples`. Takes 2^(56 - known bits) trials. The returned key has odd parity. Gives up with
+/// `None` when more than [`MAX_FREE_BITS`] bits are unknown.
+pub fn complete_key(rounds: usize, sub_key: &[Option<u8>; 8], samples: &[(u64, u64)]) -> Option<DesKey> {
+    let sources = sub_key_sources()[rounds - 1];
+    let mut known = 0u64;
+    let mut mask = 0u64;
+    for (position, &source) in sources.iter().enumerate() {
+        if let Some(chunk) = sub_key[position / 6] {
+            let bit = 1 << (64 - source as u32);
+            mask |= bit;
+            if chunk >> (5 - position % 6) & 1 != 0 {
+                known |= bit;
+            }
+        }
+    }
+    let parity = 0x0101010101010101u64;
+    let free: Vec<u32> = (0..64).filter(|&i| (mask | parity) >> i & 1 == 0).collect();
+    if free.len() > MAX_FREE_BITS {
+        return None;
+    }
+    (0..1u64 << free.len())
+        .map(|x| free.iter().enumerate().fold(known, |key, (j, &i)| key | (x >> j & 1) << i))
+        .map(|key| DesKey::from(key).with_odd_parity())
+        .find(|key| {
+            let des = FastDes::with_rounds(key, rounds);
+            samples.iter().all(|&(p, c)| des.encrypt_u64(p) == c)
+        })
+}
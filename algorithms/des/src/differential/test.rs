@@ -0,0 +1,131 @@
+use rand::{thread_rng, Rng};
+
+use crate::fast::sub_keys;
+use crate::{keygen, Des, DesKey, S_BOX};
+
+use super::*;
+
+#[test]
+fn test_sbox() {
+    for (i, table) in sbox_table().iter().enumerate() {
+        for (x, &y) in table.iter().enumerate() {
+            let row = (x >> 4 & 0b10) | (x & 1);
+            let column = x >> 1 & 0b1111;
+            assert_eq!(y, S_BOX[i][row][column]);
+        }
+    }
+}
+
+#[test]
+fn test_permute_p() {
+    let mut rng = thread_rng();
+    for _ in 0..100 {
+        let x: u32 = rng.gen();
+        assert_eq!(inverse_p(permute_p(x)), x);
+        assert_eq!(permute_p(inverse_p(x)), x);
+    }
+    // P moves the first S-box output bit to position 9.
+    assert_eq!(permute_p(0x80000000), 0x00800000);
+}
+
+#[test]
+fn test_difference_distribution_table() {
+    for i in 0..8 {
+        let table = difference_distribution_table(i);
+        assert_eq!(table[0][0], 64);
+        for row in table.iter() {
+            assert_eq!(row.iter().map(|&n| n as u32).sum::<u32>(), 64);
+            assert!(row.iter().all(|n| n % 2 == 0));
+        }
+        // A difference in the middle two input bits alone never cancels.
+        assert_eq!(table[0b000100][0], 0);
+        assert_eq!(table[0b001000][0], 0);
+        assert_eq!(table[0b001100][0], 0);
+    }
+    // Example from Biham and Shamir: S1 with input difference 0x34.
+    let table = difference_distribution_table(0);
+    assert_eq!(table[0x34], [0, 8, 16, 6, 2, 0, 0, 12, 6, 0, 0, 0, 0, 8, 0, 6]);
+}
+
+#[test]
+fn test_characteristics() {
+    let tables = DifferenceTables::new();
+    let best = tables.iterative_characteristics(2);
+    assert_eq!(best.iter().map(|c| c.0).collect::<Vec<_>>(), vec![0x19600000, 0x1b600000]);
+    assert!((1.0 / best[0].1 - 234.0).abs() < 1.0);
+
+    for &input in SIX_ROUND_CHARACTERISTICS.iter() {
+        let characteristic = tables.greedy_characteristic(input, 3);
+        assert_eq!(characteristic.probability, 1.0 / 16.0);
+        assert_eq!(characteristic.output(), (input.1, input.0));
+    }
+}
+
+#[test]
+fn test_reduced_rounds() {
+    let key = DesKey::from(0x133457799bbcdff1);
+    for rounds in 1..=16 {
+        let des = Des::with_rounds(&key, rounds);
+        let fast = FastDes::with_rounds(&key, rounds);
+        let block = 0x0123456789abcdefu64.to_be_bytes();
+        let encrypted = des.encrypt_block(block);
+        assert_eq!(fast.encrypt_block(block), encrypted);
+        assert_eq!(des.decrypt_block(encrypted), block);
+        assert_eq!(fast.decrypt_block(encrypted), block);
+    }
+    assert_eq!(Des::with_rounds(&key, 16).encrypt_block(0x0123456789abcdefu64.to_be_bytes()),
+               0x85e813540f0ab405u64.to_be_bytes());
+}
+
+#[test]
+fn test_attack_four_rounds() {
+    let key = keygen();
+    let mut oracle = Oracle::new(&key, 4);
+    let recovery = attack_four_rounds(&mut oracle, 64);
+    let expected = sub_keys(&key)[3];
+    assert_eq!(recovery.sub_key[0], None);
+    for (recovered, &expected) in recovery.sub_key.iter().zip(expected.iter()).skip(1) {
+        assert_eq!(*recovered, Some(expected));
+    }
+    assert_eq!(recovery.recovered_bits(), 42);
+    assert_eq!(oracle.queries(), recovery.pairs * 2);
+    assert_eq!(recovery.key, Some(key));
+}
+
+#[test]
+fn test_attack_four_rounds_one_pair() {
+    // One pair leaves at least two candidates for every S-box, so no chunk is known.
+    let key = keygen();
+    let mut oracle = Oracle::new(&key, 4);
+    let recovery = attack_four_rounds(&mut oracle, 1);
+    assert_eq!(recovery.pairs, 1);
+    assert_eq!(recovery.recovered_bits(), 0);
+    assert_eq!(recovery.key, None);
+}
+
+#[test]
+fn test_complete_key() {
+    let key = keygen();
+    let des = FastDes::with_rounds(&key, 4);
+    let samples: Vec<_> = [0u64, 0x0123456789abcdef].iter().map(|&p| (p, des.encrypt_u64(p))).collect();
+    let mut sub_key = [None; 8];
+    assert_eq!(complete_key(4, &sub_key, &samples), None);
+    for (chunk, &expected) in sub_key.iter_mut().zip(sub_keys(&key)[3].iter()).skip(1) {
+        *chunk = Some(expected);
+    }
+    assert_eq!(complete_key(4, &sub_key, &samples), Some(key));
+}
+
+#[test]
+fn test_attack_six_rounds() {
+    let key = keygen();
+    let mut oracle = Oracle::new(&key, 6);
+    let recovery = attack_six_rounds(&mut oracle, 300);
+    let expected = sub_keys(&key)[5];
+    assert_eq!(recovery.sub_key[2], None);
+    for i in (0..8).filter(|&i| i != 2) {
+        assert_eq!(recovery.sub_key[i], Some(expected[i]));
+    }
+    assert_eq!(recovery.pairs, 600);
+    assert_eq!(recovery.key, Some(key));
+}
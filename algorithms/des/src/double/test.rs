@@ -0,0 +1,44 @@
+use crate::{Des, ParseKeyError};
+
+use super::*;
+
+#[test]
+fn test_parse_key() {
+    let key: DoubleDesKey = "0123456789abcdef23456789abcdef01".parse().unwrap();
+    assert_eq!(key, DoubleDesKey::new(DesKey::from(0x0123456789abcdef), DesKey::from(0x23456789abcdef01)));
+    assert_eq!(key.to_string(), "0123456789abcdef23456789abcdef01");
+    assert_eq!(DoubleDesKey::from_hex("0123456789abcdef"), Err(ParseKeyError::InvalidKeyStringFormat));
+    assert_eq!(DoubleDesKey::from_hex("0123456789abcdef23456789abcdef00"), Err(ParseKeyError::CheckSumError));
+}
+
+#[test]
+fn test_double_des() {
+    let key = keygen();
+    let cipher = DoubleDes::new(&key);
+    let block = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+    let expected = Des::new(&key.k2).encrypt_block(Des::new(&key.k1).encrypt_block(block));
+    assert_eq!(cipher.encrypt_block(block), expected);
+    assert_eq!(cipher.decrypt_block(expected), block);
+    assert_eq!(des(&key.k1, u64::from_be_bytes(block), false), u64::from_be_bytes(Des::new(&key.k1).encrypt_block(block)));
+    let input = b"meet in the middle";
+    assert_eq!(&decrypt(&encrypt(input, &key), &key)[..input.len()], input);
+}
+
+#[test]
+fn test_meet_in_the_middle() {
+    let key = keygen();
+    let cipher = DoubleDes::new(&key);
+    let pairs: Vec<_> = [0x0123456789abcdefu64, 0xfedcba9876543210].iter().map(|&p| (p, cipher.encrypt_u64(p))).collect();
+    let first = KeySpace::lowest(key.k1, 9);
+    let second = KeySpace::lowest(key.k2, 8);
+    let attack = meet_in_the_middle(&pairs, &first, &second);
+    assert_eq!(attack.keys, vec![key]);
+    assert_eq!(attack.table_entries, 512);
+    assert!(attack.table_bytes >= 512 * 16);
+    assert!(attack.operations >= 512 + 256 + 2);
+
+    // One pair is enough for keyspaces this small.
+    let attack = meet_in_the_middle(&pairs[..1], &first, &second);
+    assert_eq!(attack.keys, vec![key]);
+    assert_eq!(attack.operations, 512 + 256);
+}
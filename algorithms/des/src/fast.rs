@@ -0,0 +1,151 @@
+//! Word-oriented DES engine.
+//!
+//! The bool-array code in the crate root follows FIPS 46-3 line by line and is meant to be read.
+//! This module computes exactly the same function on `u32`/`u64` words: the initial and final
+//! permutations are done with a handful of masked swaps, and S-box lookup, P permutation and E
+//! expansion are folded into eight 64-entry SP tables built at compile time.
+
+use crate::{DesKey, P, PC1, PC2, S_BOX};
+
+#[cfg(test)]
+mod test;
+
+const SHIFTS: [u32; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+/// `SP[i][x]` is the output of S-box `i` for the 6-bit input `x`, already moved through P.
+const SP: [[u32; 64]; 8] = sp_table();
+
+const fn sp_table() -> [[u32; 64]; 8] {
+    let mut result = [[0; 64]; 8];
+    let mut i = 0;
+    while i < 8 {
+        let mut x = 0;
+        while x < 64 {
+            let row = (x >> 4 & 0b10) | (x & 1);
+            let column = x >> 1 & 0b1111;
+            let before_p = (S_BOX[i][row][column] as u32) << (28 - 4 * i);
+            let mut after_p = 0;
+            let mut j = 0;
+            while j < 32 {
+                if before_p >> (32 - P[j]) & 1 != 0 {
+                    after_p |= 1 << (31 - j);
+                }
+                j += 1;
+            }
+            result[i][x] = after_p;
+            x += 1;
+        }
+        i += 1;
+    }
+    result
+}
+
+/// Applies a 1-based, most significant bit first permutation table to the low `width` bits of
+/// `input`. Only used for the key schedule, which runs once per key.
+fn permute(input: u64, width: usize, table: &[usize]) -> u64 {
+    table.iter().fold(0, |acc, &t| acc << 1 | (input >> (width - t) & 1))
+}
+
+/// Exchanges the bits of `a` selected by `mask << shift` with the bits of `b` selected by `mask`.
+#[inline(always)]
+fn delta_swap(a: &mut u32, b: &mut u32, shift: u32, mask: u32) {
+    let t = (*a >> shift ^ *b) & mask;
+    *b ^= t;
+    *a ^= t << shift;
+}
+
+#[inline(always)]
+pub(crate) fn initial_permutation(block: u64) -> (u32, u32) {
+    let mut l = (block >> 32) as u32;
+    let mut r = block as u32;
+    delta_swap(&mut l, &mut r, 4, 0x0f0f0f0f);
+    delta_swap(&mut l, &mut r, 16, 0x0000ffff);
+    delta_swap(&mut r, &mut l, 2, 0x33333333);
+    delta_swap(&mut r, &mut l, 8, 0x00ff00ff);
+    delta_swap(&mut l, &mut r, 1, 0x55555555);
+    (l, r)
+}
+
+#[inline(always)]
+pub(crate) fn final_permutation(mut l: u32, mut r: u32) -> u64 {
+    delta_swap(&mut l, &mut r, 1, 0x55555555);
+    delta_swap(&mut r, &mut l, 8, 0x00ff00ff);
+    delta_swap(&mut r, &mut l, 2, 0x33333333);
+    delta_swap(&mut l, &mut r, 16, 0x0000ffff);
+    delta_swap(&mut l, &mut r, 4, 0x0f0f0f0f);
+    (l as u64) << 32 | r as u64
+}
+
+/// The six bits of E(`r`) entering S-box `i` (0-based).
+#[inline(always)]
+pub(crate) fn expand_chunk(r: u32, i: usize) -> u8 {
+    r.rotate_left(4 * i as u32 + 5) as u8 & 0x3f
+}
+
+#[inline(always)]
+fn f(r: u32, k: &[u8; 8]) -> u32 {
+    let mut result = 0;
+    for (i, (sp, &k)) in SP.iter().zip(k.iter()).enumerate() {
+        result |= sp[(expand_chunk(r, i) ^ k) as usize];
+    }
+    result
+}
+
+/// The sixteen 48-bit round keys, each split into the eight 6-bit S-box inputs.
+pub(crate) fn sub_keys(key: &DesKey) -> [[u8; 8]; 16] {
+    let cd = permute(key.to_u64(), 64, &PC1);
+    let mut c = (cd >> 28) as u32;
+    let mut d = cd as u32 & 0x0fffffff;
+    let mut result = [[0; 8]; 16];
+    for (round, &shift) in result.iter_mut().zip(SHIFTS.iter()) {
+        c = (c << shift | c >> (28 - shift)) & 0x0fffffff;
+        d = (d << shift | d >> (28 - shift)) & 0x0fffffff;
+        let k = permute((c as u64) << 28 | d as u64, 56, &PC2);
+        for (i, chunk) in round.iter_mut().enumerate() {
+            *chunk = (k >> (42 - 6 * i) & 0x3f) as u8;
+        }
+    }
+    result
+}
+
+/// DES on 64-bit words. Produces the same output as [`crate::Des`] for every key and block.
+#[derive(Debug, Clone)]
+pub struct FastDes {
+    sub_key: [[u8; 8]; 16],
+    rounds: usize,
+}
+
+impl FastDes {
+    pub fn new(key: &DesKey) -> FastDes {
+        FastDes::with_rounds(key, 16)
+    }
+
+    /// DES reduced to its first `rounds` rounds, see [`crate::Des::with_rounds`].
+    pub fn with_rounds(key: &DesKey, rounds: usize) -> FastDes {
+        assert!((1..=16).contains(&rounds), "DES has 1 to 16 rounds");
+        FastDes { sub_key: sub_keys(key), rounds }
+    }
+
+    #[inline]
+    fn crypt<'a, I: Iterator<Item=&'a [u8; 8]>>(block: u64, keys: I) -> u64 {
+        let (l, r) = initial_permutation(block);
+        let (r, l) = feistel::network(l, r, keys, &|r: &u32, k: &[u8; 8]| f(*r, k));
+        final_permutation(r, l)
+    }
+
+    pub fn encrypt_u64(&self, block: u64) -> u64 {
+        FastDes::crypt(block, self.sub_key[..self.rounds].iter())
+    }
+
+    pub fn decrypt_u64(&self, block: u64) -> u64 {
+        FastDes::crypt(block, self.sub_key[..self.rounds].iter().rev())
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        self.encrypt_u64(u64::from_be_bytes(block)).to_be_bytes()
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        self.decrypt_u64(u64::from_be_bytes(block)).to_be_bytes()
+    }
+}
@@ -0,0 +1,48 @@
+use rand::{Rng, thread_rng};
+
+use crate::{Des, IP, IP_INVERSE};
+
+use super::*;
+
+#[test]
+fn test_permutations() {
+    let mut rng = thread_rng();
+    for _ in 0..1000 {
+        let block: u64 = rng.gen();
+        let (l, r) = initial_permutation(block);
+        assert_eq!((l as u64) << 32 | r as u64, permute(block, 64, &IP));
+        assert_eq!(final_permutation(l, r), block);
+        assert_eq!(final_permutation(l, r), permute((l as u64) << 32 | r as u64, 64, &IP_INVERSE));
+    }
+}
+
+#[test]
+fn test_sp_table() {
+    assert_eq!(SP[0][0], permute(14 << 28, 32, &P) as u32);
+    assert_eq!(SP[7][0b111111], permute(11, 32, &P) as u32);
+    for sp in SP.iter() {
+        assert!(sp.iter().all(|v| v.count_ones() <= 4));
+    }
+}
+
+#[test]
+fn test_known_answer() {
+    let des = FastDes::new(&DesKey::from(0x133457799bbcdff1));
+    assert_eq!(des.encrypt_u64(0x0123456789abcdef), 0x85e813540f0ab405);
+    assert_eq!(des.decrypt_u64(0x85e813540f0ab405), 0x0123456789abcdef);
+}
+
+#[test]
+fn test_cross_check() {
+    let mut rng = thread_rng();
+    for _ in 0..200 {
+        let key = DesKey::new(rng.gen());
+        let block: [u8; 8] = rng.gen();
+        let slow = Des::new(&key);
+        let fast = FastDes::new(&key);
+        let encrypted = slow.encrypt_block(block);
+        assert_eq!(fast.encrypt_block(block), encrypted);
+        assert_eq!(fast.decrypt_block(encrypted), block);
+        assert_eq!(fast.decrypt_block(block), slow.decrypt_block(block));
+    }
+}
@@ -0,0 +1,80 @@
+//! Sets of DES keys that differ only in some bits, for exhaustive search on a reduced keyspace.
+
+use crate::DesKey;
+
+#[cfg(test)]
+mod test;
+
+/// The parity bits, the least significant bit of every byte.
+pub const PARITY_MASK: u64 = 0x0101010101010101;
+
+/// The keys agreeing with a base key everywhere but in the free bits. Parity bits are never free:
+/// every key of the space has odd parity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySpace {
+    base: u64,
+    /// Positions of the free bits, least significant first, counted from the least significant bit
+    /// of the key.
+    free: Vec<u32>,
+}
+
+impl KeySpace {
+    /// The keys that agree with `base` outside of `mask`. Parity bits in `mask` are ignored.
+    pub fn from_mask(base: DesKey, mask: u64) -> KeySpace {
+        let mask = mask & !PARITY_MASK;
+        KeySpace {
+            base: base.to_u64() & !mask,
+            free: (0..64).filter(|i| mask >> i & 1 != 0).collect(),
+        }
+    }
+
+    /// The keys that agree with `base` except in the last `count` non-parity bits.
+    pub fn lowest(base: DesKey, count: u32) -> KeySpace {
+        assert!(count <= 56, "a DES key has 56 bits");
+        let mask = (0..64).filter(|i| PARITY_MASK >> i & 1 == 0).take(count as usize).fold(0, |acc, i| acc | 1 << i);
+        KeySpace::from_mask(base, mask)
+    }
+
+    /// The free bits.
+    pub fn mask(&self) -> u64 {
+        self.free.iter().fold(0, |acc, i| acc | 1 << i)
+    }
+
+    /// Positions of the free bits, least significant first, bit 0 being the last bit of the key.
+    pub fn free_bits(&self) -> &[u32] {
+        &self.free
+    }
+
+    /// Number of free bits.
+    pub fn bits(&self) -> u32 {
+        self.free.len() as u32
+    }
+
+    /// Number of keys, `2^bits()`.
+    pub fn size(&self) -> u64 {
+        1 << self.free.len()
+    }
+
+    /// The key with number `index`, which holds the values of the free bits in order.
+    pub fn key(&self, index: u64) -> DesKey {
+        let value = self.free.iter().enumerate().fold(self.base, |acc, (j, &i)| acc | (index >> j & 1) << i);
+        DesKey::from(value).with_odd_parity()
+    }
+
+    /// The inverse of [`KeySpace::key`], ignoring parity bits.
+    pub fn index_of(&self, key: &DesKey) -> Option<u64> {
+        let value = key.to_u64();
+        if value & !self.mask() & !PARITY_MASK != self.base & !PARITY_MASK {
+            return None;
+        }
+        Some(self.free.iter().enumerate().fold(0, |acc, (j, &i)| acc | (value >> i & 1) << j))
+    }
+
+    pub fn contains(&self, key: &DesKey) -> bool {
+        self.index_of(key).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=DesKey> + '_ {
+        (0..self.size()).map(move |index| self.key(index))
+    }
+}
@@ -0,0 +1,26 @@
+use super::*;
+
+#[test]
+fn test_lowest() {
+    let space = KeySpace::lowest(DesKey::from(0x0123456789abcdef), 10);
+    assert_eq!(space.mask(), 0x0000000000000efe);
+    assert_eq!(space.bits(), 10);
+    assert_eq!(space.size(), 1024);
+    assert_eq!(KeySpace::lowest(DesKey::from(0), 56).mask(), !PARITY_MASK);
+}
+
+#[test]
+fn test_key() {
+    let base = DesKey::from(0x0123456789abcdef);
+    let space = KeySpace::from_mask(base, 0xff000000000000ff);
+    assert_eq!(space.bits(), 14);
+    assert_eq!(space.key(0), DesKey::from(0x0123456789abcd01));
+    assert_eq!(space.key(space.size() - 1), DesKey::from(0xfe23456789abcdfe).with_odd_parity());
+    for (index, key) in space.iter().enumerate().step_by(97) {
+        assert!(key.has_odd_parity());
+        assert_eq!(space.index_of(&key), Some(index as u64));
+    }
+    assert!(space.contains(&base));
+    assert!(space.contains(&DesKey::from(0x0123456789abcdee)));
+    assert!(!space.contains(&DesKey::from(0x0123456789abcfef)));
+}
@@ -1,115 +1,347 @@
-use std::convert::TryInto;
-use std::mem::swap;
+//! DES block cipher (FIPS 46-3).
+//!
+//! ```
+//! use des::{Des, DesKey};
+//!
+//! let key: DesKey = "133457799bbcdff1".parse().unwrap();
+//! let des = Des::new(&key);
+//! let ciphertext = des.encrypt_block([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+//! assert_eq!(ciphertext, [0x85, 0xe8, 0x13, 0x54, 0x0f, 0x0a, 0xb4, 0x05]);
+//! assert_eq!(des.decrypt_block(ciphertext), [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
 
 use rand::{Rng, thread_rng};
 use regex::Regex;
 
+pub mod bitslice;
+pub mod bruteforce;
+pub mod desx;
+pub mod differential;
+pub mod double;
+pub mod fast;
+pub mod keyspace;
+pub mod linear;
+pub mod provenance;
+pub mod trace;
+pub mod triple;
+pub mod validation;
+
 #[cfg(test)]
 mod test;
 
-pub fn keygen() -> [bool; 64] {
-    let mut rng = thread_rng();
-    let mut key: u64 = rng.gen();
-    let mut result = [false; 64];
-    for i in 0..8 {
-        let mut xor = true;
-        for j in 0..7 {
-            let x = (key & 1) != 0;
-            result[i * 8 + j] = x;
-            xor ^= x;
-            key >>= 1;
-        }
-        result[i * 8 + 7] = xor;
-    }
-    result
-}
+/// A 64-bit DES key. The least significant bit of every byte is a parity bit which the cipher
+/// ignores; [`DesKey::from_hex`] insists on odd parity, the other constructors accept any value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DesKey([u8; 8]);
 
 #[derive(Debug, PartialEq)]
 pub enum ParseKeyError {
+    /// The key is not exactly 16 hexadecimal digits.
     InvalidKeyStringFormat,
+    /// A byte of the key has even parity.
     CheckSumError,
+    /// One of the 4 weak keys, see [`KeyStrength::Weak`].
+    WeakKey,
+    /// One of the 12 semi-weak keys, see [`KeyStrength::SemiWeak`].
+    SemiWeakKey,
+    /// One of the 48 possibly-weak keys, see [`KeyStrength::PossiblyWeak`].
+    PossiblyWeakKey,
 }
 
-pub fn parse_key(key: &str) -> Result<[bool; 64], ParseKeyError> {
-    let regex = Regex::new("^[0-9a-fA-F]{16}$").unwrap();
-    if !regex.is_match(key) { return Err(ParseKeyError::InvalidKeyStringFormat); }
-    let mut result = Vec::with_capacity(64);
-    let mut chars = key.chars();
-    for _ in 0..16 {
-        let c = chars.next().unwrap();
-        let value = match c {
-            c @ '0'..='9' => { c as u32 - '0' as u32 }
-            c @ 'a'..='f' => { c as u32 - 'a' as u32 + 10 }
-            c @ 'A'..='F' => { c as u32 - 'A' as u32 + 10 }
-            _ => unreachable!()
-        };
-        for i in 0..4 {
-            result.push(((value >> (3 - i)) & 1) == 1);
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseKeyError::InvalidKeyStringFormat => write!(f, "key should be 16 hexadecimal digits per DES key"),
+            ParseKeyError::CheckSumError => write!(f, "every key byte should have odd parity"),
+            ParseKeyError::WeakKey => write!(f, "weak key: encryption is an involution"),
+            ParseKeyError::SemiWeakKey => write!(f, "semi-weak key: another key decrypts what this key encrypts"),
+            ParseKeyError::PossiblyWeakKey => write!(f, "possibly-weak key: the key schedule has only 4 distinct round keys"),
+        }
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+impl DesKey {
+    pub fn new(bytes: [u8; 8]) -> DesKey {
+        DesKey(bytes)
+    }
+
+    /// Parses 16 hexadecimal digits and checks the parity bits.
+    ///
+    /// Weak keys are accepted, since the published test vectors use them; call
+    /// [`DesKey::check_strength`] to reject them.
+    pub fn from_hex(key: &str) -> Result<DesKey, ParseKeyError> {
+        let key = DesKey::from_hex_ignoring_parity(key)?;
+        if key.has_odd_parity() {
+            Ok(key)
+        } else {
+            Err(ParseKeyError::CheckSumError)
+        }
+    }
+
+    /// Parses 16 hexadecimal digits without looking at the parity bits.
+    pub fn from_hex_ignoring_parity(key: &str) -> Result<DesKey, ParseKeyError> {
+        let regex = Regex::new("^[0-9a-fA-F]{16}$").unwrap();
+        if !regex.is_match(key) { return Err(ParseKeyError::InvalidKeyStringFormat); }
+        let value = u64::from_str_radix(key, 16).map_err(|_| ParseKeyError::InvalidKeyStringFormat)?;
+        Ok(DesKey::from(value))
+    }
+
+    pub fn to_bytes(self) -> [u8; 8] {
+        self.0
+    }
+
+    pub fn to_u64(self) -> u64 {
+        u64::from_be_bytes(self.0)
+    }
+
+    /// Whether every byte has an odd number of set bits, as FIPS 46-3 requires.
+    pub fn has_odd_parity(&self) -> bool {
+        self.0.iter().all(|b| b.count_ones() % 2 == 1)
+    }
+
+    /// Sets the low bit of every byte so that each byte has odd parity.
+    pub fn with_odd_parity(self) -> DesKey {
+        let mut bytes = self.0;
+        for b in bytes.iter_mut() {
+            let high = *b & 0xfe;
+            *b = high | (high.count_ones() + 1) as u8 & 1;
         }
+        DesKey(bytes)
     }
-    let result: [bool; 64] = result.try_into().unwrap();
-    if key_checksum(&result) {
-        Ok(result)
-    } else {
-        Err(ParseKeyError::CheckSumError)
+
+    /// Classifies the key by its key schedule.
+    ///
+    /// The round keys only depend on the 28-bit halves C0 and D0 produced by PC1, which are rotated
+    /// in place. If both halves are all zeros or all ones, every round key is the same (weak); if
+    /// both repeat with period 2 there are only two round keys (semi-weak); if both repeat with
+    /// period 4 or less there are only four (possibly-weak). This yields exactly the 4, 12 and 48
+    /// keys listed in the literature, up to parity bits.
+    pub fn strength(&self) -> KeyStrength {
+        const CONSTANT: [u32; 2] = [0, 0x0fffffff];
+        const PERIOD_2: [u32; 2] = [0x05555555, 0x0aaaaaaa];
+        const PERIOD_4: [u32; 4] = [0x03333333, 0x06666666, 0x0ccccccc, 0x09999999];
+        let cd = pc1(&self.bits());
+        let half = |bits: &[bool]| bits.iter().fold(0, |acc, &b| acc << 1 | b as u32);
+        let (c, d) = (half(&cd[..28]), half(&cd[28..]));
+        let within = |sets: &[&[u32]]| sets.iter().any(|set| set.contains(&c)) && sets.iter().any(|set| set.contains(&d));
+        if within(&[&CONSTANT]) {
+            KeyStrength::Weak
+        } else if within(&[&CONSTANT, &PERIOD_2]) {
+            KeyStrength::SemiWeak
+        } else if within(&[&CONSTANT, &PERIOD_2, &PERIOD_4]) {
+            KeyStrength::PossiblyWeak
+        } else {
+            KeyStrength::Normal
+        }
+    }
+
+    /// Rejects weak, semi-weak and possibly-weak keys with the matching [`ParseKeyError`].
+    pub fn check_strength(&self) -> Result<(), ParseKeyError> {
+        match self.strength() {
+            KeyStrength::Normal => Ok(()),
+            KeyStrength::Weak => Err(ParseKeyError::WeakKey),
+            KeyStrength::SemiWeak => Err(ParseKeyError::SemiWeakKey),
+            KeyStrength::PossiblyWeak => Err(ParseKeyError::PossiblyWeakKey),
+        }
+    }
+
+    fn bits(&self) -> [bool; 64] {
+        pick_64bit_from_slice(&self.0)
     }
 }
 
-pub fn key_checksum(key: &[bool; 64]) -> bool {
-    for i in 0..8 {
-        let mut sum = false;
-        for j in 0..8 {
-            sum ^= key[i * 8 + j];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStrength {
+    Normal,
+    /// All sixteen round keys are equal, so encryption and decryption are the same function.
+    Weak,
+    /// The round keys alternate between two values; the key has a partner key that decrypts.
+    SemiWeak,
+    /// The key schedule produces only four distinct round keys.
+    PossiblyWeak,
+}
+
+impl From<[u8; 8]> for DesKey {
+    fn from(bytes: [u8; 8]) -> DesKey {
+        DesKey(bytes)
+    }
+}
+
+impl From<u64> for DesKey {
+    fn from(value: u64) -> DesKey {
+        DesKey(value.to_be_bytes())
+    }
+}
+
+impl FromStr for DesKey {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<DesKey, ParseKeyError> {
+        DesKey::from_hex(s)
+    }
+}
+
+impl fmt::Display for DesKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in &self.0 {
+            write!(f, "{:02x}", b)?;
         }
-        if !sum { return false; }
+        Ok(())
     }
-    true
 }
 
+/// Generates a random key with correct parity bits which is not weak, semi-weak or possibly-weak.
+pub fn keygen() -> DesKey {
+    let mut rng = thread_rng();
+    loop {
+        let key = DesKey(rng.gen()).with_odd_parity();
+        if key.strength() == KeyStrength::Normal {
+            return key;
+        }
+    }
+}
+
+const PC1: [usize; 56] = [
+    57, 49, 41, 33, 25, 17, 9,
+    1, 58, 50, 42, 34, 26, 18,
+    10, 2, 59, 51, 43, 35, 27,
+    19, 11, 3, 60, 52, 44, 36,
+    63, 55, 47, 39, 31, 23, 15,
+    7, 62, 54, 46, 38, 30, 22,
+    14, 6, 61, 53, 45, 37, 29,
+    21, 13, 5, 28, 20, 12, 4
+];
+
+const PC2: [usize; 48] = [
+    14, 17, 11, 24, 1, 5,
+    3, 28, 15, 6, 21, 10,
+    23, 19, 12, 4, 26, 8,
+    16, 7, 27, 20, 13, 2,
+    41, 52, 31, 37, 47, 55,
+    30, 40, 51, 45, 33, 48,
+    44, 49, 39, 56, 34, 53,
+    46, 42, 50, 36, 29, 32
+];
+
+const IP: [usize; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2,
+    60, 52, 44, 36, 28, 20, 12, 4,
+    62, 54, 46, 38, 30, 22, 14, 6,
+    64, 56, 48, 40, 32, 24, 16, 8,
+    57, 49, 41, 33, 25, 17, 9, 1,
+    59, 51, 43, 35, 27, 19, 11, 3,
+    61, 53, 45, 37, 29, 21, 13, 5,
+    63, 55, 47, 39, 31, 23, 15, 7
+];
+
+const IP_INVERSE: [usize; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32,
+    39, 7, 47, 15, 55, 23, 63, 31,
+    38, 6, 46, 14, 54, 22, 62, 30,
+    37, 5, 45, 13, 53, 21, 61, 29,
+    36, 4, 44, 12, 52, 20, 60, 28,
+    35, 3, 43, 11, 51, 19, 59, 27,
+    34, 2, 42, 10, 50, 18, 58, 26,
+    33, 1, 41, 9, 49, 17, 57, 25
+];
+
+const E: [usize; 48] = [
+    32, 1, 2, 3, 4, 5,
+    4, 5, 6, 7, 8, 9,
+    8, 9, 10, 11, 12, 13,
+    12, 13, 14, 15, 16, 17,
+    16, 17, 18, 19, 20, 21,
+    20, 21, 22, 23, 24, 25,
+    24, 25, 26, 27, 28, 29,
+    28, 29, 30, 31, 32, 1
+];
+
+const P: [usize; 32] = [
+    16, 7, 20, 21,
+    29, 12, 28, 17,
+    1, 15, 23, 26,
+    5, 18, 31, 10,
+    2, 8, 24, 14,
+    32, 27, 3, 9,
+    19, 13, 30, 6,
+    22, 11, 4, 25
+];
+
+const S_BOX: [[[u8; 16]; 4]; 8] = [
+    [
+        [14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7],
+        [0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8],
+        [4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0],
+        [15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13],
+    ],
+    [
+        [15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10],
+        [3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5],
+        [0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15],
+        [13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9]
+    ],
+    [
+        [10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8],
+        [13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1],
+        [13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7],
+        [1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12]
+    ],
+    [
+        [7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15],
+        [13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9],
+        [10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4],
+        [3, 15, 0, 6, 10, 1, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14]
+    ],
+    [
+        [2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9],
+        [14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6],
+        [4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14],
+        [11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3]
+    ],
+    [
+        [12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11],
+        [10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8],
+        [9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6],
+        [4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13]
+    ],
+    [
+        [4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1],
+        [13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6],
+        [1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2],
+        [6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12]
+    ],
+    [
+        [13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7],
+        [1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2],
+        [7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8],
+        [2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11]
+    ]
+];
+
 fn pc1<T: Default + Copy>(key: &[T; 64]) -> [T; 56] {
-    const TABLE: [usize; 56] = [
-        57, 49, 41, 33, 25, 17, 9,
-        1, 58, 50, 42, 34, 26, 18,
-        10, 2, 59, 51, 43, 35, 27,
-        19, 11, 3, 60, 52, 44, 36,
-        63, 55, 47, 39, 31, 23, 15,
-        7, 62, 54, 46, 38, 30, 22,
-        14, 6, 61, 53, 45, 37, 29,
-        21, 13, 5, 28, 20, 12, 4
-    ];
     let mut result = [Default::default(); 56];
     for i in 0..56 {
-        result[i] = key[TABLE[i] - 1];
+        result[i] = key[PC1[i] - 1];
     }
     result
 }
 
 fn pc2<T: Default + Copy>(key: &[T; 56]) -> [T; 48] {
-    const TABLE: [usize; 48] = [
-        14, 17, 11, 24, 1, 5,
-        3, 28, 15, 6, 21, 10,
-        23, 19, 12, 4, 26, 8,
-        16, 7, 27, 20, 13, 2,
-        41, 52, 31, 37, 47, 55,
-        30, 40, 51, 45, 33, 48,
-        44, 49, 39, 56, 34, 53,
-        46, 42, 50, 36, 29, 32
-    ];
     let mut result = [Default::default(); 48];
     for i in 0..48 {
-        result[i] = key[TABLE[i] - 1];
+        result[i] = key[PC2[i] - 1];
     }
     result
 }
 
-fn rotate_left<T: Default + Clone>(array: &mut [T], count: usize) {
+fn rotate_left<T>(array: &mut [T], count: usize) {
     let count = count % array.len();
-    let mut result = vec![Default::default(); array.len()];
-    for i in 0..array.len() {
-        let value = array[(i + count) % array.len()].clone();
-        result[i] = value;
-    }
-    array.clone_from_slice(&result);
+    array.rotate_left(count);
 }
 
 fn generate_sub_key<T: Default + Copy>(key: &[T; 64]) -> [[T; 48]; 16] {
@@ -128,73 +360,33 @@ fn generate_sub_key<T: Default + Copy>(key: &[T; 64]) -> [[T; 48]; 16] {
 }
 
 fn ip<T: Default + Copy>(input: &[T; 64]) -> [T; 64] {
-    const TABLE: [usize; 64] = [
-        58, 50, 42, 34, 26, 18, 10, 2,
-        60, 52, 44, 36, 28, 20, 12, 4,
-        62, 54, 46, 38, 30, 22, 14, 6,
-        64, 56, 48, 40, 32, 24, 16, 8,
-        57, 49, 41, 33, 25, 17, 9, 1,
-        59, 51, 43, 35, 27, 19, 11, 3,
-        61, 53, 45, 37, 29, 21, 13, 5,
-        63, 55, 47, 39, 31, 23, 15, 7
-    ];
     let mut result = [Default::default(); 64];
     for i in 0..64 {
-        result[i] = input[TABLE[i] - 1];
+        result[i] = input[IP[i] - 1];
     }
     result
 }
 
 fn ip_inverse<T: Default + Copy>(input: &[T; 64]) -> [T; 64] {
-    const TABLE: [usize; 64] = [
-        40, 8, 48, 16, 56, 24, 64, 32,
-        39, 7, 47, 15, 55, 23, 63, 31,
-        38, 6, 46, 14, 54, 22, 62, 30,
-        37, 5, 45, 13, 53, 21, 61, 29,
-        36, 4, 44, 12, 52, 20, 60, 28,
-        35, 3, 43, 11, 51, 19, 59, 27,
-        34, 2, 42, 10, 50, 18, 58, 26,
-        33, 1, 41, 9, 49, 17, 57, 25
-    ];
     let mut result = [Default::default(); 64];
     for i in 0..64 {
-        result[i] = input[TABLE[i] - 1];
+        result[i] = input[IP_INVERSE[i] - 1];
     }
     result
 }
 
 fn e<T: Default + Copy>(input: &[T; 32]) -> [T; 48] {
-    const TABLE: [usize; 48] = [
-        32, 1, 2, 3, 4, 5,
-        4, 5, 6, 7, 8, 9,
-        8, 9, 10, 11, 12, 13,
-        12, 13, 14, 15, 16, 17,
-        16, 17, 18, 19, 20, 21,
-        20, 21, 22, 23, 24, 25,
-        24, 25, 26, 27, 28, 29,
-        28, 29, 30, 31, 32, 1
-    ];
     let mut result = [Default::default(); 48];
     for i in 0..48 {
-        result[i] = input[TABLE[i] - 1];
+        result[i] = input[E[i] - 1];
     }
     result
 }
 
 fn p<T: Default + Copy>(input: &[T; 32]) -> [T; 32] {
-    const TABLE: [usize; 32] = [
-        16, 7, 20, 21,
-        29, 12, 28, 17,
-        1, 15, 23, 26,
-        5, 18, 31, 10,
-        2, 8, 24, 14,
-        32, 27, 3, 9,
-        19, 13, 30, 6,
-        22, 11, 4, 25
-    ];
     let mut result = [Default::default(); 32];
     for i in 0..32 {
-        result[i] = input[TABLE[i] - 1];
+        result[i] = input[P[i] - 1];
     }
     result
 }
@@ -224,56 +416,7 @@ const fn to_bool(input: [[[u8; 16]; 4]; 8]) -> [[[[bool; 4]; 16]; 4]; 8] {
 }
 
 fn s(index: usize, input: &[bool], output: &mut [bool]) {
-    const TABLE: [[[[bool; 4]; 16]; 4]; 8] = to_bool([
-        [
-            [14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7],
-            [0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8],
-            [4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0],
-            [15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13],
-        ],
-        [
-            [15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10],
-            [3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5],
-            [0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15],
-            [13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9]
-        ],
-        [
-            [10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8],
-            [13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1],
-            [13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7],
-            [1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12]
-        ],
-        [
-            [7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15],
-            [13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9],
-            [10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4],
-            [3, 15, 0, 6, 10, 1, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14]
-        ],
-        [
-            [2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9],
-            [14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6],
-            [4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14],
-            [11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3]
-        ],
-        [
-            [12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11],
-            [10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8],
-            [9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6],
-            [4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13]
-        ],
-        [
-            [4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1],
-            [13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6],
-            [1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2],
-            [6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12]
-        ],
-        [
-            [13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7],
-            [1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2],
-            [7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8],
-            [2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11]
-        ]
-    ]);
+    const TABLE: [[[[bool; 4]; 16]; 4]; 8] = to_bool(S_BOX);
     debug_assert_eq!(input.len(), 6);
     debug_assert_eq!(output.len(), 4);
 
@@ -301,72 +444,88 @@ fn f(x: &[bool; 32], k: &[bool; 48]) -> [bool; 32] {
     p(&output)
 }
 
-fn enc(m: &[bool; 64], sub_key: &[[bool; 48]; 16]) -> [bool; 64] {
+/// Runs one round per element of `sub_key`; full DES passes all sixteen.
+fn enc(m: &[bool; 64], sub_key: &[[bool; 48]]) -> [bool; 64] {
     let mut m = ip(m);
-    let mut l = &mut [false; 32];
-    let mut r = &mut [false; 32];
+    let mut l = [false; 32];
+    let mut r = [false; 32];
     l.clone_from_slice(&m[..32]);
     r.clone_from_slice(&m[32..]);
-    for key in sub_key {
-        let y = f(r, key);
-        for i in 0..32 {
-            l[i] ^= y[i];
-        }
-        swap::<&mut [bool; 32]>(&mut l, &mut r);
-    }
-    m[..32].clone_from_slice(r);
-    m[32..].clone_from_slice(l);
+    let (r, l) = feistel::network(l, r, sub_key, &f);
+    m[..32].clone_from_slice(&r);
+    m[32..].clone_from_slice(&l);
     ip_inverse(&m)
 }
 
 fn pick_64bit_from_slice(input: &[u8]) -> [bool; 64] {
     let mut result = [false; 64];
-    for (value, i) in input.iter().zip(0..8) {
-        result[i * 8 + 0] = (value & 0b10000000) != 0;
-        result[i * 8 + 1] = (value & 0b01000000) != 0;
-        result[i * 8 + 2] = (value & 0b00100000) != 0;
-        result[i * 8 + 3] = (value & 0b00010000) != 0;
-        result[i * 8 + 4] = (value & 0b00001000) != 0;
-        result[i * 8 + 5] = (value & 0b00000100) != 0;
-        result[i * 8 + 6] = (value & 0b00000010) != 0;
-        result[i * 8 + 7] = (value & 0b00000001) != 0;
+    for (i, value) in input.iter().take(8).enumerate() {
+        for j in 0..8 {
+            result[i * 8 + j] = (value >> (7 - j)) & 1 != 0;
+        }
     }
     result
 }
 
-pub(crate) fn push_64bit(vec: &mut Vec<u8>, value: [bool; 64]) {
-    for i in 0..8 {
-        let mut v = 0;
+fn to_block(value: [bool; 64]) -> [u8; 8] {
+    let mut result = [0; 8];
+    for (i, r) in result.iter_mut().enumerate() {
         for j in 0..8 {
-            v <<= 1;
-            v |= if value[i * 8 + j] { 1 } else { 0 };
+            *r = *r << 1 | value[i * 8 + j] as u8;
         }
-        vec.push(v);
     }
+    result
 }
 
-pub fn encrypt(input: &[u8], key: &[bool; 64]) -> Vec<u8> {
-    let sub_key = generate_sub_key(key);
-    let mut result = Vec::with_capacity((input.len() + 7) & !7);
-    for i in 0..(input.len() + 7) / 8 {
-        let block = pick_64bit_from_slice(&input[i * 8..]);
-        let block = enc(&block, &sub_key);
-        push_64bit(&mut result, block);
-    }
-    result
+/// DES with the sixteen round keys of one key computed up front.
+#[derive(Debug, Clone)]
+pub struct Des {
+    sub_key: [[bool; 48]; 16],
+    rounds: usize,
 }
 
-pub fn decrypt(input: &[u8], key: &[bool; 64]) -> Vec<u8> {
-    let mut sub_key = generate_sub_key(key);
-    let (l, r) = sub_key.split_at_mut(8);
-    for i in 0..8 {
-        swap(&mut l[i], &mut r[7 - i]);
+impl Des {
+    pub fn new(key: &DesKey) -> Des {
+        Des::with_rounds(key, 16)
+    }
+
+    /// DES reduced to its first `rounds` rounds, for cryptanalysis. IP, the final swap and IP⁻¹
+    /// are kept, so `with_rounds(key, 16)` is the full cipher.
+    pub fn with_rounds(key: &DesKey, rounds: usize) -> Des {
+        assert!((1..=16).contains(&rounds), "DES has 1 to 16 rounds");
+        Des { sub_key: generate_sub_key(&key.bits()), rounds }
     }
-    let mut result = Vec::with_capacity((input.len() + 7) & !7);
-    for i in 0..(input.len() + 7) / 8 {
-        let block = pick_64bit_from_slice(&input[i * 8..]);
-        let block = enc(&block, &sub_key);
-        push_64bit(&mut result, block);
+
+    pub fn encrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        to_block(enc(&pick_64bit_from_slice(&block), &self.sub_key[..self.rounds]))
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        let mut sub_key = self.sub_key;
+        sub_key[..self.rounds].reverse();
+        to_block(enc(&pick_64bit_from_slice(&block), &sub_key[..self.rounds]))
+    }
+}
+
+fn ecb(input: &[u8], mut crypt: impl FnMut(u64) -> u64) -> Vec<u8> {
+    let mut result = Vec::with_capacity(input.len().div_ceil(8) * 8);
+    for chunk in input.chunks(8) {
+        let mut block = [0; 8];
+        block[..chunk.len()].copy_from_slice(chunk);
+        result.extend_from_slice(&crypt(u64::from_be_bytes(block)).to_be_bytes());
     }
     result
 }
+
+/// Encrypts `input` in ECB mode with [`fast::FastDes`]. The last block is padded with zero bytes.
+pub fn encrypt(input: &[u8], key: &DesKey) -> Vec<u8> {
+    let des = fast::FastDes::new(key);
+    ecb(input, |block| des.encrypt_u64(block))
+}
+
+/// Decrypts `input` in ECB mode with [`fast::FastDes`]. A trailing partial block is padded with
+/// zero bytes first.
+pub fn decrypt(input: &[u8], key: &DesKey) -> Vec<u8> {
+    let des = fast::FastDes::new(key);
+    ecb(input, |block| des.decrypt_u64(block))
+}
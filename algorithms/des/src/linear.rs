@@ -0,0 +1,538 @@
+//! Linear cryptanalysis of DES reduced to a few rounds, after Matsui.
+//!
+//! A mask selects bits whose XOR (parity) is taken. A linear approximation states that the parity
+//! of some plaintext, ciphertext and key bits is zero more or less often than half the time; its
+//! correlation is `2p - 1` for probability `p`, and its bias `p - 1/2`. Correlations of the rounds
+//! of a trail multiply, which is the piling-up lemma. As in [`crate::differential`], masks apply to
+//! the halves between IP and IP⁻¹.
+
+use std::fmt::{self, Write};
+
+use rand::{thread_rng, Rng};
+
+use crate::differential::{inverse_p, nibble, permute_p, sbox, sbox_table};
+use crate::fast::{expand_chunk, initial_permutation, sub_keys, FastDes};
+use crate::provenance::sub_key_sources;
+use crate::{keygen, DesKey};
+
+#[cfg(test)]
+mod test;
+
+fn parity(x: u32) -> bool {
+    x.count_ones() & 1 != 0
+}
+
+/// `linear_approximation_table(i)[a][b]` is the number of inputs `x` of S-box `i` with
+/// `a·x = b·S(x)`, minus 32.
+pub fn linear_approximation_table(index: usize) -> [[i8; 16]; 64] {
+    let mut result = [[-32; 16]; 64];
+    for (a, row) in result.iter_mut().enumerate() {
+        for (b, entry) in row.iter_mut().enumerate() {
+            for x in 0..64u8 {
+                if parity((a as u8 & x) as u32) == parity((b as u8 & sbox(index, x)) as u32) {
+                    *entry += 1;
+                }
+            }
+        }
+    }
+    result
+}
+
+/// A linear approximation table as 64 rows of 16 entries.
+pub fn lat_matrix(table: &[[i8; 16]; 64]) -> String {
+    let mut result = String::from("   a\\b");
+    for b in 0..16 {
+        write!(result, " {:>3x}", b).unwrap();
+    }
+    result.push('\n');
+    for (a, row) in table.iter().enumerate() {
+        write!(result, "{:>6x}", a).unwrap();
+        for entry in row.iter() {
+            write!(result, " {:>3}", entry).unwrap();
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// The mask on R selecting the bits that E moves into the input bits of S-box `index` selected by
+/// `mask`.
+fn expand_mask(index: usize, mask: u8) -> u32 {
+    (mask as u32).rotate_right(4 * index as u32 + 5)
+}
+
+/// The approximation of f used in one round: `f_mask·f(R, K) = r_mask·R ⊕ key_mask·K`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundApproximation {
+    pub r_mask: u32,
+    pub f_mask: u32,
+    /// Mask on the six round key bits entering each S-box.
+    pub key_mask: [u8; 8],
+    pub correlation: f64,
+}
+
+impl RoundApproximation {
+    const PASS: RoundApproximation = RoundApproximation { r_mask: 0, f_mask: 0, key_mask: [0; 8], correlation: 1.0 };
+}
+
+/// A linear trail: masks on (L0, R0) and the approximation of f in every round. The masks on the
+/// halves after each round follow from them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearTrail {
+    pub input: (u32, u32),
+    pub rounds: Vec<RoundApproximation>,
+}
+
+impl LinearTrail {
+    /// The masks on (L, R) after each round.
+    pub fn masks(&self) -> Vec<(u32, u32)> {
+        let mut state = self.input;
+        self.rounds.iter().map(|round| {
+            state = (state.1 ^ round.r_mask, round.f_mask);
+            state
+        }).collect()
+    }
+
+    /// The masks on (L, R) after the last round.
+    pub fn output(&self) -> (u32, u32) {
+        self.masks().last().copied().unwrap_or(self.input)
+    }
+
+    pub fn correlation(&self) -> f64 {
+        self.rounds.iter().map(|round| round.correlation).product()
+    }
+
+    pub fn bias(&self) -> f64 {
+        self.correlation() / 2.0
+    }
+
+    /// The key bits (FIPS numbering, most significant bit is bit 1) whose parity the trail
+    /// approximates, as a mask. Round key bits coming from the same key bit cancel.
+    pub fn key_bits(&self) -> u64 {
+        key_bits(self.rounds.iter().map(|round| round.key_mask))
+    }
+
+    /// Parity of the round key bits the trail approximates, under `key`.
+    pub fn key_parity(&self, key: &DesKey) -> bool {
+        parity_u64(self.key_bits() & key.to_u64())
+    }
+
+    /// `λ0·L0 ⊕ ρ0·R0 ⊕ λn·Ln ⊕ ρn·Rn` for a plaintext and the ciphertext of the cipher with as many
+    /// rounds as the trail.
+    fn data_parity(&self, plaintext: u64, ciphertext: u64) -> bool {
+        let (l0, r0) = initial_permutation(plaintext);
+        let (rn, ln) = initial_permutation(ciphertext);
+        let (lambda, rho) = self.output();
+        parity(self.input.0 & l0 ^ self.input.1 & r0 ^ lambda & ln ^ rho & rn)
+    }
+}
+
+fn parity_u64(x: u64) -> bool {
+    x.count_ones() & 1 != 0
+}
+
+/// The key bits selected by masks on the round keys of the first rounds.
+fn key_bits(round_masks: impl Iterator<Item=[u8; 8]>) -> u64 {
+    let sources = sub_key_sources();
+    let mut result = 0;
+    for (mask, sources) in round_masks.zip(sources.iter()) {
+        for (position, &source) in sources.iter().enumerate() {
+            if mask[position / 6] >> (5 - position % 6) & 1 != 0 {
+                result ^= 1 << (64 - source as u32);
+            }
+        }
+    }
+    result
+}
+
+/// Writes a key bit mask as `K[a] ⊕ K[b] ⊕ …`.
+pub fn key_bits_string(bits: u64) -> String {
+    let terms: Vec<_> = (1..=64).filter(|i| bits >> (64 - i) & 1 != 0).map(|i| format!("K[{}]", i)).collect();
+    if terms.is_empty() { String::from("0") } else { terms.join(" ⊕ ") }
+}
+
+impl fmt::Display for LinearTrail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "     L mask   R mask    R->f     f out    correlation")?;
+        writeln!(f, "  0  {:08x} {:08x}", self.input.0, self.input.1)?;
+        for (i, (round, (l, r))) in self.rounds.iter().zip(self.masks()).enumerate() {
+            if round.f_mask == 0 {
+                writeln!(f, "{:>3}  {:08x} {:08x}", i + 1, l, r)?;
+            } else {
+                writeln!(f, "{:>3}  {:08x} {:08x}    {:08x} {:08x}  {:+.4}", i + 1, l, r, round.r_mask, round.f_mask, round.correlation)?;
+            }
+        }
+        writeln!(f, "key bits {}", key_bits_string(self.key_bits()))?;
+        write!(f, "bias {:+.3e} (2^{:.2})", self.bias(), self.bias().abs().log2())
+    }
+}
+
+/// The linear approximation tables of all eight S-boxes, and the search for linear trails.
+#[derive(Debug, Clone)]
+pub struct LinearTables {
+    tables: [[[i8; 16]; 64]; 8],
+    /// For each S-box and output mask, the input masks with non-zero entry, strongest first.
+    inputs: Vec<Vec<Vec<(u8, i8)>>>,
+    /// Every single S-box approximation as a round approximation, strongest first.
+    singles: Vec<RoundApproximation>,
+}
+
+impl Default for LinearTables {
+    fn default() -> LinearTables {
+        LinearTables::new()
+    }
+}
+
+impl LinearTables {
+    pub fn new() -> LinearTables {
+        let mut tables = [[[0; 16]; 64]; 8];
+        for (i, table) in tables.iter_mut().enumerate() {
+            *table = linear_approximation_table(i);
+        }
+        let mut inputs = Vec::with_capacity(8);
+        let mut singles = Vec::new();
+        for (i, table) in tables.iter().enumerate() {
+            let by_output: Vec<_> = (0..16).map(|b| {
+                let mut masks: Vec<_> = (0..64u8).map(|a| (a, table[a as usize][b])).filter(|&(_, n)| n != 0).collect();
+                masks.sort_by_key(|&(a, n)| (-n.abs(), a));
+                if b != 0 {
+                    for &(a, n) in &masks {
+                        let mut key_mask = [0; 8];
+                        key_mask[i] = a;
+                        singles.push(RoundApproximation {
+                            r_mask: expand_mask(i, a),
+                            f_mask: permute_p((b as u32) << (28 - 4 * i)),
+                            key_mask,
+                            correlation: n as f64 / 32.0,
+                        });
+                    }
+                }
+                masks
+            }).collect();
+            inputs.push(by_output);
+        }
+        singles.sort_by(|a, b| b.correlation.abs().partial_cmp(&a.correlation.abs()).unwrap());
+        LinearTables { tables, inputs, singles }
+    }
+
+    pub fn table(&self, index: usize) -> &[[i8; 16]; 64] {
+        &self.tables[index]
+    }
+
+    /// The best trails over 1 to `max_rounds` rounds.
+    ///
+    /// This is Matsui's branch and bound: the approximations of the first two rounds are chosen
+    /// freely, which fixes the output masks of f in all later rounds, and a partial trail is given up
+    /// as soon as it cannot beat the best trail found so far even if the remaining rounds are as good
+    /// as the best shorter trail. The free rounds are limited to a single active S-box.
+    pub fn best_trails(&self, max_rounds: usize) -> Vec<LinearTrail> {
+        self.search_all(max_rounds)
+    }
+
+    /// The best `rounds`-round trail among those whose output mask on L selects the output of one
+    /// or two S-boxes in the next round, so that [`algorithm2`] can guess their key bits. `rounds`
+    /// is at least 2.
+    pub fn best_trail_for_algorithm2(&self, rounds: usize) -> LinearTrail {
+        assert!(rounds >= 2, "a one-round trail leaves the mask on L free");
+        let mut bounds = vec![1.0];
+        bounds.extend(self.best_trails(rounds - 1).iter().map(|trail| trail.correlation().abs()));
+        self.search(rounds, &bounds, &|trail: &LinearTrail| (1..=2).contains(&active_sboxes(trail.output().0).len()))
+    }
+
+    fn search_all(&self, max_rounds: usize) -> Vec<LinearTrail> {
+        let mut bounds = vec![1.0];
+        let mut result = Vec::with_capacity(max_rounds);
+        for rounds in 1..=max_rounds {
+            let best = self.search(rounds, &bounds, &|_: &LinearTrail| true);
+            bounds.push(best.correlation().abs());
+            result.push(best);
+        }
+        result
+    }
+
+    /// The best `rounds`-round trail passing `accept`, given the best correlations of shorter trails
+    /// in `bounds`.
+    fn search<F: Fn(&LinearTrail) -> bool>(&self, rounds: usize, bounds: &[f64], accept: &F) -> LinearTrail {
+        let options: Vec<_> = std::iter::once(RoundApproximation::PASS).chain(self.singles.iter().copied()).collect();
+        // Matsui's estimate: a trail found early only prunes if it is good, so start by asking for
+        // one nearly as good as the best shorter trail extended by the best round, and lower the
+        // estimate until a trail beats it.
+        let mut estimate = bounds[rounds - 1] * bounds[1.min(rounds - 1)];
+        loop {
+            let mut search = Search {
+                tables: self,
+                rounds,
+                bounds,
+                accept,
+                trail: LinearTrail { input: (0, 0), rounds: Vec::with_capacity(rounds) },
+                best: None,
+                best_correlation: estimate,
+            };
+            search.run(&options);
+            match search.best {
+                Some(best) => return best,
+                None => estimate /= 2.0,
+            }
+        }
+    }
+}
+
+/// The S-boxes (0-based) whose outputs the mask on f's output selects.
+fn active_sboxes(f_mask: u32) -> Vec<usize> {
+    let b = inverse_p(f_mask);
+    (0..8).filter(|&i| nibble(b, i) != 0).collect()
+}
+
+struct Search<'a, F> {
+    tables: &'a LinearTables,
+    rounds: usize,
+    /// `bounds[n]` is the absolute correlation of the best `n`-round trail.
+    bounds: &'a [f64],
+    accept: &'a F,
+    /// The trail being built.
+    trail: LinearTrail,
+    best: Option<LinearTrail>,
+    best_correlation: f64,
+}
+
+impl<'a, F: Fn(&LinearTrail) -> bool> Search<'a, F> {
+    fn run(&mut self, options: &[RoundApproximation]) {
+        for first in options {
+            if first.correlation.abs() * self.bounds[self.rounds - 1] <= self.best_correlation {
+                break;
+            }
+            if self.rounds == 1 {
+                if first.f_mask != 0 {
+                    self.trail.input = (first.f_mask, first.r_mask);
+                    self.trail.rounds.push(*first);
+                    self.finish();
+                    self.trail.rounds.pop();
+                }
+                continue;
+            }
+            for second in options {
+                let correlation = (first.correlation * second.correlation).abs();
+                if correlation * self.bounds[self.rounds - 2] <= self.best_correlation {
+                    break;
+                }
+                if first.f_mask == 0 && second.f_mask == 0 {
+                    continue;
+                }
+                self.trail.input = (first.f_mask, second.f_mask ^ first.r_mask);
+                self.trail.rounds.push(*first);
+                self.trail.rounds.push(*second);
+                self.extend((first.f_mask ^ second.r_mask, second.f_mask), correlation);
+                self.trail.rounds.clear();
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        let correlation = self.trail.correlation().abs();
+        if correlation > self.best_correlation && (self.accept)(&self.trail) {
+            self.best_correlation = correlation;
+            self.best = Some(self.trail.clone());
+        }
+    }
+
+    /// Appends the rounds whose output mask of f is forced by the masks `(l, r)` after the trail so
+    /// far.
+    fn extend(&mut self, (l, r): (u32, u32), correlation: f64) {
+        if self.trail.rounds.len() == self.rounds {
+            self.finish();
+            return;
+        }
+        if l == 0 {
+            self.trail.rounds.push(RoundApproximation::PASS);
+            self.extend((r, 0), correlation);
+            self.trail.rounds.pop();
+            return;
+        }
+        let b = inverse_p(l);
+        let mut active = [(0, 0); 8];
+        let mut count = 0;
+        for i in 0..8 {
+            if nibble(b, i) != 0 {
+                active[count] = (i, nibble(b, i) as usize);
+                count += 1;
+            }
+        }
+        let round = RoundApproximation { r_mask: 0, f_mask: l, key_mask: [0; 8], correlation: 1.0 };
+        self.choose_inputs(r, &active[..count], round, correlation);
+    }
+
+    /// Picks the input mask of the first of the `active` (S-box, output mask) pairs still open in
+    /// `round`, then appends the round once all are picked.
+    fn choose_inputs(&mut self, r: u32, active: &[(usize, usize)], round: RoundApproximation, correlation: f64) {
+        let ((i, b), rest) = match active.split_first() {
+            Some(first) => first,
+            None => {
+                self.trail.rounds.push(round);
+                self.extend((r ^ round.r_mask, round.f_mask), correlation);
+                self.trail.rounds.pop();
+                return;
+            }
+        };
+        let remaining_rounds = self.rounds - self.trail.rounds.len() - 1;
+        let rest_bound: f64 = rest.iter()
+            .map(|&(i, b)| self.tables.inputs[i][b].first().map_or(0.0, |&(_, n)| n.abs() as f64 / 32.0))
+            .product();
+        let bound = rest_bound * self.bounds[remaining_rounds];
+        for &(a, n) in &self.tables.inputs[*i][*b] {
+            let c = n as f64 / 32.0;
+            if correlation * c.abs() * bound <= self.best_correlation {
+                break;
+            }
+            let mut next = round;
+            next.r_mask ^= expand_mask(*i, a);
+            next.key_mask[*i] = a;
+            next.correlation *= c;
+            self.choose_inputs(r, rest, next, correlation * c.abs());
+        }
+    }
+}
+
+/// `count` random plaintexts and their encryptions under `rounds`-round DES with `key`.
+pub fn known_plaintexts(key: &DesKey, rounds: usize, count: usize) -> Vec<(u64, u64)> {
+    let des = FastDes::with_rounds(key, rounds);
+    let mut rng = thread_rng();
+    (0..count).map(|_| {
+        let plaintext = rng.gen();
+        (plaintext, des.encrypt_u64(plaintext))
+    }).collect()
+}
+
+/// Matsui's Algorithm 1: the parity of the key bits of `trail`, guessed from how often the data
+/// side of the approximation is zero over known `samples` of a cipher with as many rounds as the
+/// trail.
+pub fn algorithm1(trail: &LinearTrail, samples: &[(u64, u64)]) -> bool {
+    let zeros = samples.iter().filter(|&&(p, c)| !trail.data_parity(p, c)).count();
+    (zeros * 2 < samples.len()) ^ (trail.correlation() < 0.0)
+}
+
+/// What [`algorithm2`] recovered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Algorithm2Result {
+    /// Rounds of the attacked cipher. The recovered round key bits are of the last round.
+    pub rounds: usize,
+    /// The six bits of the last round key entering each S-box guessed by the attack.
+    pub sub_key: [Option<u8>; 8],
+    /// The parity of the key bits in [`Algorithm2Result::key_bits`].
+    pub parity: bool,
+    /// The key bits of the trail, see [`LinearTrail::key_bits`].
+    pub key_bits: u64,
+    pub samples: usize,
+}
+
+impl Algorithm2Result {
+    /// Number of key bits recovered: the round key bits and the parity bit.
+    pub fn recovered_bits(&self) -> usize {
+        self.sub_key.iter().flatten().count() * 6 + 1
+    }
+
+    /// Whether every recovered bit is right for `key`.
+    pub fn is_correct(&self, key: &DesKey) -> bool {
+        let expected = sub_keys(key)[self.rounds - 1];
+        self.sub_key.iter().zip(expected.iter()).all(|(k, &e)| k.iter().all(|&k| k == e))
+            && self.parity == parity_u64(self.key_bits & key.to_u64())
+    }
+}
+
+impl fmt::Display for Algorithm2Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}-round DES, {} known plaintexts", self.rounds, self.samples)?;
+        write!(f, "K{}:", self.rounds)?;
+        for chunk in self.sub_key.iter() {
+            match chunk {
+                Some(chunk) => write!(f, " {:06b}", chunk)?,
+                None => write!(f, " ??????")?,
+            }
+        }
+        writeln!(f)?;
+        write!(f, "{} = {}", key_bits_string(self.key_bits), self.parity as u8)
+    }
+}
+
+/// Matsui's Algorithm 2: guesses the last round key bits entering the S-boxes that `trail`, an
+/// approximation of all but the last round, needs the output of, and the parity of the trail's key
+/// bits.
+///
+/// Every guess partially decrypts the last round and counts how often the approximation holds; the
+/// guess with the largest deviation from half the samples wins. The samples are first reduced to
+/// counts per value of the bits that matter, so each guess costs a pass over at most 2^13 counters.
+pub fn algorithm2(trail: &LinearTrail, samples: &[(u64, u64)]) -> Algorithm2Result {
+    let (lambda, rho) = trail.output();
+    let sboxes = active_sboxes(lambda);
+    assert!((1..=2).contains(&sboxes.len()), "the trail has to need the output of one or two S-boxes");
+    let b = inverse_p(lambda);
+    let table = sbox_table();
+    let width = 6 * sboxes.len();
+    let mut counts = vec![0u32; 1 << (width + 1)];
+    for &(p, c) in samples {
+        let (l0, r0) = initial_permutation(p);
+        let (rn, ln) = initial_permutation(c);
+        // L_{n-1} = Rn ⊕ f(Ln, Kn) and R_{n-1} = Ln.
+        let v = parity(trail.input.0 & l0 ^ trail.input.1 & r0 ^ lambda & rn ^ rho & ln);
+        let index = sboxes.iter().fold(0, |acc, &i| acc << 6 | expand_chunk(ln, i) as usize);
+        counts[index << 1 | v as usize] += 1;
+    }
+    let mut best = (0, 0i64);
+    for guess in 0..1usize << width {
+        let mut zeros = 0i64;
+        for (index, &count) in counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let mut v = index & 1 != 0;
+            for (j, &i) in sboxes.iter().enumerate() {
+                let shift = 6 * (sboxes.len() - 1 - j);
+                let x = (index >> 1 >> shift ^ guess >> shift) & 0x3f;
+                v ^= parity((table[i][x] & nibble(b, i)) as u32);
+            }
+            if !v {
+                zeros += count as i64;
+            }
+        }
+        let deviation = zeros - samples.len() as i64 / 2;
+        if deviation.abs() > best.1.abs() {
+            best = (guess, deviation);
+        }
+    }
+    let mut sub_key = [None; 8];
+    for (j, &i) in sboxes.iter().enumerate() {
+        sub_key[i] = Some((best.0 >> (6 * (sboxes.len() - 1 - j)) & 0x3f) as u8);
+    }
+    Algorithm2Result {
+        rounds: trail.rounds.len() + 1,
+        sub_key,
+        parity: (best.1 < 0) ^ (trail.correlation() < 0.0),
+        key_bits: trail.key_bits(),
+        samples: samples.len(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    One,
+    Two,
+}
+
+/// The fraction of `trials` random keys for which `algorithm` recovers every bit it guesses from
+/// `samples` known plaintexts. `trail` covers all rounds for Algorithm 1 and all but the last for
+/// Algorithm 2.
+pub fn success_rate(trail: &LinearTrail, algorithm: Algorithm, samples: usize, trials: usize) -> f64 {
+    let successes = (0..trials).filter(|_| {
+        let key = keygen();
+        match algorithm {
+            Algorithm::One => {
+                let data = known_plaintexts(&key, trail.rounds.len(), samples);
+                algorithm1(trail, &data) == trail.key_parity(&key)
+            }
+            Algorithm::Two => {
+                let data = known_plaintexts(&key, trail.rounds.len() + 1, samples);
+                algorithm2(trail, &data).is_correct(&key)
+            }
+        }
+    }).count();
+    successes as f64 / trials as f64
+}
@@ -0,0 +1,71 @@
+use crate::fast::sub_keys;
+
+use super::*;
+
+#[test]
+fn test_linear_approximation_table() {
+    for i in 0..8 {
+        let table = linear_approximation_table(i);
+        assert_eq!(table[0][0], 32);
+        assert!(table[0][1..].iter().all(|&n| n == 0));
+        assert!(table[1..].iter().all(|row| row[0] == 0));
+        assert!(table.iter().all(|row| row.iter().all(|n| n % 2 == 0)));
+    }
+    // Matsui's strongest approximation: bit 2 of the S5 input against all four output bits holds
+    // for only 12 of 64 inputs.
+    assert_eq!(linear_approximation_table(4)[0x10][0xf], -20);
+}
+
+#[test]
+fn test_best_trails() {
+    // |bias| of the best trails for 1 to 8 rounds as listed by Matsui.
+    let expected = [0.3125, 0.3125, 0.1953125, 0.06103515625, 0.019073486328125, 0.003814697265625,
+        0.0019073486328125, 0.000596046447753906];
+    let trails = LinearTables::new().best_trails(8);
+    for (trail, &expected) in trails.iter().zip(expected.iter()) {
+        assert!((trail.bias().abs() - expected).abs() < 1e-12, "{}", trail);
+    }
+}
+
+#[test]
+fn test_trail_holds() {
+    let trail = &LinearTables::new().best_trails(3)[2];
+    let key = keygen();
+    let samples = known_plaintexts(&key, 3, 20000);
+    let holds = samples.iter().filter(|&&(p, c)| trail.data_parity(p, c) == trail.key_parity(&key)).count();
+    let probability = holds as f64 / samples.len() as f64;
+    assert!((probability - 0.5 - trail.bias()).abs() < 0.02, "{}", probability);
+}
+
+#[test]
+fn test_key_bits() {
+    let trail = &LinearTables::new().best_trails(6)[5];
+    for _ in 0..20 {
+        let key = keygen();
+        let sub_key = sub_keys(&key);
+        let expected = trail.rounds.iter().zip(sub_key.iter())
+            .flat_map(|(round, k)| round.key_mask.iter().zip(k.iter()).map(|(m, k)| m & k))
+            .fold(0, |acc, x| acc ^ x as u32);
+        assert_eq!(trail.key_parity(&key), parity(expected));
+    }
+}
+
+#[test]
+fn test_algorithm1() {
+    let trail = &LinearTables::new().best_trails(4)[3];
+    let samples = (16.0 / (trail.bias() * trail.bias())) as usize;
+    let key = keygen();
+    assert_eq!(algorithm1(trail, &known_plaintexts(&key, 4, samples)), trail.key_parity(&key));
+}
+
+#[test]
+fn test_algorithm2() {
+    let trail = LinearTables::new().best_trail_for_algorithm2(4);
+    let samples = (32.0 / (trail.bias() * trail.bias())) as usize;
+    let key = keygen();
+    let result = algorithm2(&trail, &known_plaintexts(&key, 5, samples));
+    assert_eq!(result.rounds, 5);
+    assert_eq!(result.samples, samples);
+    assert!(result.recovered_bits() >= 7);
+    assert!(result.is_correct(&key), "{}", result);
+}
@@ -1,15 +1,203 @@
-use clap::{App, Arg, SubCommand};
+use std::io::{Read, Write};
+use std::time::Duration;
 
-use lib::{decrypt, encrypt, keygen, parse_key, push_64bit};
-use std::io::Read;
+use clap::{App, Arg, ArgMatches, SubCommand};
 
-mod lib;
+use des::bruteforce::BruteForce;
+use des::desx::{self, DesXKey};
+use des::differential::{self, DifferenceTables, Oracle, SIX_ROUND_CHARACTERISTICS};
+use des::double::{self, DoubleDes, DoubleDesKey};
+use des::fast::FastDes;
+use des::keyspace::KeySpace;
+use des::linear::{self, Algorithm, LinearTables};
+use des::provenance;
+use des::trace::{trace_block, Direction, Trace};
+use des::triple::{self, KeyingOption, TripleDesKey};
+use des::validation::{self, FULL_MONTE_CARLO_ROUNDS, MONTE_CARLO_CHAIN};
+use des::{decrypt, encrypt, keygen, DesKey, ParseKeyError};
+use keyfile::{path_of_argument, KeyFile};
 
 fn main() {
     let matches = App::new("des")
         .about("DES cipher")
         .subcommand(SubCommand::with_name("keygen")
-            .about("generate new key by random"))
+            .about("generate new key by random")
+            .arg(Arg::with_name("keys")
+                .short("n")
+                .long("keys")
+                .help("number of independent keys in a triple DES bundle")
+                .takes_value(true)
+                .possible_values(&["1", "2", "3"])
+                .default_value("1"))
+            .arg(Arg::with_name("desx")
+                .short("x")
+                .long("desx")
+                .conflicts_with("keys")
+                .help("flag to generate a DES-X key(K, K1 and K2)"))
+            .arg(Arg::with_name("double")
+                .long("double")
+                .conflicts_with_all(&["keys", "desx"])
+                .help("flag to generate a double DES key(K1 and K2)"))
+            .arg(Arg::with_name("out")
+                .short("o")
+                .long("out")
+                .help("write the key to a new key file instead of printing it")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("provenance")
+            .about("show which key and data bits reach each position")
+            .arg(Arg::with_name("view")
+                .help("matrix to print")
+                .possible_values(&["schedule", "usage", "e", "permutations"])
+                .default_value("schedule"))
+            .arg(Arg::with_name("key-bit")
+                .long("key-bit")
+                .help("list the rounds, round key positions and S-boxes using this key bit(1-64)")
+                .takes_value(true))
+            .arg(Arg::with_name("r-bit")
+                .long("r-bit")
+                .help("list the S-boxes reading this bit of R(1-32) through E")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("differential")
+            .about("differential cryptanalysis of reduced-round DES")
+            .arg(Arg::with_name("view")
+                .help("what to compute")
+                .possible_values(&["ddt", "characteristics", "attack"])
+                .default_value("characteristics"))
+            .arg(Arg::with_name("sbox")
+                .long("sbox")
+                .help("S-box(1-8) whose difference distribution table to print")
+                .takes_value(true)
+                .default_value("1"))
+            .arg(Arg::with_name("rounds")
+                .short("r")
+                .long("rounds")
+                .help("number of rounds of the attacked DES")
+                .takes_value(true)
+                .possible_values(&["4", "6"])
+                .default_value("6"))
+            .arg(Arg::with_name("pairs")
+                .short("p")
+                .long("pairs")
+                .help("chosen plaintext pairs per characteristic(at most, for 4 rounds)")
+                .takes_value(true))
+            .arg(Arg::with_name("key")
+                .short("k")
+                .long("key")
+                .help("key of the oracle(random if omitted)")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("linear")
+            .about("linear cryptanalysis of reduced-round DES")
+            .arg(Arg::with_name("view")
+                .help("what to compute")
+                .possible_values(&["lat", "trails", "attack", "success"])
+                .default_value("trails"))
+            .arg(Arg::with_name("sbox")
+                .long("sbox")
+                .help("S-box(1-8) whose linear approximation table to print")
+                .takes_value(true)
+                .default_value("5"))
+            .arg(Arg::with_name("rounds")
+                .short("r")
+                .long("rounds")
+                .help("number of rounds of the attacked DES(the longest trail for 'trails')")
+                .takes_value(true)
+                .possible_values(&["3", "4", "5", "6", "7", "8"])
+                .default_value("8"))
+            .arg(Arg::with_name("algorithm")
+                .short("a")
+                .long("algorithm")
+                .help("Matsui's algorithm to run")
+                .takes_value(true)
+                .possible_values(&["1", "2"])
+                .default_value("2"))
+            .arg(Arg::with_name("samples")
+                .short("n")
+                .long("samples")
+                .help("known plaintexts(8 / bias^2 if omitted)")
+                .takes_value(true))
+            .arg(Arg::with_name("trials")
+                .long("trials")
+                .help("random keys per sample count for 'success'")
+                .takes_value(true)
+                .default_value("20"))
+            .arg(Arg::with_name("key")
+                .short("k")
+                .long("key")
+                .help("key of the attacked DES(random if omitted)")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("double")
+            .about("meet-in-the-middle attack on double DES with most key bits known")
+            .arg(Arg::with_name("free-bits")
+                .short("b")
+                .long("free-bits")
+                .help("unknown bits of each of the two keys(1-22)")
+                .takes_value(true)
+                .default_value("16"))
+            .arg(Arg::with_name("pairs")
+                .short("p")
+                .long("pairs")
+                .help("known plaintext-ciphertext pairs")
+                .takes_value(true)
+                .possible_values(&["1", "2", "3"])
+                .default_value("2"))
+            .arg(Arg::with_name("key")
+                .short("k")
+                .long("key")
+                .help("K1 and K2 of the attacked double DES as 32 hex digits(random if omitted)")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("bruteforce")
+            .about("search the unknown bits of a key with a known plaintext-ciphertext pair")
+            .arg(Arg::with_name("plaintext")
+                .short("p")
+                .long("plaintext")
+                .help("known plaintext block as 16 hex digits(random if omitted)")
+                .takes_value(true))
+            .arg(Arg::with_name("ciphertext")
+                .short("c")
+                .long("ciphertext")
+                .help("ciphertext of the plaintext as 16 hex digits(encrypted under the key if omitted)")
+                .requires("plaintext")
+                .takes_value(true))
+            .arg(Arg::with_name("complement")
+                .long("complement")
+                .help("ciphertext of the complemented plaintext, to test two keys per encryption")
+                .requires("ciphertext")
+                .takes_value(true))
+            .arg(Arg::with_name("no-complement")
+                .long("no-complement")
+                .conflicts_with("complement")
+                .help("flag to search without the complementation property"))
+            .arg(Arg::with_name("mask")
+                .short("m")
+                .long("mask")
+                .help("unknown key bits as 16 hex digits(parity bits are skipped)")
+                .takes_value(true))
+            .arg(Arg::with_name("bits")
+                .short("b")
+                .long("bits")
+                .help("number of unknown key bits, the last non-parity bits of the key(default 24)")
+                .conflicts_with("mask")
+                .takes_value(true))
+            .arg(Arg::with_name("key")
+                .short("k")
+                .long("key")
+                .help("key with the known bits(with --ciphertext), or the key to find(random if omitted)")
+                .takes_value(true))
+            .arg(Arg::with_name("threads")
+                .short("t")
+                .long("threads")
+                .help("worker threads(all cores if omitted)")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name("selftest")
+            .about("run the NBS SP 500-20 known answer tests and the Monte Carlo tests")
+            .arg(Arg::with_name("full")
+                .long("full")
+                .help("flag to run all 400 outer Monte Carlo iterations instead of the first 20")))
+        .subcommand(SubCommand::with_name("fix-parity")
+            .about("set the parity bit of every byte of a key")
+            .arg(Arg::with_name("key")
+                .help("key to fix(16, 32 or 48 hex digits)")
+                .required(true)))
         .arg(Arg::with_name("decrypt")
             .short("d")
             .long("decrypt")
@@ -17,8 +205,27 @@ fn main() {
         .arg(Arg::with_name("key")
             .short("k")
             .long("key")
-            .help("Key for encrypt or decrypt")
+            .help("Key for encrypt or decrypt, or @FILE to read it and its cipher from a key file")
             .takes_value(true))
+        .arg(Arg::with_name("triple")
+            .short("t")
+            .long("triple")
+            .help("flag to use triple DES(key is 16, 32 or 48 hex digits)"))
+        .arg(Arg::with_name("desx")
+            .long("desx")
+            .conflicts_with("triple")
+            .help("flag to use DES-X(key is K, K1 and K2 as 48 hex digits)"))
+        .arg(Arg::with_name("double")
+            .long("double")
+            .conflicts_with_all(&["triple", "desx"])
+            .help("flag to use double DES(key is K1 and K2 as 32 hex digits)"))
+        .arg(Arg::with_name("trace")
+            .long("trace")
+            .help("flag to print every intermediate value of each block(single DES only)"))
+        .arg(Arg::with_name("json")
+            .long("json")
+            .requires("trace")
+            .help("flag to print the trace as JSON"))
         .arg(Arg::with_name("hex")
             .short("x")
             .long("hex")
@@ -26,12 +233,71 @@ fn main() {
         .arg(Arg::with_name("input")
             .help("input value to encrypt or decrypt"))
         .get_matches();
-    if let Some(_) = matches.subcommand_matches("keygen") {
-        let key = keygen();
-        let mut vec = Vec::with_capacity(8);
-        push_64bit(&mut vec, key);
-        let key = binary_to_hex_string(vec);
-        println!("generated key: {}", key);
+    if let Some(matches) = matches.subcommand_matches("keygen") {
+        let key = if matches.is_present("desx") {
+            Key::DesX(desx::keygen())
+        } else if matches.is_present("double") {
+            Key::Double(double::keygen())
+        } else {
+            match matches.value_of("keys").unwrap() {
+                "2" => Key::Triple(triple::keygen(KeyingOption::Two)),
+                "3" => Key::Triple(triple::keygen(KeyingOption::One)),
+                _ => Key::Single(keygen()),
+            }
+        };
+        match matches.value_of("out") {
+            Some(path) => match key.to_key_file().write(path) {
+                Ok(()) => println!("generated {} key written to {}", key.algorithm(), path),
+                Err(e) => eprintln!("error in writing key file: {}", e),
+            },
+            None => println!("generated key: {}", key),
+        }
+    } else if let Some(matches) = matches.subcommand_matches("provenance") {
+        print_provenance(matches);
+    } else if let Some(matches) = matches.subcommand_matches("differential") {
+        print_differential(matches);
+    } else if let Some(matches) = matches.subcommand_matches("linear") {
+        print_linear(matches);
+    } else if let Some(matches) = matches.subcommand_matches("double") {
+        print_double(matches);
+    } else if let Some(matches) = matches.subcommand_matches("selftest") {
+        let full = matches.is_present("full");
+        if full {
+            println!("Monte Carlo: {} x {} blocks per direction", FULL_MONTE_CARLO_ROUNDS, MONTE_CARLO_CHAIN);
+        }
+        let mut passed = true;
+        for report in validation::run_all(full) {
+            println!("{}", report);
+            passed &= report.passed();
+        }
+        if !passed {
+            std::process::exit(1);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("bruteforce") {
+        run_bruteforce(matches);
+    } else if let Some(matches) = matches.subcommand_matches("fix-parity") {
+        let key = matches.value_of("key").unwrap();
+        if !key.is_ascii() || key.len() % 16 != 0 || key.is_empty() || key.len() > 48 {
+            eprintln!("error in parsing key: {}", ParseKeyError::InvalidKeyStringFormat);
+            return;
+        }
+        let mut fixed = String::with_capacity(key.len());
+        for i in 0..key.len() / 16 {
+            match DesKey::from_hex_ignoring_parity(&key[i * 16..(i + 1) * 16]) {
+                Ok(part) => {
+                    let part = part.with_odd_parity();
+                    if let Err(e) = part.check_strength() {
+                        eprintln!("warning: part {}: {}", i + 1, e);
+                    }
+                    fixed.push_str(&part.to_string());
+                }
+                Err(e) => {
+                    eprintln!("error in parsing key: {}", e);
+                    return;
+                }
+            }
+        }
+        println!("{}", fixed);
     } else {
         let input = matches.value_of("input").map(str::to_string).unwrap_or_else(|| {
             let mut s = String::new();
@@ -50,28 +316,451 @@ fn main() {
                 return;
             }
         };
-        match parse_key(matches.value_of("key").unwrap()) {
+        let key = match matches.value_of("key") {
+            Some(key) => key,
+            None => {
+                eprintln!("argument 'key' is required");
+                return;
+            }
+        };
+        let flagged = if matches.is_present("triple") {
+            Some(Key::TRIPLE)
+        } else if matches.is_present("desx") {
+            Some(Key::DESX)
+        } else if matches.is_present("double") {
+            Some(Key::DOUBLE)
+        } else {
+            None
+        };
+        let file = match path_of_argument(key).map(KeyFile::read) {
+            Some(Ok(file)) => Some(file),
+            Some(Err(e)) => {
+                eprintln!("error in reading key file: {}", e);
+                return;
+            }
+            None => None,
+        };
+        // A key file records its cipher, so no flag is needed, but one given must agree with it.
+        let algorithm = match &file {
+            Some(file) => match Key::ALGORITHMS.iter().find(|&&algorithm| algorithm == file.algorithm) {
+                Some(&algorithm) => algorithm,
+                None => {
+                    eprintln!("error in reading key file: key file holds a {} key, not a DES one", file.algorithm);
+                    return;
+                }
+            },
+            None => flagged.unwrap_or(Key::SINGLE),
+        };
+        if let (Some(file), Some(flagged)) = (&file, flagged) {
+            if let Err(e) = file.check_algorithm(flagged) {
+                eprintln!("error in reading key file: {}", e);
+                return;
+            }
+        }
+        let key = file.as_ref().map(KeyFile::key_hex).unwrap_or_else(|| key.to_string());
+        let key = match algorithm {
+            Key::TRIPLE => key.parse().map(Key::Triple),
+            Key::DESX => key.parse().map(Key::DesX),
+            Key::DOUBLE => key.parse().map(Key::Double),
+            _ => key.parse().map(Key::Single),
+        };
+        match key {
             Ok(key) => {
-                if matches.is_present("decrypt") {
-                    let result = encrypt(&input, &key);
-                    if !matches.is_present("hex") {
-                        let string = String::from_utf8(result).expect("failed to encode to utf8 decrypt result.");
-                        println!("{}", string);
-                    } else {
-                        println!("{}", binary_to_hex_string(result));
+                if let Some(file) = file {
+                    if let Err(e) = file.verify(algorithm, &key.encrypt(&[0; 8])) {
+                        eprintln!("error in reading key file: {}", e);
+                        return;
                     }
-                } else {
-                    let result = decrypt(&input, &key);
-                    println!("{}", binary_to_hex_string(result));
                 }
+                key.warn_weak();
+                if matches.is_present("trace") {
+                    let direction = if matches.is_present("decrypt") { Direction::Decrypt } else { Direction::Encrypt };
+                    match key {
+                        Key::Single(key) => print_trace(&key, &input, direction, matches.is_present("json")),
+                        _ => {
+                            eprintln!("--trace is only available for single DES");
+                            return;
+                        }
+                    }
+                }
+                println!("{}", crypt(&key, &input, matches.is_present("decrypt"), matches.is_present("hex")));
             }
             Err(e) => {
-                eprintln!("error in parsing key: {:?}", e);
+                eprintln!("error in parsing key: {}", e);
+            }
+        }
+    }
+}
+
+fn print_provenance(matches: &ArgMatches) {
+    if let Some(bit) = matches.value_of("key-bit") {
+        match bit.parse::<u8>() {
+            Ok(bit @ 1..=64) => {
+                let uses = provenance::key_bit_uses(bit);
+                println!("key bit {} is used in {} rounds", bit, uses.len());
+                for u in uses {
+                    println!("  round {:>2}: K{} position {:>2} (S{})", u.round, u.round, u.position, u.sbox);
+                }
+            }
+            _ => eprintln!("argument 'key-bit' should be a number from 1 to 64"),
+        }
+    } else if let Some(bit) = matches.value_of("r-bit") {
+        match bit.parse::<u8>() {
+            Ok(bit @ 1..=32) => {
+                let sboxes: Vec<_> = provenance::sboxes_reading(bit).iter().map(|s| format!("S{}", s)).collect();
+                println!("R bit {} is read by {}", bit, sboxes.join(" and "));
+            }
+            _ => eprintln!("argument 'r-bit' should be a number from 1 to 32"),
+        }
+    } else {
+        match matches.value_of("view").unwrap() {
+            "usage" => print!("{}", provenance::key_bit_usage_matrix()),
+            "e" => print!("{}", provenance::e_matrix()),
+            "permutations" => print!("{}", provenance::permutation_matrix()),
+            _ => print!("{}", provenance::sub_key_matrix()),
+        }
+    }
+}
+
+fn print_differential(matches: &ArgMatches) {
+    match matches.value_of("view").unwrap() {
+        "ddt" => match matches.value_of("sbox").unwrap().parse::<usize>() {
+            Ok(sbox @ 1..=8) => print!("{}", differential::ddt_matrix(&differential::difference_distribution_table(sbox - 1))),
+            _ => eprintln!("argument 'sbox' should be a number from 1 to 8"),
+        },
+        "attack" => {
+            let key = match matches.value_of("key").map(str::parse::<DesKey>) {
+                Some(Ok(key)) => key,
+                Some(Err(e)) => {
+                    eprintln!("error in parsing key: {}", e);
+                    return;
+                }
+                None => keygen(),
+            };
+            let pairs = match matches.value_of("pairs").map(str::parse::<usize>) {
+                Some(Ok(pairs)) if pairs > 0 => Some(pairs),
+                Some(_) => {
+                    eprintln!("argument 'pairs' should be a positive number");
+                    return;
+                }
+                None => None,
+            };
+            let rounds = matches.value_of("rounds").unwrap().parse().unwrap();
+            let mut oracle = Oracle::new(&key, rounds);
+            let recovery = if rounds == 4 {
+                differential::attack_four_rounds(&mut oracle, pairs.unwrap_or(64))
+            } else {
+                differential::attack_six_rounds(&mut oracle, pairs.unwrap_or(300))
+            };
+            println!("{}", recovery);
+            println!("oracle key: {}", key);
+            println!("chosen plaintexts: {}", oracle.queries());
+        }
+        _ => {
+            let tables = DifferenceTables::new();
+            println!("two-round iterative characteristics (dx, 0) -> (0, dx)");
+            for (dx, probability) in tables.iterative_characteristics(8) {
+                println!("  {:08x}  1/{:.1}", dx, 1.0 / probability);
+            }
+            for &input in SIX_ROUND_CHARACTERISTICS.iter() {
+                println!();
+                println!("{}", tables.greedy_characteristic(input, 3));
+            }
+        }
+    }
+}
+
+fn print_linear(matches: &ArgMatches) {
+    let rounds: usize = matches.value_of("rounds").unwrap().parse().unwrap();
+    let algorithm = if matches.value_of("algorithm").unwrap() == "1" { Algorithm::One } else { Algorithm::Two };
+    match matches.value_of("view").unwrap() {
+        "lat" => match matches.value_of("sbox").unwrap().parse::<usize>() {
+            Ok(sbox @ 1..=8) => print!("{}", linear::lat_matrix(&linear::linear_approximation_table(sbox - 1))),
+            _ => eprintln!("argument 'sbox' should be a number from 1 to 8"),
+        },
+        "trails" => {
+            for (i, trail) in LinearTables::new().best_trails(rounds).iter().enumerate() {
+                println!("=== {} rounds ===", i + 1);
+                println!("{}", trail);
+                println!();
+            }
+        }
+        view => {
+            let tables = LinearTables::new();
+            let trail = match algorithm {
+                Algorithm::One => tables.best_trails(rounds).pop().unwrap(),
+                Algorithm::Two => tables.best_trail_for_algorithm2(rounds - 1),
+            };
+            let samples = match matches.value_of("samples").map(str::parse::<usize>) {
+                Some(Ok(samples)) if samples > 0 => Some(samples),
+                Some(_) => {
+                    eprintln!("argument 'samples' should be a positive number");
+                    return;
+                }
+                None => None,
+            };
+            let bias_samples = |factor: f64| (factor / (trail.bias() * trail.bias())).ceil() as usize;
+            println!("{}", trail);
+            println!();
+            if view == "success" {
+                let trials = match matches.value_of("trials").unwrap().parse::<usize>() {
+                    Ok(trials) if trials > 0 => trials,
+                    _ => {
+                        eprintln!("argument 'trials' should be a positive number");
+                        return;
+                    }
+                };
+                let counts: Vec<_> = match samples {
+                    Some(samples) => vec![samples],
+                    None => [0.5, 1.0, 2.0, 4.0, 8.0, 16.0].iter().map(|&f| bias_samples(f)).collect(),
+                };
+                println!("   samples  bias^-2  success");
+                for samples in counts {
+                    let rate = linear::success_rate(&trail, algorithm, samples, trials);
+                    println!("{:>10}  {:>7.2}  {:>6.1}%", samples, samples as f64 * trail.bias() * trail.bias(), rate * 100.0);
+                }
+                return;
+            }
+            let key = match matches.value_of("key").map(str::parse::<DesKey>) {
+                Some(Ok(key)) => key,
+                Some(Err(e)) => {
+                    eprintln!("error in parsing key: {}", e);
+                    return;
+                }
+                None => keygen(),
+            };
+            let samples = linear::known_plaintexts(&key, rounds, samples.unwrap_or_else(|| bias_samples(8.0)));
+            match algorithm {
+                Algorithm::One => {
+                    let parity = linear::algorithm1(&trail, &samples);
+                    println!("{}-round DES, {} known plaintexts", rounds, samples.len());
+                    println!("{} = {}", linear::key_bits_string(trail.key_bits()), parity as u8);
+                    println!("correct: {}", parity == trail.key_parity(&key));
+                }
+                Algorithm::Two => {
+                    let result = linear::algorithm2(&trail, &samples);
+                    println!("{}", result);
+                    println!("correct: {}", result.is_correct(&key));
+                }
             }
+            println!("key: {}", key);
         }
     }
 }
 
+fn print_double(matches: &ArgMatches) {
+    let bits = match matches.value_of("free-bits").unwrap().parse::<u32>() {
+        Ok(bits @ 1..=double::MAX_FREE_BITS) => bits,
+        _ => {
+            eprintln!("argument 'free-bits' should be a number from 1 to {}", double::MAX_FREE_BITS);
+            return;
+        }
+    };
+    let key = match matches.value_of("key").map(str::parse::<DoubleDesKey>) {
+        Some(Ok(key)) => key,
+        Some(Err(e)) => {
+            eprintln!("error in parsing key: {}", e);
+            return;
+        }
+        None => double::keygen(),
+    };
+    let count: usize = matches.value_of("pairs").unwrap().parse().unwrap();
+    let cipher = DoubleDes::new(&key);
+    let pairs: Vec<_> = (0..count).map(|_| {
+        let plaintext = rand::random::<u64>();
+        (plaintext, cipher.encrypt_u64(plaintext))
+    }).collect();
+    for (plaintext, ciphertext) in &pairs {
+        println!("known pair: {:016x} -> {:016x}", plaintext, ciphertext);
+    }
+    let first = KeySpace::lowest(key.k1, bits);
+    let second = KeySpace::lowest(key.k2, bits);
+    println!("unknown key bits: {:016x} of each key", first.mask());
+    println!("expected cost: table of {} KiB, about 2^{} DES operations", (16u64 << bits) / 1024, bits + 1);
+    let attack = double::meet_in_the_middle(&pairs, &first, &second);
+    println!("{}", attack);
+    println!("actual key: K1 = {}, K2 = {}", key.k1, key.k2);
+}
+
+fn run_bruteforce(matches: &ArgMatches) {
+    let block = |name: &str| match matches.value_of(name).map(|value| (value.len(), u64::from_str_radix(value, 16))) {
+        Some((16, Ok(block))) => Ok(Some(block)),
+        Some(_) => Err(format!("argument '{}' should be 16 hexadecimal digits", name)),
+        None => Ok(None),
+    };
+    let (plaintext, ciphertext, complement, mask) = match (block("plaintext"), block("ciphertext"), block("complement"), block("mask")) {
+        (Ok(p), Ok(c), Ok(complement), Ok(mask)) => (p, c, complement, mask),
+        (Err(e), ..) | (_, Err(e), ..) | (_, _, Err(e), _) | (.., Err(e)) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let key = match matches.value_of("key").map(DesKey::from_hex_ignoring_parity) {
+        Some(Ok(key)) => Some(key.with_odd_parity()),
+        Some(Err(e)) => {
+            eprintln!("error in parsing key: {}", e);
+            return;
+        }
+        None => None,
+    };
+    let threads = match matches.value_of("threads").map(str::parse::<usize>) {
+        Some(Ok(threads)) if threads > 0 => Some(threads),
+        Some(_) => {
+            eprintln!("argument 'threads' should be a positive number");
+            return;
+        }
+        None => None,
+    };
+    let plaintext = plaintext.unwrap_or_else(rand::random);
+    let (base, ciphertext, complement) = match ciphertext {
+        Some(ciphertext) => (key.unwrap_or_else(|| DesKey::from(0)), ciphertext, complement),
+        None => {
+            let key = key.unwrap_or_else(keygen);
+            let des = FastDes::new(&key);
+            println!("secret key: {}", key);
+            (key, des.encrypt_u64(plaintext), Some(des.encrypt_u64(!plaintext)))
+        }
+    };
+    let space = match mask {
+        Some(mask) => KeySpace::from_mask(base, mask),
+        None => match matches.value_of("bits").unwrap_or("24").parse::<u32>() {
+            Ok(bits @ 1..=56) => KeySpace::lowest(base, bits),
+            _ => {
+                eprintln!("argument 'bits' should be a number from 1 to 56");
+                return;
+            }
+        },
+    };
+    let mut search = BruteForce::new(plaintext, ciphertext, space);
+    if let (Some(complement), false) = (complement, matches.is_present("no-complement")) {
+        search = search.with_complement(complement);
+        println!("known pairs: {:016x} -> {:016x}, {:016x} -> {:016x}", plaintext, ciphertext, !plaintext, complement);
+    } else {
+        println!("known pair: {:016x} -> {:016x}", plaintext, ciphertext);
+    }
+    if let Some(threads) = threads {
+        search = search.threads(threads);
+    }
+    println!("unknown key bits: {:016x} ({} bits)", search.space().mask(), search.space().bits());
+    println!("keys covered: {}, encryptions: {}", search.keys(), search.encryptions());
+    let outcome = search.run_with_progress(Duration::from_secs(1), |progress| {
+        eprint!("\r{}", progress);
+        std::io::stderr().flush().ok();
+    });
+    eprintln!();
+    println!("{}", outcome);
+}
+
+fn print_trace(key: &DesKey, input: &[u8], direction: Direction, json: bool) {
+    let traces: Vec<_> = input.chunks(8)
+        .map(|chunk| {
+            let mut block = [0; 8];
+            block[..chunk.len()].copy_from_slice(chunk);
+            trace_block(key, block, direction)
+        })
+        .collect();
+    if json {
+        let traces: Vec<_> = traces.iter().map(Trace::to_json).collect();
+        println!("[{}]", traces.join(","));
+    } else {
+        for (i, trace) in traces.iter().enumerate() {
+            println!("=== block {} ===", i + 1);
+            println!("{}", trace);
+            println!();
+        }
+    }
+}
+
+enum Key {
+    Single(DesKey),
+    Triple(TripleDesKey),
+    DesX(DesXKey),
+    Double(DoubleDesKey),
+}
+
+impl Key {
+    const SINGLE: &'static str = "DES";
+    const TRIPLE: &'static str = "TDEA";
+    const DESX: &'static str = "DESX";
+    const DOUBLE: &'static str = "2DES";
+    const ALGORITHMS: [&'static str; 4] = [Key::SINGLE, Key::TRIPLE, Key::DESX, Key::DOUBLE];
+
+    /// The name of the cipher, as written in key files.
+    fn algorithm(&self) -> &'static str {
+        match self {
+            Key::Single(_) => Key::SINGLE,
+            Key::Triple(_) => Key::TRIPLE,
+            Key::DesX(_) => Key::DESX,
+            Key::Double(_) => Key::DOUBLE,
+        }
+    }
+
+    fn to_key_file(&self) -> KeyFile {
+        let bytes = hex_string_to_binary(&self.to_string()).unwrap();
+        KeyFile::new(self.algorithm(), &bytes, &self.encrypt(&[0; 8]))
+    }
+
+    fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        match self {
+            Key::Single(key) => encrypt(input, key),
+            Key::Triple(key) => triple::encrypt(input, key),
+            Key::DesX(key) => desx::encrypt(input, key),
+            Key::Double(key) => double::encrypt(input, key),
+        }
+    }
+
+    fn warn_weak(&self) {
+        let parts = match self {
+            Key::Single(key) => vec![*key],
+            Key::Triple(key) => vec![key.k1, key.k2, key.k3],
+            Key::DesX(key) => vec![key.k],
+            Key::Double(key) => vec![key.k1, key.k2],
+        };
+        for (i, part) in parts.iter().enumerate() {
+            if let Err(e) = part.check_strength() {
+                if parts.len() == 1 {
+                    eprintln!("warning: {}", e);
+                } else {
+                    eprintln!("warning: K{}: {}", i + 1, e);
+                }
+            }
+        }
+    }
+
+    fn decrypt(&self, input: &[u8]) -> Vec<u8> {
+        match self {
+            Key::Single(key) => decrypt(input, key),
+            Key::Triple(key) => triple::decrypt(input, key),
+            Key::DesX(key) => desx::decrypt(input, key),
+            Key::Double(key) => double::decrypt(input, key),
+        }
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Key::Single(key) => write!(f, "{}", key),
+            Key::Triple(key) => write!(f, "{}", key),
+            Key::DesX(key) => write!(f, "{}", key),
+            Key::Double(key) => write!(f, "{}", key),
+        }
+    }
+}
+
+/// Encrypts `input` to hex digits, or decrypts it to text, or to hex digits with `hex`.
+fn crypt(key: &Key, input: &[u8], decrypt: bool, hex: bool) -> String {
+    if decrypt {
+        let result = key.decrypt(input);
+        if !hex {
+            String::from_utf8(result).expect("failed to encode to utf8 decrypt result.")
+        } else {
+            binary_to_hex_string(result)
+        }
+    } else {
+        binary_to_hex_string(key.encrypt(input))
+    }
+}
 
 #[derive(Debug, PartialEq)]
 enum InputToBinaryError {
@@ -86,9 +775,9 @@ fn hex_string_to_binary(value: &str) -> Result<Vec<u8>, InputToBinaryError> {
     let mut result = Vec::with_capacity(value.len() >> 1);
     let mut chars = value.chars();
     let char_to_int = |c| match c {
-        c @ '0'..='9' => Some(c as u8 - '0' as u8),
-        c @ 'a'..='f' => Some(c as u8 - 'a' as u8 + 10),
-        c @ 'A'..='F' => Some(c as u8 - 'A' as u8 + 10),
+        c @ '0'..='9' => Some(c as u8 - b'0'),
+        c @ 'a'..='f' => Some(c as u8 - b'a' + 10),
+        c @ 'A'..='F' => Some(c as u8 - b'A' + 10),
         _ => None
     };
     while let Some(c) = chars.next() {
@@ -123,7 +812,7 @@ mod test {
 
     #[test]
     fn test_utf8_to_binary() {
-        assert_eq!(utf8_to_binary("abc"), Ok(vec!['a' as u8, 'b' as u8, 'c' as u8]));
+        assert_eq!(utf8_to_binary("abc"), Ok(vec![b'a', b'b', b'c']));
     }
 
     #[test]
@@ -133,10 +822,23 @@ mod test {
         assert!(hex_string_to_binary("0123456789abcdefg").is_err());
     }
 
+    #[test]
+    fn test_crypt() {
+        let key = Key::Single("133457799bbcdff1".parse().unwrap());
+        let plaintext = hex_string_to_binary("0123456789abcdef").unwrap();
+        assert_eq!(crypt(&key, &plaintext, false, true), "85e813540f0ab405");
+        assert_eq!(crypt(&key, &hex_string_to_binary("85e813540f0ab405").unwrap(), true, true), "0123456789abcdef");
+
+        let key = Key::Triple("0123456789abcdef23456789abcdef01".parse().unwrap());
+        let ciphertext = crypt(&key, &utf8_to_binary("plaintext!").unwrap(), false, false);
+        assert_eq!(ciphertext.len(), 32);
+        assert_eq!(crypt(&key, &hex_string_to_binary(&ciphertext).unwrap(), true, false), "plaintext!\0\0\0\0\0\0");
+    }
+
     #[test]
     fn test_binary_to_hex_string() {
         assert_eq!(binary_to_hex_string(vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]), String::from("0123456789abcdef"));
-        assert_eq!(binary_to_hex_string((0..=255).into_iter().collect()), String::from("000102030405060708090a0b0c0d0e0f\
+        assert_eq!(binary_to_hex_string((0..=255).collect()), String::from("000102030405060708090a0b0c0d0e0f\
 101112131415161718191a1b1c1d1e1f\
 202122232425262728292a2b2c2d2e2f\
 303132333435363738393a3b3c3d3e3f\
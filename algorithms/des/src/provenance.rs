@@ -0,0 +1,154 @@
+//! Where the bits of DES come from.
+//!
+//! The permutations and the key schedule in the crate root are generic over the element type, so
+//! running them on bit numbers instead of bit values shows which input bit lands in each output
+//! position. Bit numbers are 1-based and most significant bit first, as in FIPS 46-3.
+
+use std::fmt::Write;
+
+use crate::{e, generate_sub_key, ip, ip_inverse, p};
+
+#[cfg(test)]
+mod test;
+
+fn labels<const N: usize>() -> [u8; N] {
+    let mut result = [0; N];
+    for (i, label) in result.iter_mut().enumerate() {
+        *label = i as u8 + 1;
+    }
+    result
+}
+
+/// `sub_key_sources()[round][position]` is the key bit (1–64) used at `position` of the round key
+/// of round `round + 1`.
+pub fn sub_key_sources() -> [[u8; 48]; 16] {
+    generate_sub_key(&labels::<64>())
+}
+
+/// Key bits that no round key uses: the eight parity bits.
+pub fn unused_key_bits() -> Vec<u8> {
+    let schedule = sub_key_sources();
+    (1..=64).filter(|bit| schedule.iter().all(|k| !k.contains(bit))).collect()
+}
+
+/// A place where a key bit is used: 1-based round, 1-based position in the round key and the
+/// 1-based S-box whose input it is XORed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBitUse {
+    pub round: usize,
+    pub position: usize,
+    pub sbox: usize,
+}
+
+/// Every use of key bit `bit` (1–64), in round order.
+pub fn key_bit_uses(bit: u8) -> Vec<KeyBitUse> {
+    let mut result = Vec::new();
+    for (round, sub_key) in sub_key_sources().iter().enumerate() {
+        if let Some(position) = sub_key.iter().position(|&b| b == bit) {
+            result.push(KeyBitUse { round: round + 1, position: position + 1, sbox: position / 6 + 1 });
+        }
+    }
+    result
+}
+
+/// `sbox_sources()[i]` are the bits of R (1–32) which E feeds into S-box `i + 1`.
+pub fn sbox_sources() -> [[u8; 6]; 8] {
+    let expanded = e(&labels::<32>());
+    let mut result = [[0; 6]; 8];
+    for (i, sources) in result.iter_mut().enumerate() {
+        sources.copy_from_slice(&expanded[i * 6..(i + 1) * 6]);
+    }
+    result
+}
+
+/// The S-boxes (1–8) reading bit `bit` (1–32) of R. Every bit reaches one or two S-boxes.
+pub fn sboxes_reading(bit: u8) -> Vec<usize> {
+    sbox_sources().iter().enumerate().filter(|(_, sources)| sources.contains(&bit)).map(|(i, _)| i + 1).collect()
+}
+
+/// `ip_sources()[i]` is the input bit which IP moves to position `i + 1`.
+pub fn ip_sources() -> [u8; 64] {
+    ip(&labels::<64>())
+}
+
+/// `ip_inverse_sources()[i]` is the input bit which IP⁻¹ moves to position `i + 1`.
+pub fn ip_inverse_sources() -> [u8; 64] {
+    ip_inverse(&labels::<64>())
+}
+
+/// `p_sources()[i]` is the S-box output bit (1–32) which P moves to position `i + 1`.
+pub fn p_sources() -> [u8; 32] {
+    p(&labels::<32>())
+}
+
+fn matrix(rows: &[(String, &[u8])], group: usize) -> String {
+    let mut result = String::new();
+    for (name, row) in rows {
+        write!(result, "{:>6} ", name).unwrap();
+        for (i, value) in row.iter().enumerate() {
+            if i != 0 && i % group == 0 {
+                result.push_str(" |");
+            }
+            write!(result, " {:>2}", value).unwrap();
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// The key bit in every position of every round key, one row per round, grouped by S-box.
+pub fn sub_key_matrix() -> String {
+    let schedule = sub_key_sources();
+    let rows: Vec<_> = schedule.iter().enumerate().map(|(i, k)| (format!("K{}", i + 1), &k[..])).collect();
+    matrix(&rows, 6)
+}
+
+/// One row per key bit and one column per round: the S-box the bit is XORed into, or `.` when
+/// the round does not use it.
+pub fn key_bit_usage_matrix() -> String {
+    let schedule = sub_key_sources();
+    let mut result = String::from("   bit ");
+    for round in 1..=16 {
+        write!(result, " {:>2}", round).unwrap();
+    }
+    result.push_str("  rounds\n");
+    for bit in 1..=64u8 {
+        write!(result, "{:>6} ", bit).unwrap();
+        let mut count = 0;
+        for sub_key in schedule.iter() {
+            match sub_key.iter().position(|&b| b == bit) {
+                Some(position) => {
+                    count += 1;
+                    write!(result, " S{}", position / 6 + 1).unwrap();
+                }
+                None => result.push_str("  ."),
+            }
+        }
+        writeln!(result, "  {:>6}", count).unwrap();
+    }
+    result
+}
+
+/// The R bits feeding each S-box through E.
+pub fn e_matrix() -> String {
+    let sources = sbox_sources();
+    let rows: Vec<_> = sources.iter().enumerate().map(|(i, s)| (format!("S{}", i + 1), &s[..])).collect();
+    matrix(&rows, 6)
+}
+
+/// The source bit of every output position of IP, IP⁻¹ and P, in rows of eight.
+pub fn permutation_matrix() -> String {
+    let ip = ip_sources();
+    let ip_inverse = ip_inverse_sources();
+    let p = p_sources();
+    let mut result = String::from("IP\n");
+    let rows: Vec<_> = ip.chunks(8).enumerate().map(|(i, row)| (format!("{}-{}", i * 8 + 1, i * 8 + 8), row)).collect();
+    result.push_str(&matrix(&rows, 8));
+    result.push_str("IP^-1\n");
+    let rows: Vec<_> = ip_inverse.chunks(8).enumerate().map(|(i, row)| (format!("{}-{}", i * 8 + 1, i * 8 + 8), row)).collect();
+    result.push_str(&matrix(&rows, 8));
+    result.push_str("P\n");
+    let rows: Vec<_> = p.chunks(8).enumerate().map(|(i, row)| (format!("{}-{}", i * 8 + 1, i * 8 + 8), row)).collect();
+    result.push_str(&matrix(&rows, 8));
+    result
+}
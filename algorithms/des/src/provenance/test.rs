@@ -0,0 +1,69 @@
+use crate::{DesKey, PC1};
+
+use super::*;
+
+#[test]
+fn test_sub_key_sources() {
+    let schedule = sub_key_sources();
+    assert_eq!(schedule[0][..6], [10, 51, 34, 60, 49, 17]);
+    assert_eq!(unused_key_bits(), vec![8, 16, 24, 32, 40, 48, 56, 64]);
+    let uses: usize = (1..=64).map(|bit| key_bit_uses(bit).len()).sum();
+    assert_eq!(uses, 16 * 48);
+    for bit in (1..=64).filter(|bit| bit % 8 != 0) {
+        let count = key_bit_uses(bit).len();
+        assert!((12..=15).contains(&count), "key bit {} used in {} rounds", bit, count);
+    }
+}
+
+#[test]
+fn test_sub_key_sources_match_values() {
+    // Setting a single key bit sets exactly the round key positions it is reported in.
+    let schedule = sub_key_sources();
+    for bit in 1..=64u8 {
+        let key = DesKey::from(1u64 << (64 - bit as u32));
+        let sub_key = crate::generate_sub_key(&key.bits());
+        for (round, k) in sub_key.iter().enumerate() {
+            for (position, &b) in k.iter().enumerate() {
+                assert_eq!(b, schedule[round][position] == bit);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_key_bit_uses() {
+    let uses = key_bit_uses(10);
+    assert_eq!(uses[0], KeyBitUse { round: 1, position: 1, sbox: 1 });
+    assert!(uses.windows(2).all(|w| w[0].round < w[1].round));
+    assert!(key_bit_uses(8).is_empty());
+    // Bits dropped by PC1 are exactly the parity bits.
+    assert!(PC1.iter().all(|&b| b % 8 != 0));
+}
+
+#[test]
+fn test_e_and_permutations() {
+    assert_eq!(sbox_sources()[0], [32, 1, 2, 3, 4, 5]);
+    assert_eq!(sbox_sources()[7], [28, 29, 30, 31, 32, 1]);
+    assert_eq!(sboxes_reading(1), vec![1, 8]);
+    assert_eq!(sboxes_reading(2), vec![1]);
+    assert_eq!(sboxes_reading(4), vec![1, 2]);
+    assert_eq!(ip_sources()[0], 58);
+    assert_eq!(ip_inverse_sources()[0], 40);
+    assert_eq!(p_sources()[0], 16);
+    let ip = ip_sources();
+    let ip_inverse = ip_inverse_sources();
+    for i in 0..64 {
+        assert_eq!(ip[ip_inverse[i] as usize - 1] as usize, i + 1);
+    }
+}
+
+#[test]
+fn test_matrices() {
+    assert!(sub_key_matrix().starts_with("    K1  10 51 34 60 49 17 |"));
+    assert_eq!(sub_key_matrix().lines().count(), 16);
+    let usage = key_bit_usage_matrix();
+    assert_eq!(usage.lines().count(), 65);
+    assert!(usage.lines().nth(8).unwrap().trim_end().ends_with(" 0"));
+    assert_eq!(e_matrix().lines().next().unwrap(), "    S1  32  1  2  3  4  5");
+    assert!(permutation_matrix().starts_with("IP\n   1-8  58 50 42 34 26 18 10  2\n"));
+}
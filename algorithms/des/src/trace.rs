@@ -0,0 +1,207 @@
+//! Records every intermediate value of one DES block operation.
+//!
+//! The trace is computed with the same bit-array functions as [`crate::Des`], step by step as
+//! FIPS 46-3 describes them, so the numbers can be compared with a worked example by hand. Every
+//! value is stored right-aligned in an integer of the stated width, most significant bit first as
+//! in the standard's tables.
+
+use std::fmt::{self, Write};
+
+use crate::{e, generate_sub_key, ip, ip_inverse, p, pick_64bit_from_slice, s, to_block, DesKey};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+/// The values computed in one of the sixteen rounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round {
+    /// 1-based round number.
+    pub number: usize,
+    /// Index into [`Trace::sub_keys`] of the round key used; rounds run backwards when decrypting.
+    pub sub_key_index: usize,
+    /// L and R entering the round (32 bits each).
+    pub l: u32,
+    pub r: u32,
+    /// E(R) (48 bits).
+    pub expanded: u64,
+    /// E(R) ⊕ K (48 bits).
+    pub xored: u64,
+    /// The 6-bit input and 4-bit output of each S-box.
+    pub sbox_inputs: [u8; 8],
+    pub sbox_outputs: [u8; 8],
+    /// P of the concatenated S-box outputs, i.e. f(R, K) (32 bits).
+    pub p_output: u32,
+    /// L and R leaving the round.
+    pub next_l: u32,
+    pub next_r: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub direction: Direction,
+    pub input: u64,
+    /// The sixteen 48-bit round keys in the order of the key schedule.
+    pub sub_keys: [u64; 16],
+    /// Output of the initial permutation.
+    pub ip_output: u64,
+    pub rounds: Vec<Round>,
+    /// R16 ‖ L16, the input to IP⁻¹.
+    pub preoutput: u64,
+    /// Output of IP⁻¹, the result of the block operation.
+    pub output: u64,
+}
+
+fn value(bits: &[bool]) -> u64 {
+    bits.iter().fold(0, |acc, &b| acc << 1 | b as u64)
+}
+
+/// Encrypts or decrypts `block` with `key`, recording every step.
+pub fn trace_block(key: &DesKey, block: [u8; 8], direction: Direction) -> Trace {
+    let sub_key = generate_sub_key(&key.bits());
+    let input = pick_64bit_from_slice(&block);
+    let m = ip(&input);
+    let mut l = [false; 32];
+    let mut r = [false; 32];
+    l.copy_from_slice(&m[..32]);
+    r.copy_from_slice(&m[32..]);
+    let mut rounds = Vec::with_capacity(16);
+    for number in 1..=16 {
+        let sub_key_index = match direction {
+            Direction::Encrypt => number - 1,
+            Direction::Decrypt => 16 - number,
+        };
+        let expanded = e(&r);
+        let mut xored = [false; 48];
+        for (x, (a, b)) in xored.iter_mut().zip(expanded.iter().zip(sub_key[sub_key_index].iter())) {
+            *x = a ^ b;
+        }
+        let mut sbox_output = [false; 32];
+        let mut sbox_inputs = [0; 8];
+        let mut sbox_outputs = [0; 8];
+        for i in 0..8 {
+            s(i, &xored[i * 6..(i + 1) * 6], &mut sbox_output[i * 4..(i + 1) * 4]);
+            sbox_inputs[i] = value(&xored[i * 6..(i + 1) * 6]) as u8;
+            sbox_outputs[i] = value(&sbox_output[i * 4..(i + 1) * 4]) as u8;
+        }
+        let f = p(&sbox_output);
+        let mut next_r = [false; 32];
+        for (x, (a, b)) in next_r.iter_mut().zip(l.iter().zip(f.iter())) {
+            *x = a ^ b;
+        }
+        rounds.push(Round {
+            number,
+            sub_key_index,
+            l: value(&l) as u32,
+            r: value(&r) as u32,
+            expanded: value(&expanded),
+            xored: value(&xored),
+            sbox_inputs,
+            sbox_outputs,
+            p_output: value(&f) as u32,
+            next_l: value(&r) as u32,
+            next_r: value(&next_r) as u32,
+        });
+        l = r;
+        r = next_r;
+    }
+    let mut preoutput = [false; 64];
+    preoutput[..32].copy_from_slice(&r);
+    preoutput[32..].copy_from_slice(&l);
+    let output = ip_inverse(&preoutput);
+    let mut sub_keys = [0; 16];
+    for (k, bits) in sub_keys.iter_mut().zip(sub_key.iter()) {
+        *k = value(bits);
+    }
+    Trace {
+        direction,
+        input: u64::from_be_bytes(block),
+        sub_keys,
+        ip_output: value(&m),
+        rounds,
+        preoutput: value(&preoutput),
+        output: u64::from_be_bytes(to_block(output)),
+    }
+}
+
+/// `width` low bits of `value` in binary, split into groups of `group` bits.
+fn binary(value: u64, width: usize, group: usize) -> String {
+    let mut result = String::with_capacity(width + width / group);
+    for i in (0..width).rev() {
+        result.push(if value >> i & 1 != 0 { '1' } else { '0' });
+        if i != 0 && i % group == 0 {
+            result.push(' ');
+        }
+    }
+    result
+}
+
+impl Trace {
+    /// The trace as a JSON object. Bit strings are written as hexadecimal strings, S-box values as
+    /// numbers.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        let direction = match self.direction {
+            Direction::Encrypt => "encrypt",
+            Direction::Decrypt => "decrypt",
+        };
+        write!(json, "{{\"direction\":\"{}\",\"input\":\"{:016x}\",\"sub_keys\":[", direction, self.input).unwrap();
+        for (i, k) in self.sub_keys.iter().enumerate() {
+            if i != 0 { json.push(','); }
+            write!(json, "\"{:012x}\"", k).unwrap();
+        }
+        write!(json, "],\"ip_output\":\"{:016x}\",\"rounds\":[", self.ip_output).unwrap();
+        for (i, round) in self.rounds.iter().enumerate() {
+            if i != 0 { json.push(','); }
+            write!(json, "{{\"round\":{},\"sub_key\":{},\"l\":\"{:08x}\",\"r\":\"{:08x}\",\"e\":\"{:012x}\",\"xor\":\"{:012x}\",\"sbox_inputs\":{:?},\"sbox_outputs\":{:?},\"p\":\"{:08x}\",\"next_l\":\"{:08x}\",\"next_r\":\"{:08x}\"}}",
+                   round.number, round.sub_key_index + 1, round.l, round.r, round.expanded, round.xored,
+                   round.sbox_inputs, round.sbox_outputs, round.p_output, round.next_l, round.next_r).unwrap();
+        }
+        write!(json, "],\"preoutput\":\"{:016x}\",\"output\":\"{:016x}\"}}", self.preoutput, self.output).unwrap();
+        json
+    }
+}
+
+/// Human-readable tables in the binary grouping used by textbook walkthroughs.
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "input     {:016x}  {}", self.input, binary(self.input, 64, 8))?;
+        writeln!(f)?;
+        writeln!(f, "sub keys")?;
+        for (i, k) in self.sub_keys.iter().enumerate() {
+            writeln!(f, "  K{:<2}  {:012x}  {}", i + 1, k, binary(*k, 48, 6))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "IP        {:016x}  {}", self.ip_output, binary(self.ip_output, 64, 4))?;
+        for round in &self.rounds {
+            writeln!(f)?;
+            writeln!(f, "round {} (K{})", round.number, round.sub_key_index + 1)?;
+            writeln!(f, "  L       {:08x}  {}", round.l, binary(round.l as u64, 32, 4))?;
+            writeln!(f, "  R       {:08x}  {}", round.r, binary(round.r as u64, 32, 4))?;
+            writeln!(f, "  E(R)    {:012x}  {}", round.expanded, binary(round.expanded, 48, 6))?;
+            writeln!(f, "  K       {:012x}  {}", self.sub_keys[round.sub_key_index], binary(self.sub_keys[round.sub_key_index], 48, 6))?;
+            writeln!(f, "  E(R)^K  {:012x}  {}", round.xored, binary(round.xored, 48, 6))?;
+            write!(f, "  S in   ")?;
+            for x in &round.sbox_inputs {
+                write!(f, " {}", binary(*x as u64, 6, 6))?;
+            }
+            writeln!(f)?;
+            write!(f, "  S out  ")?;
+            for x in &round.sbox_outputs {
+                write!(f, " {:>6}", binary(*x as u64, 4, 4))?;
+            }
+            writeln!(f)?;
+            writeln!(f, "  P       {:08x}  {}", round.p_output, binary(round.p_output as u64, 32, 4))?;
+            writeln!(f, "  L'      {:08x}  {}", round.next_l, binary(round.next_l as u64, 32, 4))?;
+            writeln!(f, "  R'      {:08x}  {}", round.next_r, binary(round.next_r as u64, 32, 4))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "R16L16    {:016x}  {}", self.preoutput, binary(self.preoutput, 64, 4))?;
+        write!(f, "IP^-1     {:016x}  {}", self.output, binary(self.output, 64, 8))
+    }
+}
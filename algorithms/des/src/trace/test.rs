@@ -0,0 +1,62 @@
+use crate::Des;
+
+use super::*;
+
+/// The worked example of J. Orlin Grabbe, "The DES Algorithm Illustrated".
+fn example() -> Trace {
+    trace_block(&DesKey::from(0x133457799bbcdff1), 0x0123456789abcdef_u64.to_be_bytes(), Direction::Encrypt)
+}
+
+#[test]
+fn test_worked_example() {
+    let trace = example();
+    assert_eq!(trace.sub_keys[0], 0b000110_110000_001011_101111_111111_000111_000001_110010);
+    assert_eq!(trace.sub_keys[15], 0b110010_110011_110110_001011_000011_100001_011111_110101);
+    assert_eq!(trace.ip_output, 0xcc00ccfff0aaf0aa);
+    let first = &trace.rounds[0];
+    assert_eq!((first.l, first.r), (0xcc00ccff, 0xf0aaf0aa));
+    assert_eq!(first.expanded, 0b011110_100001_010101_010101_011110_100001_010101_010101);
+    assert_eq!(first.xored, 0b011000_010001_011110_111010_100001_100110_010100_100111);
+    assert_eq!(first.sbox_outputs, [0b0101, 0b1100, 0b1000, 0b0010, 0b1011, 0b0101, 0b1001, 0b0111]);
+    assert_eq!(first.p_output, 0b0010_0011_0100_1010_1010_1001_1011_1011);
+    assert_eq!(first.next_r, 0b1110_1111_0100_1010_0110_0101_0100_0100);
+    assert_eq!(trace.preoutput, 0x0a4cd99543423234);
+    assert_eq!(trace.output, 0x85e813540f0ab405);
+}
+
+#[test]
+fn test_rounds_chain() {
+    let trace = example();
+    assert_eq!(trace.rounds.len(), 16);
+    for pair in trace.rounds.windows(2) {
+        assert_eq!((pair[0].next_l, pair[0].next_r), (pair[1].l, pair[1].r));
+    }
+    for round in &trace.rounds {
+        assert_eq!(round.xored, round.expanded ^ trace.sub_keys[round.sub_key_index]);
+        for i in 0..8 {
+            assert_eq!(round.sbox_inputs[i] as u64, round.xored >> (42 - 6 * i) & 0x3f);
+        }
+    }
+}
+
+#[test]
+fn test_decrypt_trace() {
+    let key = DesKey::from(0x0e329232ea6d0d73);
+    let ciphertext = Des::new(&key).encrypt_block([0x87; 8]);
+    let trace = trace_block(&key, ciphertext, Direction::Decrypt);
+    assert_eq!(trace.output, 0x8787878787878787);
+    assert_eq!(trace.rounds[0].sub_key_index, 15);
+    assert_eq!(trace.rounds[15].sub_key_index, 0);
+}
+
+#[test]
+fn test_output_formats() {
+    let trace = example();
+    let json = trace.to_json();
+    assert!(json.starts_with("{\"direction\":\"encrypt\",\"input\":\"0123456789abcdef\",\"sub_keys\":[\"1b02effc7072\","));
+    assert!(json.ends_with("\"output\":\"85e813540f0ab405\"}"));
+    assert_eq!(json.matches("\"round\":").count(), 16);
+    let table = trace.to_string();
+    assert!(table.contains("  K1   1b02effc7072  000110 110000 001011 101111 111111 000111 000001 110010"));
+    assert!(table.ends_with("IP^-1     85e813540f0ab405  10000101 11101000 00010011 01010100 00001111 00001010 10110100 00000101"));
+}
@@ -0,0 +1,165 @@
+//! Triple DES (TDEA, NIST SP 800-67) in encrypt-decrypt-encrypt form.
+//!
+//! A block is encrypted as `E_k3(D_k2(E_k1(x)))`. The three keying options of SP 800-67 are
+//! expressed by which of the keys are equal; with a single key the construction collapses to plain
+//! DES, which is what makes option 3 backward compatible. Each of the three passes is the bit-array
+//! DES of the crate root, `generate_sub_key` and `enc`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{enc, generate_sub_key, keygen as des_keygen, pick_64bit_from_slice, to_block, DesKey, ParseKeyError};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyingOption {
+    /// Keying option 1: three independent keys.
+    One,
+    /// Keying option 2: K1 and K2 independent, K3 = K1.
+    Two,
+    /// Keying option 3: K1 = K2 = K3, equivalent to single DES.
+    Three,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TripleDesKey {
+    pub k1: DesKey,
+    pub k2: DesKey,
+    pub k3: DesKey,
+}
+
+impl TripleDesKey {
+    pub fn new(k1: DesKey, k2: DesKey, k3: DesKey) -> TripleDesKey {
+        TripleDesKey { k1, k2, k3 }
+    }
+
+    pub fn two_key(k1: DesKey, k2: DesKey) -> TripleDesKey {
+        TripleDesKey { k1, k2, k3: k1 }
+    }
+
+    pub fn single(k: DesKey) -> TripleDesKey {
+        TripleDesKey { k1: k, k2: k, k3: k }
+    }
+
+    pub fn keying_option(&self) -> KeyingOption {
+        if self.k1 == self.k2 && self.k2 == self.k3 {
+            KeyingOption::Three
+        } else if self.k1 == self.k3 {
+            KeyingOption::Two
+        } else {
+            KeyingOption::One
+        }
+    }
+
+    /// Parses 16, 32 or 48 hexadecimal digits as K1, K1‖K2 or K1‖K2‖K3. Every 8-byte part has to
+    /// pass the same parity check as a single DES key.
+    pub fn from_hex(key: &str) -> Result<TripleDesKey, ParseKeyError> {
+        if !key.is_ascii() {
+            return Err(ParseKeyError::InvalidKeyStringFormat);
+        }
+        let parts = match key.len() {
+            16 | 32 | 48 => (0..key.len() / 16)
+                .map(|i| DesKey::from_hex(&key[i * 16..(i + 1) * 16]))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(ParseKeyError::InvalidKeyStringFormat),
+        };
+        Ok(match parts[..] {
+            [k] => TripleDesKey::single(k),
+            [k1, k2] => TripleDesKey::two_key(k1, k2),
+            [k1, k2, k3] => TripleDesKey::new(k1, k2, k3),
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl FromStr for TripleDesKey {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<TripleDesKey, ParseKeyError> {
+        TripleDesKey::from_hex(s)
+    }
+}
+
+/// Writes the shortest bundle [`TripleDesKey::from_hex`] reads back to the same key.
+impl fmt::Display for TripleDesKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.keying_option() {
+            KeyingOption::Three => write!(f, "{}", self.k1),
+            KeyingOption::Two => write!(f, "{}{}", self.k1, self.k2),
+            KeyingOption::One => write!(f, "{}{}{}", self.k1, self.k2, self.k3),
+        }
+    }
+}
+
+/// Generates a key bundle for `option` whose independent parts are pairwise distinct.
+pub fn keygen(option: KeyingOption) -> TripleDesKey {
+    let k1 = des_keygen();
+    if option == KeyingOption::Three {
+        return TripleDesKey::single(k1);
+    }
+    let k2 = loop {
+        let k = des_keygen();
+        if k != k1 { break k; }
+    };
+    if option == KeyingOption::Two {
+        return TripleDesKey::two_key(k1, k2);
+    }
+    let k3 = loop {
+        let k = des_keygen();
+        if k != k1 && k != k2 { break k; }
+    };
+    TripleDesKey::new(k1, k2, k3)
+}
+
+/// Triple DES with the round keys of K1, K2 and K3 computed up front, in encryption order and
+/// reversed for decryption.
+#[derive(Debug, Clone)]
+pub struct TripleDes {
+    encrypt: [[[bool; 48]; 16]; 3],
+    decrypt: [[[bool; 48]; 16]; 3],
+}
+
+impl TripleDes {
+    pub fn new(key: &TripleDesKey) -> TripleDes {
+        let encrypt = [generate_sub_key(&key.k1.bits()), generate_sub_key(&key.k2.bits()), generate_sub_key(&key.k3.bits())];
+        let mut decrypt = encrypt;
+        for sub_key in decrypt.iter_mut() {
+            sub_key.reverse();
+        }
+        TripleDes { encrypt, decrypt }
+    }
+
+    pub fn encrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        let block = enc(&pick_64bit_from_slice(&block), &self.encrypt[0]);
+        let block = enc(&block, &self.decrypt[1]);
+        to_block(enc(&block, &self.encrypt[2]))
+    }
+
+    pub fn decrypt_block(&self, block: [u8; 8]) -> [u8; 8] {
+        let block = enc(&pick_64bit_from_slice(&block), &self.decrypt[2]);
+        let block = enc(&block, &self.encrypt[1]);
+        to_block(enc(&block, &self.decrypt[0]))
+    }
+
+    pub fn encrypt_u64(&self, block: u64) -> u64 {
+        u64::from_be_bytes(self.encrypt_block(block.to_be_bytes()))
+    }
+
+    pub fn decrypt_u64(&self, block: u64) -> u64 {
+        u64::from_be_bytes(self.decrypt_block(block.to_be_bytes()))
+    }
+}
+
+/// Encrypts `input` in ECB mode. The last block is padded with zero bytes.
+pub fn encrypt(input: &[u8], key: &TripleDesKey) -> Vec<u8> {
+    let tdea = TripleDes::new(key);
+    crate::ecb(input, |block| tdea.encrypt_u64(block))
+}
+
+/// Decrypts `input` in ECB mode. A trailing partial block is padded with zero bytes first.
+pub fn decrypt(input: &[u8], key: &TripleDesKey) -> Vec<u8> {
+    let tdea = TripleDes::new(key);
+    crate::ecb(input, |block| tdea.decrypt_u64(block))
+}
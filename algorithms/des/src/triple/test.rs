@@ -0,0 +1,90 @@
+use crate::fast::FastDes;
+use crate::{Des, ParseKeyError};
+
+use super::*;
+
+fn key(value: u64) -> DesKey {
+    DesKey::from(value)
+}
+
+#[test]
+fn test_parse_key() {
+    let three: TripleDesKey = "0123456789abcdef23456789abcdef01456789abcdef0123".parse().unwrap();
+    assert_eq!(three, TripleDesKey::new(key(0x0123456789abcdef), key(0x23456789abcdef01), key(0x456789abcdef0123)));
+    assert_eq!(three.keying_option(), KeyingOption::One);
+    assert_eq!(three.to_string(), "0123456789abcdef23456789abcdef01456789abcdef0123");
+
+    let two: TripleDesKey = "0123456789abcdef23456789abcdef01".parse().unwrap();
+    assert_eq!(two, TripleDesKey::two_key(key(0x0123456789abcdef), key(0x23456789abcdef01)));
+    assert_eq!(two.keying_option(), KeyingOption::Two);
+    assert_eq!(two.to_string(), "0123456789abcdef23456789abcdef01");
+
+    let one: TripleDesKey = "0123456789abcdef".parse().unwrap();
+    assert_eq!(one.keying_option(), KeyingOption::Three);
+    assert_eq!(one.to_string(), "0123456789abcdef");
+
+    assert_eq!(TripleDesKey::from_hex("0123456789abcdef23456789abcdef"), Err(ParseKeyError::InvalidKeyStringFormat));
+    assert_eq!(TripleDesKey::from_hex("0123456789abcdef23456789abcdef0100"), Err(ParseKeyError::InvalidKeyStringFormat));
+    assert_eq!(TripleDesKey::from_hex("0123456789abcdef23456789abcdef00"), Err(ParseKeyError::CheckSumError));
+    assert_eq!(TripleDesKey::from_hex("0123456789abcdefあ3456789abcde"), Err(ParseKeyError::InvalidKeyStringFormat));
+}
+
+#[test]
+fn test_sp800_67_example() {
+    let key: TripleDesKey = "0123456789abcdef23456789abcdef01456789abcdef0123".parse().unwrap();
+    // The published example really encrypts "qufck"; the first block only matches with the typo.
+    let plaintext = b"The qufck brown fox jump";
+    let ciphertext = [
+        0xa8, 0x26, 0xfd, 0x8c, 0xe5, 0x3b, 0x85, 0x5f,
+        0xcc, 0xe2, 0x1c, 0x81, 0x12, 0x25, 0x6f, 0xe6,
+        0x68, 0xd5, 0xc0, 0x5d, 0xd9, 0xb6, 0xb9, 0x00,
+    ];
+    assert_eq!(encrypt(plaintext, &key), ciphertext.to_vec());
+    assert_eq!(decrypt(&ciphertext, &key), plaintext.to_vec());
+}
+
+#[test]
+fn test_sp800_20_known_answers() {
+    let tdea = TripleDes::new(&TripleDesKey::single(key(0x0101010101010101)));
+    assert_eq!(tdea.encrypt_u64(0x8000000000000000), 0x95f8a5e5dd31d900);
+    assert_eq!(tdea.encrypt_u64(0x4000000000000000), 0xdd7f121ca5015619);
+    assert_eq!(tdea.decrypt_u64(0x95f8a5e5dd31d900), 0x8000000000000000);
+    let tdea = TripleDes::new(&TripleDesKey::single(key(0x8001010101010101)));
+    assert_eq!(tdea.encrypt_u64(0), 0x95a8d72813daa94d);
+}
+
+#[test]
+fn test_keying_option_3_is_des() {
+    for _ in 0..20 {
+        let k = crate::keygen();
+        let des = Des::new(&k);
+        let tdea = TripleDes::new(&TripleDesKey::single(k));
+        let block = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+        assert_eq!(tdea.encrypt_block(block), des.encrypt_block(block));
+    }
+}
+
+#[test]
+fn test_matches_fast_des() {
+    for _ in 0..20 {
+        let key = keygen(KeyingOption::One);
+        let tdea = TripleDes::new(&key);
+        let (d1, d2, d3) = (FastDes::new(&key.k1), FastDes::new(&key.k2), FastDes::new(&key.k3));
+        let block = rand::random::<u64>();
+        let encrypted = d3.encrypt_u64(d2.decrypt_u64(d1.encrypt_u64(block)));
+        assert_eq!(tdea.encrypt_u64(block), encrypted);
+        assert_eq!(tdea.decrypt_u64(encrypted), block);
+    }
+}
+
+#[test]
+fn test_keygen() {
+    for &option in [KeyingOption::Three, KeyingOption::Two, KeyingOption::One].iter() {
+        let key = keygen(option);
+        assert_eq!(key.keying_option(), option);
+        assert!(key.k1.has_odd_parity() && key.k2.has_odd_parity() && key.k3.has_odd_parity());
+        assert_eq!(key.to_string().parse(), Ok(key));
+        let input: Vec<u8> = (0..40).collect();
+        assert_eq!(decrypt(&encrypt(&input, &key), &key), input);
+    }
+}
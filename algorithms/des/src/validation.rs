@@ -0,0 +1,334 @@
+//! The known answer tests of NBS Special Publication 500-20, and Monte Carlo tests.
+//!
+//! SP 500-20 ("Validating the Correctness of Hardware Implementations of the NBS Data Encryption
+//! Standard", reissued as NIST SP 800-17) exercises every part of the algorithm with a few hundred
+//! vectors:
+//!
+//! * variable plaintext: key 0101010101010101 and every plaintext with a single bit set, which
+//!   exercises the initial permutation and E;
+//! * inverse permutation: the ciphertexts of the previous test encrypted again, since that key is
+//!   weak and encryption is an involution, to get back the single bits through IP⁻¹;
+//! * variable key: plaintext 0 and every key with a single non-parity bit set (and odd parity),
+//!   for PC-1, the shifts and PC-2;
+//! * permutation operation: 32 keys chosen to exercise P;
+//! * substitution table: 19 key and plaintext pairs giving every S-box entry.
+//!
+//! The Monte Carlo tests chain encryptions the way SP 800-17 does for ECB: 10000 encryptions
+//! feeding each output back as the next input, then the key XORed with the last output, repeated
+//! 400 times. Their expected values come from an independent implementation. Every test goes
+//! through the public [`crate::encrypt`] and [`crate::decrypt`].
+
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::{decrypt, encrypt, DesKey};
+
+#[cfg(test)]
+mod test;
+
+/// Outer Monte Carlo iterations of a quick run, each checked against a recorded value.
+pub const QUICK_MONTE_CARLO_ROUNDS: usize = 20;
+/// Outer Monte Carlo iterations of SP 800-17; the last one is checked as well.
+pub const FULL_MONTE_CARLO_ROUNDS: usize = 400;
+/// Encryptions chained in one outer Monte Carlo iteration.
+pub const MONTE_CARLO_CHAIN: usize = 10000;
+
+const WEAK_KEY: u64 = 0x0101010101010101;
+
+const VARIABLE_PLAINTEXT: [u64; 64] = [
+    0x95f8a5e5dd31d900, 0xdd7f121ca5015619, 0x2e8653104f3834ea, 0x4bd388ff6cd81d4f,
+    0x20b9e767b2fb1456, 0x55579380d77138ef, 0x6cc5defaaf04512f, 0x0d9f279ba5d87260,
+    0xd9031b0271bd5a0a, 0x424250b37c3dd951, 0xb8061b7ecd9a21e5, 0xf15d0f286b65bd28,
+    0xadd0cc8d6e5deba1, 0xe6d5f82752ad63d1, 0xecbfe3bd3f591a5e, 0xf356834379d165cd,
+    0x2b9f982f20037fa9, 0x889de068a16f0be6, 0xe19e275d846a1298, 0x329a8ed523d71aec,
+    0xe7fce22557d23c97, 0x12a9f5817ff2d65d, 0xa484c3ad38dc9c19, 0xfbe00a8a1ef8ad72,
+    0x750d079407521363, 0x64feed9c724c2faf, 0xf02b263b328e2b60, 0x9d64555a9a10b852,
+    0xd106ff0bed5255d7, 0xe1652c6b138c64a5, 0xe428581186ec8f46, 0xaeb5f5ede22d1a36,
+    0xe943d7568aec0c5c, 0xdf98c8276f54b04b, 0xb160e4680f6c696f, 0xfa0752b07d9c4ab8,
+    0xca3a2b036dbc8502, 0x5e0905517bb59bcf, 0x814eeb3b91d90726, 0x4d49db1532919c9f,
+    0x25eb5fc3f8cf0621, 0xab6a20c0620d1c6f, 0x79e90dbc98f92cca, 0x866ecedd8072bb0e,
+    0x8b54536f2f3e64a8, 0xea51d3975595b86b, 0xcaffc6ac4542de31, 0x8dd45a2ddf90796c,
+    0x1029d55e880ec2d0, 0x5d86cb23639dbea9, 0x1d1ca853ae7c0c5f, 0xce332329248f3228,
+    0x8405d1abe24fb942, 0xe643d78090ca4207, 0x48221b9937748a23, 0xdd7c0bbd61fafd54,
+    0x2fbc291a570db5c4, 0xe07c30d7e4e26e12, 0x0953e2258e8e90a1, 0x5b711bc4ceebf2ee,
+    0xcc083f1e6d9e85f6, 0xd2fd8867d50d2dfe, 0x06e7ea22ce92708f, 0x166b40b44aba4bd6,
+];
+
+const VARIABLE_KEY: [u64; 56] = [
+    0x95a8d72813daa94d, 0x0eec1487dd8c26d5, 0x7ad16ffb79c45926, 0xd3746294ca6a6cf3,
+    0x809f5f873c1fd761, 0xc02faffec989d1fc, 0x4615aa1d33e72f10, 0x2055123350c00858,
+    0xdf3b99d6577397c8, 0x31fe17369b5288c9, 0xdfdd3cc64dae1642, 0x178c83ce2b399d94,
+    0x50f636324a9b7f80, 0xa8468ee3bc18f06d, 0xa2dc9e92fd3cde92, 0xcac09f797d031287,
+    0x90ba680b22aeb525, 0xce7a24f350e280b6, 0x882bff0aa01a0b87, 0x25610288924511c2,
+    0xc71516c29c75d170, 0x5199c29a52c9f059, 0xc22f0a294a71f29f, 0xee371483714c02ea,
+    0xa81fbd448f9e522f, 0x4f644c92e192dfed, 0x1afa9a66a6df92ae, 0xb3c1cc715cb879d8,
+    0x19d032e64ab0bd8b, 0x3cfaa7a7dc8720dc, 0xb7265f7f447ac6f3, 0x9db73b3c0d163f54,
+    0x8181b65babf4a975, 0x93c9b64042eaa240, 0x5570530829705592, 0x8638809e878787a0,
+    0x41b9a79af79ac208, 0x7a9be42f2009a892, 0x29038d56ba6d2745, 0x5495c6abf1e5df51,
+    0xae13dbd561488933, 0x024d1ffa8904e389, 0xd1399712f99bf02e, 0x14c1d7c1cffec79e,
+    0x1de5279dae3bed6f, 0xe941a33f85501303, 0xda99dbbc9a03f379, 0xb7fc92f91d8e92e9,
+    0xae8e5caa3ca04e85, 0x9cc62df43b6eed74, 0xd863dbb5c59a91a0, 0xa1ab2190545b91d7,
+    0x0875041e64c570f7, 0x5a594528bebef1cc, 0xfcdb3291de21f0c0, 0x869efd7f9f265a09,
+];
+
+const PERMUTATION_OPERATION: [(u64, u64); 32] = [
+    (0x1046913489980131, 0x88d55e54f54c97b4), (0x1007103489988020, 0x0c0cc00c83ea48fd),
+    (0x10071034c8980120, 0x83bc8ef3a6570183), (0x1046103489988020, 0xdf725dcad94ea2e9),
+    (0x1086911519190101, 0xe652b53b550be8b0), (0x1086911519580101, 0xaf527120c485cbb0),
+    (0x5107b01519580101, 0x0f04ce393db926d5), (0x1007b01519190101, 0xc9f00ffc74079067),
+    (0x3107915498080101, 0x7cfd82a593252b4e), (0x3107919498080101, 0xcb49a2f9e91363e3),
+    (0x10079115b9080140, 0x00b588be70d23f56), (0x3107911598080140, 0x406a9a6ab43399ae),
+    (0x1007d01589980101, 0x6cb773611dca9ada), (0x9107911589980101, 0x67fd21c17dbb5d70),
+    (0x9107d01589190101, 0x9592cb4110430787), (0x1007d01598980120, 0xa6b7ff68a318ddd3),
+    (0x1007940498190101, 0x4d102196c914ca16), (0x0107910491190401, 0x2dfa9f4573594965),
+    (0x0107910491190101, 0xb46604816c0e0774), (0x0107940491190401, 0x6e7e6221a4f34e87),
+    (0x19079210981a0101, 0xaa85e74643233199), (0x1007911998190801, 0x2e5a19db4d1962d6),
+    (0x10079119981a0801, 0x23a866a809d30894), (0x1007921098190101, 0xd812d961f017d320),
+    (0x100791159819010b, 0x055605816e58608f), (0x1004801598190101, 0xabd88e8b1b7716f1),
+    (0x1004801598190102, 0x537ac95be69da1e1), (0x1004801598190108, 0xaed0f6ae3c25cdd8),
+    (0x1002911498100104, 0xb3e35a5ee53e7b8d), (0x1002911598190104, 0x61c79c71921a2ef8),
+    (0x1002911598100201, 0xe2f5728f0995013c), (0x1002911698100101, 0x1aeac39a61f0a464),
+];
+
+const SUBSTITUTION_TABLE: [(u64, u64, u64); 19] = [
+    (0x7ca110454a1a6e57, 0x01a1d6d039776742, 0x690f5b0d9a26939b),
+    (0x0131d9619dc1376e, 0x5cd54ca83def57da, 0x7a389d10354bd271),
+    (0x07a1133e4a0b2686, 0x0248d43806f67172, 0x868ebb51cab4599a),
+    (0x3849674c2602319e, 0x51454b582ddf440a, 0x7178876e01f19b2a),
+    (0x04b915ba43feb5b6, 0x42fd443059577fa2, 0xaf37fb421f8c4095),
+    (0x0113b970fd34f2ce, 0x059b5e0851cf143a, 0x86a560f10ec6d85b),
+    (0x0170f175468fb5e6, 0x0756d8e0774761d2, 0x0cd3da020021dc09),
+    (0x43297fad38e373fe, 0x762514b829bf486a, 0xea676b2cb7db2b7a),
+    (0x07a7137045da2a16, 0x3bdd119049372802, 0xdfd64a815caf1a0f),
+    (0x04689104c2fd3b2f, 0x26955f6835af609a, 0x5c513c9c4886c088),
+    (0x37d06bb516cb7546, 0x164d5e404f275232, 0x0a2aeeae3ff4ab77),
+    (0x1f08260d1ac2465e, 0x6b056e18759f5cca, 0xef1bf03e5dfa575a),
+    (0x584023641aba6176, 0x004bd6ef09176062, 0x88bf0db6d70dee56),
+    (0x025816164629b007, 0x480d39006ee762f2, 0xa1f9915541020b56),
+    (0x49793ebc79b3258f, 0x437540c8698f3cfa, 0x6fbf1cafcffd0556),
+    (0x4fb05e1515ab73a7, 0x072d43a077075292, 0x2f22e49bab7ca1ac),
+    (0x49e95d6d4ca229bf, 0x02fe55778117f12a, 0x5a6b612cc26cce4a),
+    (0x018310dc409b26d6, 0x1d9d5c5018f728c2, 0x5f4c038ed12b2e41),
+    (0x1c587f1c13924fef, 0x305532286d6f295a, 0x63fac0d034d9f793),
+];
+
+const MONTE_CARLO_KEY: u64 = 0x0123456789abcdef;
+const MONTE_CARLO_TEXT: u64 = 0x4e6f772069732074;
+
+/// Key and output of the first outer iterations of the encryption Monte Carlo test.
+const MONTE_CARLO_ENCRYPT: [(u64, u64); 20] = [
+    (0x0123456789abcdef, 0x6a2a19f41eca854b),
+    (0x6b085d92976149a4, 0xce5d6c7b63177c18),
+    (0xa45431e9f47634bc, 0xba165ffa0060347c),
+    (0x1f436e13f41601c1, 0x5c6acddd5b051d1e),
+    (0x4329a2ceae131cdf, 0x7fd863eec9bc6b22),
+    (0x3df1c12067ae76fd, 0x4aa195687c4772b7),
+    (0x765154491ae9044a, 0xb5e674897bf5ae13),
+    (0xc2b620c1611cab58, 0x1cbc42369f5391cc),
+    (0xdf0b62f7fe4f3b94, 0xc3802ff47c7cdc15),
+    (0x1c8a4c028332e680, 0xe80641b428c4b05e),
+    (0xf48c0db6abf757df, 0xeb288f6775dd651c),
+    (0x1fa483d0df2a32c2, 0x015f5bd63642cb3b),
+    (0x1ffbd907e968f8f8, 0xeec1e051f935c6dc),
+    (0xf13b3857105d3e25, 0x221f0839d2dd3403),
+    (0xd325316ec2800b26, 0x12a7ba1a9aa871ea),
+    (0xc1838a7558297acd, 0x15df075d1f81319e),
+    (0xd55d8c2946a84a52, 0x0dad409a2194ac7e),
+    (0xd9f1cdb3673de62c, 0x216d1aeffd127e4b),
+    (0xf89dd65d9b2f9867, 0x2da2b78288aa4de6),
+    (0xd53e61df1385d580, 0xbe32e98ce7729f9b),
+];
+
+/// Key and output of the last outer iteration of the encryption Monte Carlo test.
+const MONTE_CARLO_ENCRYPT_LAST: (u64, u64) = (0xc2f24cc7da68f125, 0x74d8a695064ec574);
+
+/// The same for the decryption Monte Carlo test.
+const MONTE_CARLO_DECRYPT: [(u64, u64); 20] = [
+    (0x0123456789abcdef, 0xcdd64f2f9427c15d),
+    (0xcdf40b491c8c0db3, 0x5bb675e3db3a7f3b),
+    (0x97437fabc7b67389, 0x2da0cf64b1782938),
+    (0xbae3b0ce76ce5bb0, 0xe6114b8ed2919745),
+    (0x5df2fb40a45ecdf4, 0x991745d6883a1bab),
+    (0xc4e5bf972c64d65e, 0x6c1902ec0dae7d42),
+    (0xa8fdbc7a20cbab1c, 0x5f89f8d0a78a8f84),
+    (0xf77545ab86402598, 0x1d021de34bb46da3),
+    (0xea765849cdf4493b, 0x921b9fae8eb41599),
+    (0x796dc7e643405da2, 0xdff2453466e293ec),
+    (0xa79e83d325a2ce4f, 0x433ae0d83121d080),
+    (0xe5a4620b15831fce, 0xdaf083cf068a60a6),
+    (0x3e54e0c413087f68, 0x70fe450f9a06cece),
+    (0x4faba4cb890eb0a7, 0xff7c851a2ec85ccb),
+    (0xb0d620d0a7c7ec6d, 0x213619ee200cdc37),
+    (0x91e0383e86cb315b, 0x8a591ad097b8cfd9),
+    (0x1ab923ef1073fe83, 0x210de1b7eb612877),
+    (0x3bb5c258fb13d6f4, 0x7a3648d9524e77d6),
+    (0x40838a80a85da123, 0xd0f6ef72a2b31911),
+    (0x917564f20befb932, 0x78d23d9d54ec3971),
+];
+
+/// The last outer iteration of the decryption Monte Carlo test.
+const MONTE_CARLO_DECRYPT_LAST: (u64, u64) = (0x8c3eda7a34fdd5e6, 0xe5777d545f9067f6);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suite {
+    VariablePlaintext,
+    InversePermutation,
+    VariableKey,
+    PermutationOperation,
+    SubstitutionTable,
+    MonteCarloEncrypt,
+    MonteCarloDecrypt,
+}
+
+impl Suite {
+    pub const ALL: [Suite; 7] = [
+        Suite::VariablePlaintext,
+        Suite::InversePermutation,
+        Suite::VariableKey,
+        Suite::PermutationOperation,
+        Suite::SubstitutionTable,
+        Suite::MonteCarloEncrypt,
+        Suite::MonteCarloDecrypt,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Suite::VariablePlaintext => "variable plaintext",
+            Suite::InversePermutation => "inverse permutation",
+            Suite::VariableKey => "variable key",
+            Suite::PermutationOperation => "permutation operation",
+            Suite::SubstitutionTable => "substitution table",
+            Suite::MonteCarloEncrypt => "Monte Carlo encrypt",
+            Suite::MonteCarloDecrypt => "Monte Carlo decrypt",
+        }
+    }
+}
+
+/// A known answer: `key` encrypts `plaintext` to `ciphertext`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vector {
+    pub key: DesKey,
+    pub plaintext: u64,
+    pub ciphertext: u64,
+}
+
+impl Vector {
+    fn new(key: u64, plaintext: u64, ciphertext: u64) -> Vector {
+        Vector { key: DesKey::from(key), plaintext, ciphertext }
+    }
+}
+
+/// The known answers of a suite. Monte Carlo suites have none, their values depend on the chain.
+pub fn vectors(suite: Suite) -> Vec<Vector> {
+    match suite {
+        Suite::VariablePlaintext => VARIABLE_PLAINTEXT.iter().enumerate()
+            .map(|(i, &c)| Vector::new(WEAK_KEY, 1 << (63 - i), c))
+            .collect(),
+        Suite::InversePermutation => VARIABLE_PLAINTEXT.iter().enumerate()
+            .map(|(i, &c)| Vector::new(WEAK_KEY, c, 1 << (63 - i)))
+            .collect(),
+        Suite::VariableKey => (0..64).filter(|bit| bit % 8 != 7).zip(VARIABLE_KEY.iter())
+            .map(|(bit, &c)| Vector::new(WEAK_KEY & !(1 << (56 - bit / 8 * 8)) | 1 << (63 - bit), 0, c))
+            .collect(),
+        Suite::PermutationOperation => PERMUTATION_OPERATION.iter()
+            .map(|&(key, c)| Vector::new(key, 0, c))
+            .collect(),
+        Suite::SubstitutionTable => SUBSTITUTION_TABLE.iter()
+            .map(|&(key, p, c)| Vector::new(key, p, c))
+            .collect(),
+        Suite::MonteCarloEncrypt | Suite::MonteCarloDecrypt => Vec::new(),
+    }
+}
+
+fn encrypt_u64(key: &DesKey, block: u64) -> u64 {
+    u64::from_be_bytes(encrypt(&block.to_be_bytes(), key)[..].try_into().unwrap())
+}
+
+fn decrypt_u64(key: &DesKey, block: u64) -> u64 {
+    u64::from_be_bytes(decrypt(&block.to_be_bytes(), key)[..].try_into().unwrap())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// Number of the vector, or of the outer iteration of a Monte Carlo test, from 0.
+    pub index: usize,
+    pub key: DesKey,
+    pub input: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub suite: Suite,
+    /// Values compared, encryption and decryption counted separately.
+    pub checks: usize,
+    pub failures: Vec<Failure>,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<22} {:>4}/{:<4} {}", self.suite.name(), self.checks - self.failures.len(), self.checks,
+               if self.passed() { "ok" } else { "FAILED" })?;
+        for failure in &self.failures {
+            write!(f, "\n  #{}: key {} input {:016x}: expected {:016x}, got {:016x}",
+                   failure.index, failure.key, failure.input, failure.expected, failure.actual)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs one suite. Known answers are checked in both directions. Monte Carlo tests run
+/// [`QUICK_MONTE_CARLO_ROUNDS`] outer iterations, or all [`FULL_MONTE_CARLO_ROUNDS`] if `full`.
+pub fn run(suite: Suite, full: bool) -> Report {
+    let mut report = Report { suite, checks: 0, failures: Vec::new() };
+    let mut check = |index: usize, key: &DesKey, input: u64, expected: u64, actual: u64| {
+        report.checks += 1;
+        if actual != expected {
+            report.failures.push(Failure { index, key: *key, input, expected, actual });
+        }
+    };
+    match suite {
+        Suite::MonteCarloEncrypt | Suite::MonteCarloDecrypt => {
+            let crypt: fn(&DesKey, u64) -> u64 = if suite == Suite::MonteCarloEncrypt { encrypt_u64 } else { decrypt_u64 };
+            let (first, last) = if suite == Suite::MonteCarloEncrypt {
+                (MONTE_CARLO_ENCRYPT, MONTE_CARLO_ENCRYPT_LAST)
+            } else {
+                (MONTE_CARLO_DECRYPT, MONTE_CARLO_DECRYPT_LAST)
+            };
+            let rounds = if full { FULL_MONTE_CARLO_ROUNDS } else { QUICK_MONTE_CARLO_ROUNDS };
+            let mut key = DesKey::from(MONTE_CARLO_KEY);
+            let mut text = MONTE_CARLO_TEXT;
+            for i in 0..rounds {
+                let input = text;
+                for _ in 0..MONTE_CARLO_CHAIN {
+                    text = crypt(&key, text);
+                }
+                let expected = first.get(i).copied().or(if i == FULL_MONTE_CARLO_ROUNDS - 1 { Some(last) } else { None });
+                if let Some((expected_key, expected)) = expected {
+                    check(i, &key, input, expected_key, key.to_u64());
+                    check(i, &key, input, expected, text);
+                }
+                key = DesKey::from(key.to_u64() ^ text).with_odd_parity();
+            }
+        }
+        _ => {
+            for (i, vector) in vectors(suite).iter().enumerate() {
+                check(i, &vector.key, vector.plaintext, vector.ciphertext, encrypt_u64(&vector.key, vector.plaintext));
+                check(i, &vector.key, vector.ciphertext, vector.plaintext, decrypt_u64(&vector.key, vector.ciphertext));
+            }
+        }
+    }
+    report
+}
+
+/// Runs every suite in order.
+pub fn run_all(full: bool) -> Vec<Report> {
+    Suite::ALL.iter().map(|&suite| run(suite, full)).collect()
+}
@@ -0,0 +1,55 @@
+use super::*;
+
+fn assert_passes(suite: Suite) {
+    let report = run(suite, false);
+    assert!(report.passed(), "{}", report);
+}
+
+#[test]
+fn test_variable_plaintext() {
+    assert_passes(Suite::VariablePlaintext);
+    assert_eq!(run(Suite::VariablePlaintext, false).checks, 128);
+}
+
+#[test]
+fn test_inverse_permutation() {
+    assert_passes(Suite::InversePermutation);
+}
+
+#[test]
+fn test_variable_key() {
+    assert_passes(Suite::VariableKey);
+    let keys: Vec<_> = vectors(Suite::VariableKey).iter().map(|v| v.key).collect();
+    assert_eq!(keys[0], DesKey::from(0x8001010101010101));
+    assert_eq!(keys[55], DesKey::from(0x0101010101010102));
+    assert!(keys.iter().all(DesKey::has_odd_parity));
+}
+
+#[test]
+fn test_permutation_operation() {
+    assert_passes(Suite::PermutationOperation);
+}
+
+#[test]
+fn test_substitution_table() {
+    assert_passes(Suite::SubstitutionTable);
+}
+
+#[test]
+fn test_monte_carlo() {
+    assert_passes(Suite::MonteCarloEncrypt);
+    assert_passes(Suite::MonteCarloDecrypt);
+    assert_eq!(run(Suite::MonteCarloEncrypt, false).checks, 2 * QUICK_MONTE_CARLO_ROUNDS);
+}
+
+#[test]
+fn test_report() {
+    let report = Report {
+        suite: Suite::SubstitutionTable,
+        checks: 38,
+        failures: vec![Failure { index: 3, key: DesKey::from(0x3849674c2602319e), input: 0x51454b582ddf440a, expected: 0x7178876e01f19b2a, actual: 0 }],
+    };
+    assert!(!report.passed());
+    assert_eq!(report.to_string(), "substitution table       37/38   FAILED\n  \
+        #3: key 3849674c2602319e input 51454b582ddf440a: expected 7178876e01f19b2a, got 0000000000000000");
+}
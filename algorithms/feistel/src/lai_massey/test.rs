@@ -0,0 +1,28 @@
+use rand::{thread_rng, Rng};
+
+use super::*;
+
+#[test]
+fn test_sigma() {
+    let mut rng = thread_rng();
+    for _ in 0..100 {
+        let x: u32 = rng.gen();
+        assert_eq!(sigma_inverse(sigma(x)), x);
+        // An orthomorphism: x -> σ(x) ⊕ x is a permutation as well, checked here on its inverse.
+        let y = sigma(x) ^ x;
+        assert_eq!(y, (x & 0xffff) << 16 ^ x ^ (x >> 16) ^ (x & 0xffff));
+    }
+}
+
+#[test]
+fn test_inverse() {
+    let mut rng = thread_rng();
+    let f = |x: &u32, k: &u32| (x ^ k).wrapping_mul(0x9e3779b9) & 0x0ff00ff0;
+    let sub_keys: Vec<u32> = (0..8).map(|_| rng.gen()).collect();
+    let cipher = LaiMassey::new(sub_keys, f);
+    for _ in 0..100 {
+        let (l, r) = (rng.gen(), rng.gen());
+        let (a, b) = cipher.encrypt(l, r);
+        assert_eq!(cipher.decrypt(a, b), (l, r));
+    }
+}
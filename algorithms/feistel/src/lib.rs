@@ -0,0 +1,135 @@
+//! Feistel networks with pluggable round function and key schedule.
+//!
+//! A round of a balanced Feistel network maps the halves (L, R) to (R, L ⊕ F(R, K)). The network
+//! is a permutation whatever F is, and its inverse is the same network with the round keys in
+//! reverse order, as long as the halves are exchanged once more after the last round. DES is such
+//! a network with 32-bit halves, sixteen rounds and its `f`; [`toy`] builds small ciphers for
+//! exercises from the same construction, and [`unbalanced`] and [`lai_massey`] are two relatives.
+
+use std::marker::PhantomData;
+
+pub mod lai_massey;
+pub mod toy;
+pub mod unbalanced;
+
+#[cfg(test)]
+mod test;
+
+/// A value a round function output can be XORed into. The size of the half block is the size of
+/// the implementing type.
+pub trait Xor: Sized {
+    fn xor(&self, other: &Self) -> Self;
+}
+
+macro_rules! impl_xor {
+    ($($t:ty),*) => {
+        $(
+            impl Xor for $t {
+                fn xor(&self, other: &$t) -> $t {
+                    self ^ other
+                }
+            }
+        )*
+    };
+}
+
+impl_xor!(u8, u16, u32, u64, u128);
+
+impl<const N: usize> Xor for [bool; N] {
+    fn xor(&self, other: &[bool; N]) -> [bool; N] {
+        let mut result = *self;
+        for (x, y) in result.iter_mut().zip(other.iter()) {
+            *x ^= y;
+        }
+        result
+    }
+}
+
+impl<const N: usize> Xor for [u8; N] {
+    fn xor(&self, other: &[u8; N]) -> [u8; N] {
+        let mut result = *self;
+        for (x, y) in result.iter_mut().zip(other.iter()) {
+            *x ^= y;
+        }
+        result
+    }
+}
+
+/// The F of a Feistel network: combines a half block with a round key. Closures
+/// `Fn(&H, &K) -> H` are round functions.
+pub trait RoundFunction<H, K> {
+    fn apply(&self, half: &H, sub_key: &K) -> H;
+}
+
+impl<H, K, F: Fn(&H, &K) -> H> RoundFunction<H, K> for F {
+    fn apply(&self, half: &H, sub_key: &K) -> H {
+        self(half, sub_key)
+    }
+}
+
+/// Derives the round keys from a key. Closures `Fn(&Key, usize) -> Vec<K>` taking the number of
+/// rounds are key schedules.
+pub trait KeySchedule<Key, K> {
+    fn sub_keys(&self, key: &Key, rounds: usize) -> Vec<K>;
+}
+
+impl<Key, K, S: Fn(&Key, usize) -> Vec<K>> KeySchedule<Key, K> for S {
+    fn sub_keys(&self, key: &Key, rounds: usize) -> Vec<K> {
+        self(key, rounds)
+    }
+}
+
+/// Runs one round per round key on (`l`, `r`) and returns the halves in output order, that is
+/// (Rn, Ln) with the exchange of the last round undone.
+#[inline]
+pub fn network<'a, H: Xor, K: 'a>(mut l: H, mut r: H, sub_keys: impl IntoIterator<Item=&'a K>,
+                                  f: &impl RoundFunction<H, K>) -> (H, H) {
+    for k in sub_keys {
+        let next_r = l.xor(&f.apply(&r, k));
+        l = r;
+        r = next_r;
+    }
+    (r, l)
+}
+
+/// A balanced Feistel network with its round keys.
+#[derive(Debug, Clone)]
+pub struct Feistel<H, K, F> {
+    sub_keys: Vec<K>,
+    f: F,
+    half: PhantomData<H>,
+}
+
+impl<H: Xor, K, F: RoundFunction<H, K>> Feistel<H, K, F> {
+    /// A network of `rounds` rounds with the round keys `schedule` derives from `key`.
+    pub fn new<Key>(key: &Key, rounds: usize, schedule: &impl KeySchedule<Key, K>, f: F) -> Feistel<H, K, F> {
+        let sub_keys = schedule.sub_keys(key, rounds);
+        assert_eq!(sub_keys.len(), rounds, "the key schedule has to give one key per round");
+        Feistel::from_sub_keys(sub_keys, f)
+    }
+
+    /// A network with one round per round key.
+    pub fn from_sub_keys(sub_keys: Vec<K>, f: F) -> Feistel<H, K, F> {
+        Feistel { sub_keys, f, half: PhantomData }
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.sub_keys.len()
+    }
+
+    pub fn sub_keys(&self) -> &[K] {
+        &self.sub_keys
+    }
+
+    pub fn round_function(&self) -> &F {
+        &self.f
+    }
+
+    pub fn encrypt(&self, l: H, r: H) -> (H, H) {
+        network(l, r, self.sub_keys.iter(), &self.f)
+    }
+
+    pub fn decrypt(&self, l: H, r: H) -> (H, H) {
+        network(l, r, self.sub_keys.iter().rev(), &self.f)
+    }
+}
@@ -0,0 +1,134 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use feistel::toy::{self, Toy, ToyConfig};
+
+fn main() {
+    let sbox_arg = Arg::with_name("sbox")
+        .short("s")
+        .long("sbox")
+        .help("4-bit S-box as 16 hex digits(default is the first row of DES S1)")
+        .takes_value(true);
+    let matches = App::new("feistel")
+        .about("toy Feistel cipher for exercises")
+        .subcommand(SubCommand::with_name("tables")
+            .about("print the difference distribution and linear approximation tables of the S-box")
+            .arg(sbox_arg.clone()))
+        .arg(Arg::with_name("decrypt")
+            .short("d")
+            .long("decrypt")
+            .help("flag to decrypt"))
+        .arg(Arg::with_name("key")
+            .short("k")
+            .long("key")
+            .help("Key for encrypt or decrypt(up to 16 hex digits)")
+            .takes_value(true))
+        .arg(Arg::with_name("rounds")
+            .short("r")
+            .long("rounds")
+            .help("number of rounds")
+            .takes_value(true)
+            .default_value("4"))
+        .arg(Arg::with_name("half-bits")
+            .short("b")
+            .long("half-bits")
+            .help("bits per half block(a multiple of 4 from 4 to 32)")
+            .takes_value(true)
+            .default_value("8"))
+        .arg(Arg::with_name("rotation")
+            .long("rotation")
+            .help("left rotation of the half after the S-boxes")
+            .takes_value(true)
+            .default_value("3"))
+        .arg(sbox_arg)
+        .arg(Arg::with_name("input")
+            .help("blocks to encrypt or decrypt, as hex digits(2 * half-bits / 4 per block)")
+            .multiple(true))
+        .get_matches();
+    if let Some(matches) = matches.subcommand_matches("tables") {
+        let sbox = match parse_sbox(matches) {
+            Ok(sbox) => sbox,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+        println!("difference distribution table (dx rows, dy columns)");
+        for (dx, row) in toy::difference_distribution_table(&sbox).iter().enumerate() {
+            let row: Vec<_> = row.iter().map(|n| format!("{:>2}", n)).collect();
+            println!("{:x}: {}", dx, row.join(" "));
+        }
+        println!();
+        println!("linear approximation table (a rows, b columns)");
+        for (a, row) in toy::linear_approximation_table(&sbox).iter().enumerate() {
+            let row: Vec<_> = row.iter().map(|n| format!("{:>2}", n)).collect();
+            println!("{:x}: {}", a, row.join(" "));
+        }
+        return;
+    }
+    let config = match parse_config(&matches) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let key = match matches.value_of("key").map(|key| u64::from_str_radix(key, 16)) {
+        Some(Ok(key)) => key,
+        Some(Err(_)) => {
+            eprintln!("error in parsing key: key should be up to 16 hexadecimal digits");
+            return;
+        }
+        None => {
+            eprintln!("argument 'key' is required");
+            return;
+        }
+    };
+    let toy = match Toy::new(config, key) {
+        Ok(toy) => toy,
+        Err(e) => {
+            eprintln!("error in configuration: {}", e);
+            return;
+        }
+    };
+    let digits = (config.half_bits / 2) as usize;
+    let mut output = Vec::new();
+    for block in matches.values_of("input").into_iter().flatten() {
+        let value = match u64::from_str_radix(block, 16) {
+            Ok(value) if block.len() <= digits => value,
+            _ => {
+                eprintln!("error in parsing input: blocks should be up to {} hexadecimal digits", digits);
+                return;
+            }
+        };
+        let result = if matches.is_present("decrypt") { toy.decrypt_block(value) } else { toy.encrypt_block(value) };
+        output.push(format!("{:01$x}", result, digits));
+    }
+    println!("{}", output.join(" "));
+}
+
+fn parse_sbox(matches: &ArgMatches) -> Result<[u8; 16], String> {
+    let sbox = match matches.value_of("sbox") {
+        Some(sbox) => sbox,
+        None => return Ok(toy::DEFAULT_SBOX),
+    };
+    let values: Vec<_> = sbox.chars().filter_map(|c| c.to_digit(16)).collect();
+    if values.len() != 16 || sbox.len() != 16 {
+        return Err(String::from("argument 'sbox' should be 16 hexadecimal digits"));
+    }
+    let mut result = [0; 16];
+    for (x, &v) in result.iter_mut().zip(values.iter()) {
+        *x = v as u8;
+    }
+    Ok(result)
+}
+
+fn parse_config(matches: &ArgMatches) -> Result<ToyConfig, String> {
+    let number = |name: &str| matches.value_of(name).unwrap().parse::<u32>()
+        .map_err(|_| format!("argument '{}' should be a number", name));
+    Ok(ToyConfig {
+        half_bits: number("half-bits")?,
+        rounds: number("rounds")? as usize,
+        sbox: parse_sbox(matches)?,
+        rotation: number("rotation")?,
+    })
+}
@@ -0,0 +1,48 @@
+use rand::{thread_rng, Rng};
+
+use super::*;
+
+/// Deliberately not a permutation of the half.
+fn squash(half: &u32, key: &u32) -> u32 {
+    (half ^ key).wrapping_mul(0x9e3779b9) & 0xff00ff00
+}
+
+#[test]
+fn test_network() {
+    let f = |r: &u8, k: &u8| r.wrapping_add(*k);
+    // Two rounds by hand: (1, 2) -> (2, 1 ^ (2 + 10)) -> (13, 2 ^ (13 + 20)), output (R2, L2).
+    assert_eq!(network(1u8, 2u8, [10u8, 20].iter(), &f), (2 ^ 33, 13));
+}
+
+#[test]
+fn test_inverse() {
+    let mut rng = thread_rng();
+    for rounds in 0..8 {
+        let sub_keys: Vec<u32> = (0..rounds).map(|_| rng.gen()).collect();
+        let feistel = Feistel::from_sub_keys(sub_keys, squash);
+        for _ in 0..100 {
+            let (l, r) = (rng.gen(), rng.gen());
+            let (a, b) = feistel.encrypt(l, r);
+            assert_eq!(feistel.decrypt(a, b), (l, r));
+        }
+    }
+}
+
+#[test]
+fn test_key_schedule() {
+    let schedule = |key: &u64, rounds: usize| (0..rounds).map(|i| key.rotate_left(i as u32) as u32).collect();
+    let feistel = Feistel::new(&0x8000000000000001u64, 3, &schedule, squash);
+    assert_eq!(feistel.rounds(), 3);
+    assert_eq!(feistel.sub_keys(), &[1, 3, 6]);
+}
+
+#[test]
+fn test_bool_halves() {
+    let f = |r: &[bool; 4], k: &[bool; 4]| [r[1] & k[0], r[2] | k[1], r[3] ^ k[2], r[0] & k[3]];
+    let feistel = Feistel::from_sub_keys(vec![[true, false, true, true], [false, true, true, false]], f);
+    let l = [true, true, false, false];
+    let r = [false, true, false, true];
+    let (a, b) = feistel.encrypt(l, r);
+    assert_ne!((a, b), (l, r));
+    assert_eq!(feistel.decrypt(a, b), (l, r));
+}
@@ -0,0 +1,172 @@
+//! Small Feistel ciphers for exercises and cryptanalysis demos.
+//!
+//! The round function XORs the round key into the half, sends every nibble through one 4-bit
+//! S-box and rotates the result left. Everything the analysis depends on can be chosen: the half
+//! size, the number of rounds, the S-box and the rotation. With 8-bit halves the whole codebook
+//! has 65536 entries, so attacks can be checked against exhaustive search.
+
+use std::fmt;
+
+use crate::{Feistel, RoundFunction};
+
+#[cfg(test)]
+mod test;
+
+/// The first row of the DES S1, a 4-bit permutation.
+pub const DEFAULT_SBOX: [u8; 16] = [14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToyConfig {
+    /// Bits per half, a multiple of 4 from 4 to 32. The block is twice as long.
+    pub half_bits: u32,
+    pub rounds: usize,
+    /// Applied to every nibble. It does not have to be a permutation: F never gets inverted.
+    pub sbox: [u8; 16],
+    /// Left rotation of the half after the S-boxes, in bits.
+    pub rotation: u32,
+}
+
+impl Default for ToyConfig {
+    fn default() -> ToyConfig {
+        ToyConfig { half_bits: 8, rounds: 4, sbox: DEFAULT_SBOX, rotation: 3 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    InvalidHalfBits,
+    InvalidSbox,
+    NoRounds,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidHalfBits => write!(f, "half size should be a multiple of 4 from 4 to 32 bits"),
+            ConfigError::InvalidSbox => write!(f, "S-box entries should be 4-bit values"),
+            ConfigError::NoRounds => write!(f, "at least one round is needed"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The toy round function for one configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToyRound {
+    half_bits: u32,
+    sbox: [u8; 16],
+    rotation: u32,
+}
+
+impl ToyRound {
+    fn mask(&self) -> u32 {
+        u32::MAX >> (32 - self.half_bits)
+    }
+}
+
+impl RoundFunction<u32, u32> for ToyRound {
+    fn apply(&self, half: &u32, sub_key: &u32) -> u32 {
+        let x = half ^ sub_key;
+        let mut y = 0;
+        for i in (0..self.half_bits).step_by(4) {
+            y |= (self.sbox[(x >> i & 0xf) as usize] as u32) << i;
+        }
+        let rotation = self.rotation % self.half_bits;
+        if rotation == 0 {
+            y
+        } else {
+            (y << rotation | y >> (self.half_bits - rotation)) & self.mask()
+        }
+    }
+}
+
+/// Round key `i` (0-based) is the low `half_bits` bits of the key rotated right by `5·i` bits.
+pub fn sub_keys(key: u64, half_bits: u32, rounds: usize) -> Vec<u32> {
+    let mask = u32::MAX >> (32 - half_bits);
+    (0..rounds).map(|i| key.rotate_right(5 * i as u32) as u32 & mask).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Toy {
+    config: ToyConfig,
+    feistel: Feistel<u32, u32, ToyRound>,
+}
+
+impl Toy {
+    pub fn new(config: ToyConfig, key: u64) -> Result<Toy, ConfigError> {
+        if !(4..=32).contains(&config.half_bits) || config.half_bits & 3 != 0 {
+            return Err(ConfigError::InvalidHalfBits);
+        }
+        if config.sbox.iter().any(|&y| y > 0xf) {
+            return Err(ConfigError::InvalidSbox);
+        }
+        if config.rounds == 0 {
+            return Err(ConfigError::NoRounds);
+        }
+        let round = ToyRound { half_bits: config.half_bits, sbox: config.sbox, rotation: config.rotation };
+        let schedule = |key: &u64, rounds: usize| sub_keys(*key, config.half_bits, rounds);
+        Ok(Toy { config, feistel: Feistel::new(&key, config.rounds, &schedule, round) })
+    }
+
+    pub fn config(&self) -> &ToyConfig {
+        &self.config
+    }
+
+    pub fn sub_keys(&self) -> &[u32] {
+        self.feistel.sub_keys()
+    }
+
+    /// F of this cipher, for following a value through the rounds by hand.
+    pub fn round_function(&self, half: u32, sub_key: u32) -> u32 {
+        self.feistel.round_function().apply(&half, &sub_key)
+    }
+
+    /// Blocks are `2 * half_bits` bits, L in the upper half. Higher bits are ignored.
+    pub fn encrypt_block(&self, block: u64) -> u64 {
+        let (l, r) = self.split(block);
+        let (l, r) = self.feistel.encrypt(l, r);
+        self.join(l, r)
+    }
+
+    pub fn decrypt_block(&self, block: u64) -> u64 {
+        let (l, r) = self.split(block);
+        let (l, r) = self.feistel.decrypt(l, r);
+        self.join(l, r)
+    }
+
+    fn split(&self, block: u64) -> (u32, u32) {
+        let mask = u32::MAX >> (32 - self.config.half_bits);
+        ((block >> self.config.half_bits) as u32 & mask, block as u32 & mask)
+    }
+
+    fn join(&self, l: u32, r: u32) -> u64 {
+        (l as u64) << self.config.half_bits | r as u64
+    }
+}
+
+/// `table[dx][dy]` counts the `x` with `S(x) ⊕ S(x ⊕ dx) = dy`.
+pub fn difference_distribution_table(sbox: &[u8; 16]) -> [[u8; 16]; 16] {
+    let mut result = [[0; 16]; 16];
+    for (dx, row) in result.iter_mut().enumerate() {
+        for x in 0..16 {
+            row[(sbox[x] ^ sbox[x ^ dx]) as usize & 0xf] += 1;
+        }
+    }
+    result
+}
+
+/// `table[a][b]` is the number of `x` with `a·x = b·S(x)`, minus 8.
+pub fn linear_approximation_table(sbox: &[u8; 16]) -> [[i8; 16]; 16] {
+    let mut result = [[-8; 16]; 16];
+    for (a, row) in result.iter_mut().enumerate() {
+        for (b, entry) in row.iter_mut().enumerate() {
+            for (x, &y) in sbox.iter().enumerate() {
+                if (a & x).count_ones() % 2 == (b & y as usize).count_ones() % 2 {
+                    *entry += 1;
+                }
+            }
+        }
+    }
+    result
+}
@@ -0,0 +1,63 @@
+use rand::{thread_rng, Rng};
+
+use super::*;
+
+#[test]
+fn test_config() {
+    assert!(Toy::new(ToyConfig::default(), 0).is_ok());
+    assert_eq!(Toy::new(ToyConfig { half_bits: 6, ..ToyConfig::default() }, 0).unwrap_err(), ConfigError::InvalidHalfBits);
+    assert_eq!(Toy::new(ToyConfig { half_bits: 36, ..ToyConfig::default() }, 0).unwrap_err(), ConfigError::InvalidHalfBits);
+    assert_eq!(Toy::new(ToyConfig { rounds: 0, ..ToyConfig::default() }, 0).unwrap_err(), ConfigError::NoRounds);
+    let mut sbox = DEFAULT_SBOX;
+    sbox[3] = 16;
+    assert_eq!(Toy::new(ToyConfig { sbox, ..ToyConfig::default() }, 0).unwrap_err(), ConfigError::InvalidSbox);
+}
+
+#[test]
+fn test_round_function() {
+    let toy = Toy::new(ToyConfig::default(), 0).unwrap();
+    // 0x12 ^ 0x30 = 0x22, S gives 0xdd, rotated left by 3 in 8 bits: 0xee.
+    assert_eq!(toy.round_function(0x12, 0x30), 0xee);
+    let one_round = Toy::new(ToyConfig { rounds: 1, ..ToyConfig::default() }, 0x30).unwrap();
+    assert_eq!(one_round.sub_keys(), &[0x30]);
+    // (L, R) = (0xab, 0x12) becomes (R1, L1) = (0xab ^ 0xee, 0x12).
+    assert_eq!(one_round.encrypt_block(0xab12), 0x4512);
+}
+
+#[test]
+fn test_codebook_is_permutation() {
+    let toy = Toy::new(ToyConfig::default(), 0x0123456789abcdef).unwrap();
+    let mut seen = vec![false; 1 << 16];
+    for block in 0..1 << 16 {
+        let c = toy.encrypt_block(block);
+        assert!(!seen[c as usize]);
+        seen[c as usize] = true;
+        assert_eq!(toy.decrypt_block(c), block);
+    }
+}
+
+#[test]
+fn test_sizes() {
+    let mut rng = thread_rng();
+    for half_bits in (4..=32).step_by(4) {
+        let config = ToyConfig { half_bits, rounds: 6, sbox: [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7], rotation: 5 };
+        let toy = Toy::new(config, rng.gen()).unwrap();
+        for _ in 0..100 {
+            let block = rng.gen::<u64>() >> (64 - 2 * half_bits);
+            let c = toy.encrypt_block(block);
+            assert_eq!(c.checked_shr(2 * half_bits).unwrap_or(0), 0);
+            assert_eq!(toy.decrypt_block(c), block);
+        }
+    }
+}
+
+#[test]
+fn test_tables() {
+    let ddt = difference_distribution_table(&DEFAULT_SBOX);
+    assert_eq!(ddt[0][0], 16);
+    assert!(ddt.iter().all(|row| row.iter().map(|&n| n as u32).sum::<u32>() == 16));
+    let lat = linear_approximation_table(&DEFAULT_SBOX);
+    assert_eq!(lat[0][0], 8);
+    assert!(lat[0][1..].iter().all(|&n| n == 0));
+    assert!(lat[1..].iter().all(|row| row[0] == 0));
+}
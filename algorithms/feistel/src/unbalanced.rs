@@ -0,0 +1,55 @@
+//! Unbalanced Feistel networks on blocks of up to 64 bits.
+//!
+//! The block is split into a source part of `source` bits and a target part of the rest. A round
+//! XORs F(source, K) into the target and then rotates the block so that the old source becomes the
+//! lowest bits, making the updated target the next source. With `source` at half the width this
+//! is a balanced network; a small source gives a target-heavy network and a large one a
+//! source-heavy network.
+
+use crate::RoundFunction;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone)]
+pub struct UnbalancedFeistel<K, F> {
+    width: u32,
+    source: u32,
+    sub_keys: Vec<K>,
+    f: F,
+}
+
+impl<K, F: RoundFunction<u64, K>> UnbalancedFeistel<K, F> {
+    /// A network on `width`-bit blocks whose source part is the top `source` bits. F gets the
+    /// source right-aligned and its output is cut to the target width.
+    pub fn new(width: u32, source: u32, sub_keys: Vec<K>, f: F) -> UnbalancedFeistel<K, F> {
+        assert!(width <= 64 && 0 < source && source < width, "0 < source < width <= 64");
+        UnbalancedFeistel { width, source, sub_keys, f }
+    }
+
+    fn mask(bits: u32) -> u64 {
+        u64::MAX >> (64 - bits)
+    }
+
+    pub fn encrypt(&self, block: u64) -> u64 {
+        let target = self.width - self.source;
+        let mut x = block & UnbalancedFeistel::<K, F>::mask(self.width);
+        for k in &self.sub_keys {
+            let a = x >> target;
+            let b = (x ^ self.f.apply(&a, k)) & UnbalancedFeistel::<K, F>::mask(target);
+            x = b << self.source | a;
+        }
+        x
+    }
+
+    pub fn decrypt(&self, block: u64) -> u64 {
+        let target = self.width - self.source;
+        let mut x = block & UnbalancedFeistel::<K, F>::mask(self.width);
+        for k in self.sub_keys.iter().rev() {
+            let a = x & UnbalancedFeistel::<K, F>::mask(self.source);
+            let b = (x >> self.source ^ self.f.apply(&a, k)) & UnbalancedFeistel::<K, F>::mask(target);
+            x = a << target | b;
+        }
+        x
+    }
+}
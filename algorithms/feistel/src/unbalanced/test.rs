@@ -0,0 +1,27 @@
+use rand::{thread_rng, Rng};
+
+use super::*;
+
+#[test]
+fn test_inverse() {
+    let mut rng = thread_rng();
+    let f = |a: &u64, k: &u64| a.wrapping_mul(0x9e3779b97f4a7c15) ^ k;
+    for &(width, source) in &[(64, 32), (64, 8), (64, 56), (24, 3), (13, 7)] {
+        let sub_keys: Vec<u64> = (0..10).map(|_| rng.gen()).collect();
+        let network = UnbalancedFeistel::new(width, source, sub_keys, f);
+        for _ in 0..100 {
+            let block = rng.gen::<u64>() >> (64 - width);
+            let c = network.encrypt(block);
+            assert_eq!(c >> (width - 1) >> 1, 0);
+            assert_eq!(network.decrypt(c), block);
+        }
+    }
+}
+
+#[test]
+fn test_round() {
+    // 8-bit blocks with a 2-bit source: 0b10_110011 -> target 0b110011 ^ 0b10 = 0b110001, then the
+    // source moves to the bottom.
+    let network = UnbalancedFeistel::new(8, 2, vec![0u64], |a: &u64, _: &u64| *a);
+    assert_eq!(network.encrypt(0b10110011), 0b11000110);
+}
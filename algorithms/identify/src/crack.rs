@@ -0,0 +1,100 @@
+//! Ciphertext-only key search for the ciphers implemented in this repository.
+//!
+//! Each routine decrypts with the matching library (`caesar`, `scytale`, `transposition`), tries
+//! every key in its search space and keeps the decryption whose letter bigrams read most
+//! like English.
+
+use crate::english::bigram_log_probabilities;
+use crate::{best_caesar_shift, bigram_score, bigram_score_with, letter_indices};
+
+#[cfg(test)]
+mod test;
+
+/// Largest transposition period tried by default (7! = 5040 keys).
+pub const DEFAULT_MAX_PERIOD: usize = 7;
+
+/// Largest transposition period the command line accepts: all 9! = 362880 keys of a period are
+/// held in memory at once.
+pub const MAX_PERIOD: usize = 9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crack<K> {
+    pub key: K,
+    pub plaintext: String,
+    /// Average log probability per bigram of the plaintext, see [`bigram_score`].
+    pub score: f64,
+}
+
+/// Recovers the shift of a Caesar ciphertext made of lowercase letters, as the `caesar` tool
+/// produces. Returns `None` if the text has other characters or no letters at all.
+pub fn caesar(text: &str) -> Option<Crack<caesar::Key>> {
+    let text = text.trim_end();
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let (shift, _) = best_caesar_shift(&letter_indices(text));
+    let key = caesar::Key::new(shift);
+    let plaintext = caesar::decrypt(text, key).ok()?;
+    let score = bigram_score(&plaintext);
+    Some(Crack { key, plaintext, score })
+}
+
+/// Recovers the key of a scytale ciphertext. The `scytale` tool pads its output to a multiple of
+/// the key, so only divisors of the length are tried.
+pub fn scytale(text: &str) -> Option<Crack<scytale::Key>> {
+    let input: Vec<char> = text.trim_end_matches(&['\r', '\n'][..]).chars().collect();
+    let table = bigram_log_probabilities();
+    let mut output = vec!['\0'; input.len()];
+    (2..=input.len() / 2)
+        .filter(|columns| input.len().is_multiple_of(*columns))
+        .map(|columns| {
+            let key = scytale::Key::new(columns).unwrap();
+            scytale::decrypt_into(&input, key, &mut output).unwrap();
+            let plaintext: String = output.iter().collect();
+            let score = bigram_score_with(&table, &plaintext);
+            Crack { key, plaintext, score }
+        })
+        .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap())
+}
+
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    fn inner(current: &mut Vec<usize>, used: &mut Vec<bool>, result: &mut Vec<Vec<usize>>) {
+        if current.len() == used.len() {
+            result.push(current.clone());
+            return;
+        }
+        for i in 0..used.len() {
+            if !used[i] {
+                used[i] = true;
+                current.push(i);
+                inner(current, used, result);
+                current.pop();
+                used[i] = false;
+            }
+        }
+    }
+    let mut result = Vec::new();
+    inner(&mut Vec::with_capacity(n), &mut vec![false; n], &mut result);
+    result
+}
+
+/// Recovers the key of a transposition ciphertext by trying every permutation of every period
+/// from 2 to `max_period`.
+pub fn transposition(text: &str, max_period: usize) -> Option<Crack<transposition::Key>> {
+    let input: Vec<char> = text.trim_end_matches(&['\r', '\n'][..]).chars().collect();
+    let table = bigram_log_probabilities();
+    let mut output = vec!['\0'; input.len()];
+    let mut best: Option<Crack<transposition::Key>> = None;
+    for period in 2..=max_period.min(input.len()) {
+        for permutation in permutations(period) {
+            let key = transposition::Key::from_permutation(permutation).unwrap();
+            transposition::decrypt_into(&input, &key, &mut output).unwrap();
+            let plaintext: String = output.iter().collect();
+            let score = bigram_score_with(&table, &plaintext);
+            if best.as_ref().map(|best| score > best.score).unwrap_or(true) {
+                best = Some(Crack { key, plaintext, score });
+            }
+        }
+    }
+    best
+}
@@ -0,0 +1,33 @@
+use crate::test::{caesar_encrypt, scytale_encrypt, transposition_encrypt, SAMPLE};
+
+use super::*;
+
+#[test]
+fn test_caesar() {
+    let cipher = caesar_encrypt(SAMPLE, 7);
+    let crack = caesar(&cipher).unwrap();
+    assert_eq!(crack.key, caesar::Key::new(7));
+    assert_eq!(crack.plaintext, caesar_encrypt(SAMPLE, 0));
+    assert!(caesar("Abc").is_none());
+    assert!(caesar("").is_none());
+}
+
+#[test]
+fn test_scytale() {
+    let crack = scytale(&scytale_encrypt(SAMPLE, 6)).unwrap();
+    assert_eq!(crack.key.columns(), 6);
+    assert!(crack.plaintext.starts_with(SAMPLE));
+}
+
+#[test]
+fn test_permutations() {
+    assert_eq!(permutations(3).len(), 6);
+    assert!(permutations(4).iter().all(|p| transposition::Key::from_permutation(p.clone()).is_ok()));
+}
+
+#[test]
+fn test_transposition() {
+    let crack = transposition(&transposition_encrypt(SAMPLE, &[3, 1, 4, 0, 2]), 6).unwrap();
+    assert_eq!(crack.plaintext, SAMPLE);
+    assert_eq!(crack.key.len() % 5, 0);
+}
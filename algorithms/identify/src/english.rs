@@ -0,0 +1,53 @@
+//! Reference statistics of English text, for lowercase letters `a`–`z`.
+
+/// Relative letter frequencies.
+pub const LETTER_FREQUENCY: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Index of coincidence of English text.
+pub const IC: f64 = 0.0667;
+
+/// Index of coincidence of uniformly random letters (1/26).
+pub const RANDOM_IC: f64 = 1.0 / 26.0;
+
+/// Relative frequencies of the most common bigrams. Other bigrams are estimated from the letter
+/// frequencies in [`bigram_log_probability`].
+const COMMON_BIGRAMS: [(&str, f64); 50] = [
+    ("th", 0.0356), ("he", 0.0307), ("in", 0.0243), ("er", 0.0205), ("an", 0.0199),
+    ("re", 0.0185), ("on", 0.0176), ("at", 0.0149), ("en", 0.0145), ("nd", 0.0135),
+    ("ti", 0.0134), ("es", 0.0134), ("or", 0.0128), ("te", 0.0120), ("of", 0.0117),
+    ("ed", 0.0117), ("is", 0.0113), ("it", 0.0112), ("al", 0.0109), ("ar", 0.0107),
+    ("st", 0.0105), ("to", 0.0104), ("nt", 0.0104), ("ng", 0.0095), ("se", 0.0093),
+    ("ha", 0.0093), ("as", 0.0087), ("ou", 0.0087), ("io", 0.0083), ("le", 0.0083),
+    ("ve", 0.0083), ("co", 0.0079), ("me", 0.0079), ("de", 0.0076), ("hi", 0.0076),
+    ("ri", 0.0073), ("ro", 0.0073), ("ic", 0.0070), ("ne", 0.0069), ("ea", 0.0069),
+    ("ra", 0.0069), ("ce", 0.0065), ("li", 0.0062), ("ch", 0.0060), ("ll", 0.0058),
+    ("be", 0.0058), ("ma", 0.0057), ("si", 0.0055), ("om", 0.0055), ("ur", 0.0054),
+];
+
+/// Table of [`bigram_log_probability`] indexed by the two letters.
+pub fn bigram_log_probabilities() -> [[f64; 26]; 26] {
+    let mut result = [[0.0; 26]; 26];
+    for (a, row) in result.iter_mut().enumerate() {
+        for (b, value) in row.iter_mut().enumerate() {
+            *value = bigram_log_probability(a as u8, b as u8);
+        }
+    }
+    result
+}
+
+/// Natural logarithm of the probability of bigram `ab` (letters given as 0–25).
+///
+/// Bigrams outside the common list get half of the probability they would have if letters were
+/// independent, which is a rough but serviceable model for ranking candidate decryptions.
+pub fn bigram_log_probability(a: u8, b: u8) -> f64 {
+    let bigram = [b'a' + a, b'a' + b];
+    COMMON_BIGRAMS.iter()
+        .find(|(s, _)| s.as_bytes() == bigram)
+        .map(|(_, p)| *p)
+        .unwrap_or_else(|| LETTER_FREQUENCY[a as usize] * LETTER_FREQUENCY[b as usize] * 0.5)
+        .ln()
+}
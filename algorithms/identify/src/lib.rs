@@ -0,0 +1,363 @@
+//! Guesses which classical cipher family produced a ciphertext.
+//!
+//! The guess is built from a handful of statistics (see [`Statistics`]) which are compared with
+//! what each family is expected to leave behind:
+//!
+//! * substitution (Caesar) keeps the index of coincidence (IC) and the shape of the letter
+//!   frequency curve of English, but moves the letters around,
+//! * transposition and scytale keep the letters themselves, so the frequencies match English
+//!   letter by letter, only their order changes,
+//! * Vigenère flattens the frequencies, but each column of the right period looks like English,
+//! * Playfair works on pairs: even length, never the same letter twice in a pair, no `j`,
+//! * a random stream (or a modern cipher's hex output) is flat at every period.
+
+use std::fmt;
+
+pub mod crack;
+pub mod english;
+
+#[cfg(test)]
+mod test;
+
+/// Minimum number of letters for the statistics to mean anything.
+pub const MIN_LETTERS: usize = 40;
+
+const MAX_PERIOD: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Family {
+    Substitution,
+    Vigenere,
+    Transposition,
+    Scytale,
+    Playfair,
+    Random,
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Family::Substitution => "Caesar/substitution",
+            Family::Vigenere => "Vigenere",
+            Family::Transposition => "transposition",
+            Family::Scytale => "scytale",
+            Family::Playfair => "Playfair",
+            Family::Random => "random stream",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Statistics of a ciphertext used to identify its family.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    /// Number of characters (not bytes) in the text.
+    pub length: usize,
+    /// Number of letters `a`–`z` regardless of case.
+    pub letters: usize,
+    pub ic: f64,
+    /// Distance of the letter frequencies from English, letter by letter.
+    pub english_distance: f64,
+    /// Distance of the frequencies sorted by size from English sorted the same way.
+    pub shape_distance: f64,
+    /// Caesar shift whose decryption is closest to English, with its distance.
+    pub best_shift: (usize, f64),
+    /// Average IC of the columns for every period from 1 to 20.
+    pub periodic_ic: Vec<(usize, f64)>,
+    /// Average natural log probability per letter bigram.
+    pub bigram_score: f64,
+    pub doubled_pairs: usize,
+    pub distinct_letters: usize,
+    pub has_j: bool,
+    pub has_uppercase: bool,
+    pub has_non_letters: bool,
+    /// Whole text (ignoring whitespace) consists of hexadecimal digits, with at least one digit.
+    pub is_hex: bool,
+}
+
+/// One ranked family with its share of the total score and the evidence behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Guess {
+    pub family: Family,
+    pub confidence: f64,
+    pub evidence: Vec<String>,
+}
+
+fn letter_indices(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect()
+}
+
+fn counts(letters: &[u8]) -> [usize; 26] {
+    let mut result = [0; 26];
+    for &l in letters {
+        result[l as usize] += 1;
+    }
+    result
+}
+
+pub fn index_of_coincidence(letters: &[u8]) -> f64 {
+    if letters.len() < 2 {
+        return 0.0;
+    }
+    let n = letters.len() as f64;
+    counts(letters).iter().map(|&c| (c * c.saturating_sub(1)) as f64).sum::<f64>() / (n * (n - 1.0))
+}
+
+/// Sum over letters of `(observed - expected)^2 / expected` on relative frequencies, which unlike
+/// the chi-squared statistic does not grow with the length of the text.
+fn frequency_distance(observed: &[f64; 26], expected: &[f64; 26]) -> f64 {
+    observed.iter().zip(expected.iter()).map(|(o, e)| (o - e) * (o - e) / e).sum()
+}
+
+fn frequencies(letters: &[u8]) -> [f64; 26] {
+    let mut result = [0.0; 26];
+    if letters.is_empty() {
+        return result;
+    }
+    for (r, &c) in result.iter_mut().zip(counts(letters).iter()) {
+        *r = c as f64 / letters.len() as f64;
+    }
+    result
+}
+
+fn shifted(frequencies: &[f64; 26], shift: usize) -> [f64; 26] {
+    let mut result = [0.0; 26];
+    for (i, r) in result.iter_mut().enumerate() {
+        *r = frequencies[(i + shift) % 26];
+    }
+    result
+}
+
+fn sorted(frequencies: &[f64; 26]) -> [f64; 26] {
+    let mut result = *frequencies;
+    result.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    result
+}
+
+/// Average natural log probability per bigram of the letters of `text`.
+pub fn bigram_score(text: &str) -> f64 {
+    bigram_score_with(&english::bigram_log_probabilities(), text)
+}
+
+fn bigram_score_with(table: &[[f64; 26]; 26], text: &str) -> f64 {
+    let letters = letter_indices(text);
+    if letters.len() < 2 {
+        return f64::NEG_INFINITY;
+    }
+    letters.windows(2).map(|w| table[w[0] as usize][w[1] as usize]).sum::<f64>() / (letters.len() - 1) as f64
+}
+
+/// Returns the shift `k` such that shifting every letter back by `k` is closest to English.
+pub fn best_caesar_shift(letters: &[u8]) -> (usize, f64) {
+    let frequencies = frequencies(letters);
+    (0..26)
+        .map(|shift| (shift, frequency_distance(&shifted(&frequencies, shift), &english::LETTER_FREQUENCY)))
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap()
+}
+
+pub fn periodic_ic(letters: &[u8], max_period: usize) -> Vec<(usize, f64)> {
+    (1..=max_period.min(letters.len() / 2).max(1))
+        .map(|period| {
+            let ic = (0..period)
+                .map(|column| {
+                    let column: Vec<_> = letters.iter().skip(column).step_by(period).copied().collect();
+                    index_of_coincidence(&column)
+                })
+                .sum::<f64>() / period as f64;
+            (period, ic)
+        })
+        .collect()
+}
+
+pub fn analyze(text: &str) -> Statistics {
+    let letters = letter_indices(text);
+    let frequencies = frequencies(&letters);
+    let mut english_sorted = english::LETTER_FREQUENCY;
+    english_sorted.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    Statistics {
+        length: text.chars().count(),
+        letters: letters.len(),
+        ic: index_of_coincidence(&letters),
+        english_distance: frequency_distance(&frequencies, &english::LETTER_FREQUENCY),
+        shape_distance: frequency_distance(&sorted(&frequencies), &english_sorted),
+        best_shift: best_caesar_shift(&letters),
+        periodic_ic: periodic_ic(&letters, MAX_PERIOD),
+        bigram_score: bigram_score(text),
+        doubled_pairs: letters.chunks(2).filter(|pair| pair.len() == 2 && pair[0] == pair[1]).count(),
+        distinct_letters: counts(&letters).iter().filter(|&&c| c != 0).count(),
+        has_j: letters.contains(&(b'j' - b'a')),
+        has_uppercase: text.chars().any(|c| c.is_ascii_uppercase()),
+        has_non_letters: text.trim_end().chars().any(|c| !c.is_ascii_alphabetic()),
+        is_hex: !compact.is_empty()
+            && compact.chars().all(|c| c.is_ascii_hexdigit())
+            && compact.chars().any(|c| c.is_ascii_digit()),
+    }
+}
+
+/// Bell-shaped closeness of `value` to `target`: 1 at the target, about 0.6 one tolerance away.
+fn closeness(value: f64, target: f64, tolerance: f64) -> f64 {
+    let x = (value - target) / tolerance;
+    (-x * x / 2.0).exp()
+}
+
+/// Like [`closeness`] but only penalizes values above `limit`.
+fn at_most(value: f64, limit: f64, tolerance: f64) -> f64 {
+    if value <= limit { 1.0 } else { closeness(value, limit, tolerance) }
+}
+
+struct Scorer {
+    score: f64,
+    evidence: Vec<String>,
+}
+
+impl Scorer {
+    fn new() -> Scorer {
+        Scorer { score: 1.0, evidence: Vec::new() }
+    }
+
+    fn factor(&mut self, factor: f64, evidence: String) {
+        self.score *= factor;
+        self.evidence.push(evidence);
+    }
+}
+
+/// Ranks every family by how well the statistics of `text` fit it.
+///
+/// `scytale_score` and `transposition_score` are the bigram scores of the best decryptions found
+/// by the crack routines, if they were run; a decryption that reads like English is strong evidence
+/// for that family over the other transposition-like one.
+///
+/// Text without letters has nothing to score, and its ranking is empty.
+pub fn rank(statistics: &Statistics, scytale_score: Option<f64>, transposition_score: Option<f64>) -> Vec<Guess> {
+    let s = statistics;
+    if s.letters == 0 {
+        return Vec::new();
+    }
+    let english_bigrams = english_bigram_score();
+    let best_periodic = s.periodic_ic.iter()
+        .filter(|(period, _)| *period > 1)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .copied()
+        .unwrap_or((1, s.ic));
+    let ic_english = closeness(s.ic, english::IC, 0.008);
+    let ic_random = closeness(s.ic, english::RANDOM_IC, 0.004);
+    // A sample of n letters drawn from English is about 25 / n away from it by chance alone.
+    let noise = 25.0 / s.letters.max(1) as f64;
+    let letters_match = at_most(s.english_distance, 0.15 + noise, 0.1 + noise);
+    let shape_match = at_most(s.shape_distance, 0.15 + noise, 0.1 + noise);
+    let read_like_english = |score: Option<f64>| score.map(|score| closeness(score, english_bigrams, 0.5)).unwrap_or(0.5);
+
+    let mut guesses = Vec::with_capacity(6);
+
+    let mut substitution = Scorer::new();
+    substitution.factor(ic_english, format!("IC {:.4} (English {:.4})", s.ic, english::IC));
+    substitution.factor(shape_match, format!("sorted frequency distance from English {:.3}", s.shape_distance));
+    substitution.factor(1.0 - letters_match * 0.9, format!("letter-by-letter distance from English {:.3}", s.english_distance));
+    substitution.factor(if s.has_uppercase || s.has_non_letters { 0.5 } else { 1.0 }, format!("non-lowercase characters present: {}", s.has_uppercase || s.has_non_letters));
+    substitution.evidence.push(format!("best Caesar shift {} (distance {:.3})", s.best_shift.0, s.best_shift.1));
+    guesses.push((Family::Substitution, substitution));
+
+    let mut vigenere = Scorer::new();
+    vigenere.factor(closeness(s.ic, 0.045, 0.008), format!("IC {:.4} between random and English", s.ic));
+    vigenere.factor(closeness(best_periodic.1, english::IC, 0.01), format!("period {} columns have average IC {:.4}", best_periodic.0, best_periodic.1));
+    vigenere.factor(1.0 - letters_match * 0.9, format!("letter-by-letter distance from English {:.3}", s.english_distance));
+    guesses.push((Family::Vigenere, vigenere));
+
+    let scytale_fit = read_like_english(scytale_score);
+    let transposition_fit = read_like_english(transposition_score);
+
+    let mut transposition = Scorer::new();
+    transposition.factor(ic_english, format!("IC {:.4} (English {:.4})", s.ic, english::IC));
+    transposition.factor(letters_match, format!("letter-by-letter distance from English {:.3}", s.english_distance));
+    transposition.factor(1.0 - closeness(s.bigram_score, english_bigrams, 0.3) * 0.9, format!("bigram score {:.3} (English {:.3})", s.bigram_score, english_bigrams));
+    transposition.factor(0.5 + (transposition_fit - scytale_fit) / 2.0, match transposition_score {
+        Some(score) => format!("best transposition decryption bigram score {:.3}", score),
+        None => "transposition decryption not tried".to_string(),
+    });
+    guesses.push((Family::Transposition, transposition));
+
+    let mut scytale = Scorer::new();
+    scytale.factor(ic_english, format!("IC {:.4} (English {:.4})", s.ic, english::IC));
+    scytale.factor(letters_match, format!("letter-by-letter distance from English {:.3}", s.english_distance));
+    scytale.factor(1.0 - closeness(s.bigram_score, english_bigrams, 0.3) * 0.9, format!("bigram score {:.3} (English {:.3})", s.bigram_score, english_bigrams));
+    scytale.factor(0.5 + (scytale_fit - transposition_fit) / 2.0, match scytale_score {
+        Some(score) => format!("best scytale decryption bigram score {:.3}", score),
+        None => "scytale decryption not tried".to_string(),
+    });
+    guesses.push((Family::Scytale, scytale));
+
+    let mut playfair = Scorer::new();
+    playfair.factor(if s.letters.is_multiple_of(2) { 1.0 } else { 0.01 }, format!("letter count {} is {}", s.letters, if s.letters.is_multiple_of(2) { "even" } else { "odd" }));
+    playfair.factor(if s.doubled_pairs == 0 { 1.0 } else { 0.01 }, format!("{} pairs with the same letter twice", s.doubled_pairs));
+    playfair.factor(if s.has_j { 0.05 } else { 1.0 }, format!("contains j: {}", s.has_j));
+    playfair.factor(if s.distinct_letters <= 25 { 1.0 } else { 0.01 }, format!("{} distinct letters", s.distinct_letters));
+    playfair.factor(closeness(s.ic, 0.052, 0.008), format!("IC {:.4}", s.ic));
+    guesses.push((Family::Playfair, playfair));
+
+    let mut random = Scorer::new();
+    if s.is_hex {
+        random.factor(1.0, "text is entirely hexadecimal digits, like the output of the des and aes tools".to_string());
+    } else {
+        random.factor(ic_random, format!("IC {:.4} (random {:.4})", s.ic, english::RANDOM_IC));
+        random.factor(1.0 - closeness(best_periodic.1, english::IC, 0.01), format!("best periodic IC {:.4} at period {}", best_periodic.1, best_periodic.0));
+    }
+    guesses.push((Family::Random, random));
+
+    if s.is_hex {
+        for (family, scorer) in guesses.iter_mut() {
+            if *family != Family::Random {
+                scorer.factor(0.01, "hexadecimal text is unlikely for a classical cipher".to_string());
+            }
+        }
+    }
+    if s.letters < MIN_LETTERS {
+        for (_, scorer) in guesses.iter_mut() {
+            scorer.evidence.push(format!("only {} letters, statistics are unreliable", s.letters));
+        }
+    }
+
+    let total: f64 = guesses.iter().map(|(_, scorer)| scorer.score).sum();
+    let mut result: Vec<_> = guesses.into_iter()
+        .map(|(family, scorer)| Guess {
+            family,
+            confidence: if total > 0.0 { scorer.score / total } else { 0.0 },
+            evidence: scorer.evidence,
+        })
+        .collect();
+    result.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+    result
+}
+
+/// Bigram score expected of English text under [`english::bigram_log_probability`].
+pub fn english_bigram_score() -> f64 {
+    let mut score = 0.0;
+    let mut total = 0.0;
+    for a in 0..26 {
+        for b in 0..26 {
+            let p = english::bigram_log_probability(a, b).exp();
+            score += p * p.ln();
+            total += p;
+        }
+    }
+    score / total
+}
+
+/// Analyzes `text`, runs the scytale and transposition crack routines when the letters look like
+/// English in some order, and ranks the families. The ranking is empty if `text` has no letters.
+pub fn identify(text: &str) -> Vec<Guess> {
+    let statistics = analyze(text);
+    let (scytale_score, transposition_score) = if !statistics.is_hex && statistics.english_distance < 0.5 {
+        (
+            crack::scytale(text).map(|c| c.score),
+            crack::transposition(text, crack::DEFAULT_MAX_PERIOD).map(|c| c.score),
+        )
+    } else {
+        (None, None)
+    };
+    rank(&statistics, scytale_score, transposition_score)
+}
@@ -0,0 +1,67 @@
+use std::io::Read;
+
+use clap::{App, Arg};
+
+use identify::{crack, identify, Family};
+
+fn main() {
+    let matches = App::new("identify")
+        .about("Estimate which classical cipher produced a ciphertext")
+        .arg(Arg::with_name("crack")
+            .short("c")
+            .long("crack")
+            .help("flag to run the crack routine of the best guess(caesar, scytale or transposition)"))
+        .arg(Arg::with_name("period")
+            .short("p")
+            .long("max-period")
+            .help("largest transposition period tried when cracking")
+            .takes_value(true)
+            .default_value("7"))
+        .arg(Arg::with_name("input")
+            .help("ciphertext to identify"))
+        .get_matches();
+    let input = matches.value_of("input").map(str::to_string).unwrap_or_else(|| {
+        let mut s = String::new();
+        std::io::stdin().read_to_string(&mut s).expect("failed to read standard input");
+        s
+    });
+    let max_period = match matches.value_of("period").unwrap().parse() {
+        Ok(period) if period <= crack::MAX_PERIOD => period,
+        Ok(_) => {
+            eprintln!("argument 'max-period' should be at most {}", crack::MAX_PERIOD);
+            return;
+        }
+        Err(_) => {
+            eprintln!("argument 'max-period' should be number");
+            return;
+        }
+    };
+    let guesses = identify(&input);
+    if guesses.is_empty() {
+        eprintln!("input has no letters to identify");
+        return;
+    }
+    for (i, guess) in guesses.iter().enumerate() {
+        println!("{}. {} ({:.1}%)", i + 1, guess.family, guess.confidence * 100.0);
+        for evidence in &guess.evidence {
+            println!("    {}", evidence);
+        }
+    }
+    if matches.is_present("crack") {
+        match guesses[0].family {
+            Family::Substitution => match crack::caesar(&input) {
+                Some(result) => println!("caesar key: {}\n{}", result.key.shift(), result.plaintext),
+                None => eprintln!("caesar crack needs lowercase letters only"),
+            },
+            Family::Scytale => match crack::scytale(&input) {
+                Some(result) => println!("scytale key: {}\n{}", result.key.columns(), result.plaintext),
+                None => eprintln!("input is too short for scytale crack"),
+            },
+            Family::Transposition => match crack::transposition(&input, max_period) {
+                Some(result) => println!("transposition key: {}\n{}", result.key, result.plaintext),
+                None => eprintln!("input is too short for transposition crack"),
+            },
+            family => eprintln!("no crack routine for {}", family),
+        }
+    }
+}
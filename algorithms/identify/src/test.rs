@@ -0,0 +1,188 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use super::*;
+
+pub(crate) const SAMPLE: &str = "the history of secret writing is as old as writing itself and for most of that time \
+the art of hiding a message relied on keeping the method secret rather than the key a general who sent orders \
+to his officers trusted that the enemy would not guess how the letters had been shuffled or replaced and \
+the simple schemes of the ancient world worked well enough while few people could read at all when the \
+study of letter frequencies spread among scholars every cipher that replaced one letter with another became \
+easy to break because the most common symbol in a long message almost always stands for the letter e and \
+the rest of the alphabet follows in a predictable order this is why later inventors moved to systems that \
+change the alphabet from one letter to the next or that mix whole pairs of letters at once";
+
+pub(crate) fn caesar_encrypt(text: &str, key: u8) -> String {
+    let letters: String = text.chars().filter(char::is_ascii_lowercase).collect();
+    caesar::encrypt(&letters, caesar::Key::new(key as usize)).unwrap()
+}
+
+pub(crate) fn scytale_encrypt(text: &str, key: usize) -> String {
+    scytale::encrypt(text, scytale::Key::new(key).unwrap()).unwrap()
+}
+
+pub(crate) fn transposition_encrypt(text: &str, key: &[usize]) -> String {
+    transposition::encrypt(text, &transposition::Key::from_permutation(key.to_vec()).unwrap()).unwrap()
+}
+
+fn vigenere_encrypt(text: &str, key: &str) -> String {
+    let key = key.as_bytes();
+    text.chars()
+        .filter(char::is_ascii_lowercase)
+        .enumerate()
+        .map(|(i, c)| ((c as u8 - b'a' + key[i % key.len()] - b'a') % 26 + b'a') as char)
+        .collect()
+}
+
+fn playfair_encrypt(text: &str, key: &str) -> String {
+    let mut square = Vec::with_capacity(25);
+    for c in key.chars().chain('a'..='z') {
+        let c = if c == 'j' { 'i' } else { c };
+        if !square.contains(&c) {
+            square.push(c);
+        }
+    }
+    let position = |c: char| {
+        let i = square.iter().position(|&s| s == c).unwrap();
+        (i / 5, i % 5)
+    };
+    let letters: Vec<char> = text.chars().filter(char::is_ascii_lowercase).map(|c| if c == 'j' { 'i' } else { c }).collect();
+    let mut pairs = Vec::new();
+    let mut i = 0;
+    while i < letters.len() {
+        let a = letters[i];
+        let b = letters.get(i + 1).copied();
+        match b {
+            Some(b) if b != a => {
+                pairs.push((a, b));
+                i += 2;
+            }
+            _ => {
+                pairs.push((a, if a == 'x' { 'q' } else { 'x' }));
+                i += 1;
+            }
+        }
+    }
+    let mut result = String::with_capacity(pairs.len() * 2);
+    for (a, b) in pairs {
+        let ((ra, ca), (rb, cb)) = (position(a), position(b));
+        let (a, b) = if ra == rb {
+            ((ra, (ca + 1) % 5), (rb, (cb + 1) % 5))
+        } else if ca == cb {
+            (((ra + 1) % 5, ca), ((rb + 1) % 5, cb))
+        } else {
+            ((ra, cb), (rb, ca))
+        };
+        result.push(square[a.0 * 5 + a.1]);
+        result.push(square[b.0 * 5 + b.1]);
+    }
+    result
+}
+
+fn top(text: &str) -> Family {
+    identify(text)[0].family
+}
+
+#[test]
+fn test_index_of_coincidence() {
+    assert_eq!(index_of_coincidence(&[0, 0, 0, 0]), 1.0);
+    assert_eq!(index_of_coincidence(&[0, 1, 2, 3]), 0.0);
+    assert_eq!(index_of_coincidence(&[0]), 0.0);
+    let english = index_of_coincidence(&letter_indices(SAMPLE));
+    assert!((english - english::IC).abs() < 0.01, "{}", english);
+}
+
+#[test]
+fn test_best_caesar_shift() {
+    assert_eq!(best_caesar_shift(&letter_indices(SAMPLE)).0, 0);
+    assert_eq!(best_caesar_shift(&letter_indices(&caesar_encrypt(SAMPLE, 3))).0, 3);
+    assert_eq!(best_caesar_shift(&letter_indices(&caesar_encrypt(SAMPLE, 17))).0, 17);
+}
+
+#[test]
+fn test_periodic_ic() {
+    let ic = periodic_ic(&letter_indices(&vigenere_encrypt(SAMPLE, "lemon")), 10);
+    assert_eq!(ic.len(), 10);
+    let best = ic.iter().max_by(|a, b| a.1.partial_cmp(&b.1).unwrap()).unwrap();
+    assert_eq!(best.0 % 5, 0);
+    assert!(ic[0].1 < 0.055);
+}
+
+#[test]
+fn test_bigram_score() {
+    let english = bigram_score(SAMPLE);
+    assert!(english > bigram_score(&caesar_encrypt(SAMPLE, 3)));
+    assert!(english > bigram_score(&transposition_encrypt(SAMPLE, &[2, 0, 3, 1])));
+    assert!((english - english_bigram_score()).abs() < 0.5, "{} {}", english, english_bigram_score());
+}
+
+#[test]
+fn test_analyze() {
+    let statistics = analyze("Ab,cd ef");
+    assert_eq!(statistics.length, 8);
+    assert_eq!(statistics.letters, 6);
+    assert!(statistics.has_uppercase);
+    assert!(statistics.has_non_letters);
+    assert!(!statistics.is_hex);
+    assert_eq!(statistics.distinct_letters, 6);
+    assert_eq!(analyze("aabbca").doubled_pairs, 2);
+    assert!(analyze("0123 abcd").is_hex);
+    assert!(!analyze("abcdef").is_hex);
+    assert!(analyze("jam").has_j);
+}
+
+#[test]
+fn test_identify_substitution() {
+    assert_eq!(top(&caesar_encrypt(SAMPLE, 3)), Family::Substitution);
+    assert_eq!(top(&caesar_encrypt(SAMPLE, 11)), Family::Substitution);
+}
+
+#[test]
+fn test_identify_vigenere() {
+    assert_eq!(top(&vigenere_encrypt(SAMPLE, "lemon")), Family::Vigenere);
+    assert_eq!(top(&vigenere_encrypt(SAMPLE, "cipher")), Family::Vigenere);
+}
+
+#[test]
+fn test_identify_transposition() {
+    assert_eq!(top(&transposition_encrypt(SAMPLE, &[2, 0, 3, 1])), Family::Transposition);
+    assert_eq!(top(&transposition_encrypt(SAMPLE, &[4, 2, 0, 1, 3])), Family::Transposition);
+}
+
+#[test]
+fn test_identify_scytale() {
+    assert_eq!(top(&scytale_encrypt(SAMPLE, 8)), Family::Scytale);
+    assert_eq!(top(&scytale_encrypt(SAMPLE, 13)), Family::Scytale);
+}
+
+#[test]
+fn test_identify_playfair() {
+    assert_eq!(top(&playfair_encrypt(SAMPLE, "monarchy")), Family::Playfair);
+    assert_eq!(top(&playfair_encrypt(SAMPLE, "keyword")), Family::Playfair);
+}
+
+#[test]
+fn test_identify_random() {
+    let mut rng = StdRng::seed_from_u64(27);
+    let letters: String = (0..600).map(|_| (b'a' + rng.gen_range(0, 26)) as char).collect();
+    assert_eq!(top(&letters), Family::Random);
+    let hex: String = (0..64).map(|_| format!("{:02x}", rng.gen::<u8>())).collect();
+    assert_eq!(top(&hex), Family::Random);
+}
+
+#[test]
+fn test_identify_confidence() {
+    let guesses = identify(&caesar_encrypt(SAMPLE, 3));
+    assert_eq!(guesses.len(), 6);
+    assert!((guesses.iter().map(|g| g.confidence).sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(guesses.windows(2).all(|w| w[0].confidence >= w[1].confidence));
+    assert!(guesses.iter().all(|g| !g.evidence.is_empty()));
+    assert!(identify("abc")[0].evidence.iter().any(|e| e.contains("unreliable")));
+}
+
+#[test]
+fn test_identify_no_letters() {
+    assert!(identify("").is_empty());
+    assert!(identify("12 34, 56!\n").is_empty());
+    assert!(!identify("1a").is_empty());
+}
@@ -0,0 +1,193 @@
+//! Key files shared by the block cipher binaries.
+//!
+//! A key file is a few lines of text:
+//!
+//! ```text
+//! impl-encrypt key file
+//! version: 1
+//! algorithm: AES-128
+//! bits: 128
+//! key: 2b7e151628aed2a6abf7158809cf4f3c
+//! check: 7df76b
+//! ```
+//!
+//! `bits` is the length of `key` and `check` is the key check value: the first three bytes of the
+//! encryption of the all-zero block, which tells a wrong or damaged key apart without revealing it.
+//! Files are created readable and writable by their owner only, and never overwrite another file.
+
+use std::convert::TryInto;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+#[cfg(test)]
+mod test;
+
+const MAGIC: &str = "impl-encrypt key file";
+
+pub const VERSION: u32 = 1;
+
+/// Bytes of the key check value.
+pub const CHECK_VALUE_BYTES: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFile {
+    pub algorithm: String,
+    pub key: Vec<u8>,
+    pub check_value: [u8; CHECK_VALUE_BYTES],
+}
+
+#[derive(Debug)]
+pub enum KeyFileError {
+    Io(io::Error),
+    NotAKeyFile,
+    UnsupportedVersion(String),
+    MissingField(&'static str),
+    InvalidField(&'static str),
+    SizeMismatch { bits: usize, key_bits: usize },
+    WrongAlgorithm { expected: String, found: String },
+    CheckValueMismatch,
+}
+
+impl fmt::Display for KeyFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyFileError::Io(e) => write!(f, "{}", e),
+            KeyFileError::NotAKeyFile => write!(f, "not a key file"),
+            KeyFileError::UnsupportedVersion(version) => write!(f, "unsupported key file version {}", version),
+            KeyFileError::MissingField(field) => write!(f, "field '{}' is missing", field),
+            KeyFileError::InvalidField(field) => write!(f, "field '{}' is invalid", field),
+            KeyFileError::SizeMismatch { bits, key_bits } => write!(f, "key has {} bits but the file says {}", key_bits, bits),
+            KeyFileError::WrongAlgorithm { expected, found } => write!(f, "key file holds a {} key, expected {}", found, expected),
+            KeyFileError::CheckValueMismatch => write!(f, "key check value does not match, the key is damaged"),
+        }
+    }
+}
+
+impl std::error::Error for KeyFileError {}
+
+impl From<io::Error> for KeyFileError {
+    fn from(e: io::Error) -> KeyFileError {
+        KeyFileError::Io(e)
+    }
+}
+
+impl KeyFile {
+    /// `encrypted_zero` is the encryption of the all-zero block under `key`.
+    pub fn new(algorithm: &str, key: &[u8], encrypted_zero: &[u8]) -> KeyFile {
+        KeyFile { algorithm: algorithm.to_string(), key: key.to_vec(), check_value: check_value(encrypted_zero) }
+    }
+
+    pub fn parse(text: &str) -> Result<KeyFile, KeyFileError> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some(MAGIC) {
+            return Err(KeyFileError::NotAKeyFile);
+        }
+        let mut version = None;
+        let mut algorithm = None;
+        let mut bits = None;
+        let mut key = None;
+        let mut check = None;
+        for line in lines {
+            let (name, value) = line.split_once(':').ok_or(KeyFileError::NotAKeyFile)?;
+            let value = value.trim();
+            match name.trim() {
+                "version" => version = Some(value),
+                "algorithm" => algorithm = Some(value),
+                "bits" => bits = Some(value),
+                "key" => key = Some(value),
+                "check" => check = Some(value),
+                _ => {}
+            }
+        }
+        let version = version.ok_or(KeyFileError::MissingField("version"))?;
+        if version.parse() != Ok(VERSION) {
+            return Err(KeyFileError::UnsupportedVersion(version.to_string()));
+        }
+        let algorithm = algorithm.filter(|a| !a.is_empty()).ok_or(KeyFileError::MissingField("algorithm"))?;
+        let bits = bits.ok_or(KeyFileError::MissingField("bits"))?.parse().map_err(|_| KeyFileError::InvalidField("bits"))?;
+        let key = from_hex(key.ok_or(KeyFileError::MissingField("key"))?).ok_or(KeyFileError::InvalidField("key"))?;
+        if key.len() * 8 != bits {
+            return Err(KeyFileError::SizeMismatch { bits, key_bits: key.len() * 8 });
+        }
+        let check_value = from_hex(check.ok_or(KeyFileError::MissingField("check"))?)
+            .and_then(|check| check.try_into().ok())
+            .ok_or(KeyFileError::InvalidField("check"))?;
+        Ok(KeyFile { algorithm: algorithm.to_string(), key, check_value })
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<KeyFile, KeyFileError> {
+        KeyFile::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Creates the file at `path` with permissions 0600 on Unix. Fails if the file exists.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), KeyFileError> {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        file.write_all(self.to_string().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn check_algorithm(&self, algorithm: &str) -> Result<(), KeyFileError> {
+        if self.algorithm != algorithm {
+            return Err(KeyFileError::WrongAlgorithm { expected: algorithm.to_string(), found: self.algorithm.clone() });
+        }
+        Ok(())
+    }
+
+    /// Checks that the key is for `algorithm` and that `encrypted_zero`, the encryption of the
+    /// all-zero block under it, gives the stored check value.
+    pub fn verify(&self, algorithm: &str, encrypted_zero: &[u8]) -> Result<(), KeyFileError> {
+        self.check_algorithm(algorithm)?;
+        if check_value(encrypted_zero) != self.check_value {
+            return Err(KeyFileError::CheckValueMismatch);
+        }
+        Ok(())
+    }
+
+    /// The key in hexadecimal, as the binaries take it on the command line.
+    pub fn key_hex(&self) -> String {
+        to_hex(&self.key)
+    }
+}
+
+impl fmt::Display for KeyFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", MAGIC)?;
+        writeln!(f, "version: {}", VERSION)?;
+        writeln!(f, "algorithm: {}", self.algorithm)?;
+        writeln!(f, "bits: {}", self.key.len() * 8)?;
+        writeln!(f, "key: {}", to_hex(&self.key))?;
+        writeln!(f, "check: {}", to_hex(&self.check_value))
+    }
+}
+
+/// The key check value from the encryption of the all-zero block.
+pub fn check_value(encrypted_zero: &[u8]) -> [u8; CHECK_VALUE_BYTES] {
+    let mut result = [0; CHECK_VALUE_BYTES];
+    result.copy_from_slice(&encrypted_zero[..CHECK_VALUE_BYTES]);
+    result
+}
+
+/// The path of a `@path` key argument, `None` for a key given directly.
+pub fn path_of_argument(argument: &str) -> Option<&str> {
+    argument.strip_prefix('@')
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.is_ascii() || value.len() & 1 != 0 {
+        return None;
+    }
+    (0..value.len()).step_by(2).map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok()).collect()
+}
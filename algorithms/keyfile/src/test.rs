@@ -0,0 +1,62 @@
+use super::*;
+
+const TEXT: &str = "impl-encrypt key file
+version: 1
+algorithm: AES-128
+bits: 128
+key: 2b7e151628aed2a6abf7158809cf4f3c
+check: 7df76b
+";
+
+const KEY: [u8; 16] = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+
+/// AES-128 of the zero block under `KEY`.
+const ENCRYPTED_ZERO: [u8; 16] = [0x7d, 0xf7, 0x6b, 0x0c, 0x1a, 0xb8, 0x99, 0xb3, 0x3e, 0x42, 0xf0, 0x47, 0xb9, 0x1b, 0x54, 0x6f];
+
+#[test]
+fn test_format_parse() {
+    let file = KeyFile::new("AES-128", &KEY, &ENCRYPTED_ZERO);
+    assert_eq!(file.to_string(), TEXT);
+    assert_eq!(KeyFile::parse(TEXT).unwrap(), file);
+    assert_eq!(file.key_hex(), "2b7e151628aed2a6abf7158809cf4f3c");
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(matches!(KeyFile::parse("2b7e151628aed2a6abf7158809cf4f3c"), Err(KeyFileError::NotAKeyFile)));
+    assert!(matches!(KeyFile::parse(&TEXT.replace("version: 1", "version: 2")), Err(KeyFileError::UnsupportedVersion(_))));
+    assert!(matches!(KeyFile::parse(&TEXT.replace("check: 7df76b\n", "")), Err(KeyFileError::MissingField("check"))));
+    assert!(matches!(KeyFile::parse(&TEXT.replace("bits: 128", "bits: 192")), Err(KeyFileError::SizeMismatch { bits: 192, key_bits: 128 })));
+    assert!(matches!(KeyFile::parse(&TEXT.replace("4f3c", "4f3")), Err(KeyFileError::InvalidField("key"))));
+    assert!(matches!(KeyFile::parse(&TEXT.replace("7df76b", "7df76b0c")), Err(KeyFileError::InvalidField("check"))));
+}
+
+#[test]
+fn test_verify() {
+    let file = KeyFile::parse(TEXT).unwrap();
+    assert!(file.verify("AES-128", &ENCRYPTED_ZERO).is_ok());
+    assert!(matches!(file.verify("AES-256", &ENCRYPTED_ZERO), Err(KeyFileError::WrongAlgorithm { .. })));
+    assert!(matches!(file.verify("AES-128", &[0; 16]), Err(KeyFileError::CheckValueMismatch)));
+}
+
+#[test]
+fn test_write_read() {
+    let path = std::env::temp_dir().join(format!("keyfile-test-{}.key", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let file = KeyFile::new("AES-128", &KEY, &ENCRYPTED_ZERO);
+    file.write(&path).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    assert!(matches!(file.write(&path), Err(KeyFileError::Io(_))));
+    assert_eq!(KeyFile::read(&path).unwrap(), file);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_path_of_argument() {
+    assert_eq!(path_of_argument("@key.file"), Some("key.file"));
+    assert_eq!(path_of_argument("2b7e151628aed2a6abf7158809cf4f3c"), None);
+}
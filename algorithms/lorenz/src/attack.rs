@@ -0,0 +1,179 @@
+//! Statistical setting of the chi wheels from ciphertext alone (Tutte's "1+2 break-in").
+//!
+//! Differencing a stream (Δ) XORs each character with the next one. In ΔZ = ΔP ⊕ Δχ ⊕ Δψ' the
+//! extended psi stream ψ' repeats its character whenever the psi wheels stand still, so Δψ' is
+//! mostly dots, and teleprinter plaintext has a biased ΔP as well. Counting, for every pair of
+//! chi start positions, how often ΔZ1 ⊕ ΔZ2 ⊕ Δχ1 ⊕ Δχ2 is a dot therefore shows an excess of dots
+//! at the true setting. The remaining chi wheels are then set one by one against those already set.
+
+use rand::Rng;
+
+use crate::baudot::{encode, FIGURE_SHIFT, LETTER_SHIFT, SPACE};
+use crate::CHI_SIZES;
+
+#[cfg(test)]
+mod test;
+
+/// Result of counting one setting: the wheel start positions tried and the number of dots seen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub positions: Vec<usize>,
+    pub dots: usize,
+    pub total: usize,
+}
+
+impl Candidate {
+    /// Excess of dots over the random expectation in standard deviations, 0 if nothing was counted.
+    pub fn sigma(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let total = self.total as f64;
+        (self.dots as f64 - total / 2.0) / (total.sqrt() / 2.0)
+    }
+}
+
+/// Fewest ciphertext characters the attack accepts: one character has no difference to count.
+pub const MIN_CIPHERTEXT_LENGTH: usize = 2;
+
+#[derive(Debug, PartialEq)]
+pub enum AttackError {
+    /// The ciphertext has this many characters, fewer than [`MIN_CIPHERTEXT_LENGTH`].
+    TooShort(usize),
+}
+
+/// Score of one start position of a wheel set after χ1 and χ2: the sum of squared sigmas of its
+/// runs against every wheel set before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    pub position: usize,
+    pub score: f64,
+}
+
+/// Recovered chi start positions with the best candidates of the 1+2 run and of each later wheel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChiRecovery {
+    pub positions: [usize; 5],
+    pub pair_run: Vec<Candidate>,
+    pub settings: Vec<Vec<Setting>>,
+}
+
+fn impulse(code: u8, index: usize) -> bool {
+    (code >> (4 - index)) & 1 != 0
+}
+
+/// Differenced stream: `result[i] = input[i] ^ input[i + 1]`.
+pub fn delta(input: &[u8]) -> Vec<u8> {
+    input.windows(2).map(|w| w[0] ^ w[1]).collect()
+}
+
+fn delta_wheel(pins: &[bool]) -> Vec<bool> {
+    (0..pins.len()).map(|i| pins[i] ^ pins[(i + 1) % pins.len()]).collect()
+}
+
+/// Counts ΔZa ⊕ ΔZb ⊕ Δχa ⊕ Δχb over every pair of start positions of the two wheels.
+///
+/// `a` and `b` are chi wheel indices (0 for χ1). Candidates are sorted by descending dot count.
+pub fn run_pair(ciphertext: &[u8], a: (usize, &[bool]), b: (usize, &[bool])) -> Vec<Candidate> {
+    let dz: Vec<bool> = delta(ciphertext).iter().map(|&z| impulse(z, a.0) ^ impulse(z, b.0)).collect();
+    let da = delta_wheel(a.1);
+    let db = delta_wheel(b.1);
+    let mut candidates = Vec::with_capacity(da.len() * db.len());
+    for sa in 0..da.len() {
+        for sb in 0..db.len() {
+            let dots = dz.iter()
+                .enumerate()
+                .filter(|&(t, &z)| z == (da[(sa + t) % da.len()] ^ db[(sb + t) % db.len()]))
+                .count();
+            candidates.push(Candidate { positions: vec![sa, sb], dots, total: dz.len() });
+        }
+    }
+    candidates.sort_by(|a, b| b.dots.cmp(&a.dots).then_with(|| a.positions.cmp(&b.positions)));
+    candidates
+}
+
+/// Counts ΔZa ⊕ ΔZb ⊕ Δχa ⊕ Δχb with wheel `a` already set at `a.2`, over every start of wheel `b`.
+///
+/// Depending on the impulses paired, ΔPa ⊕ ΔPb may lean towards crosses instead of dots, so the
+/// candidates are sorted by the size of the deviation from half dots rather than by the dot count.
+pub fn run_single(ciphertext: &[u8], a: (usize, &[bool], usize), b: (usize, &[bool])) -> Vec<Candidate> {
+    let dz: Vec<bool> = delta(ciphertext).iter().map(|&z| impulse(z, a.0) ^ impulse(z, b.0)).collect();
+    let da = delta_wheel(a.1);
+    let db = delta_wheel(b.1);
+    let mut candidates = Vec::with_capacity(db.len());
+    for sb in 0..db.len() {
+        let dots = dz.iter()
+            .enumerate()
+            .filter(|&(t, &z)| z == (da[(a.2 + t) % da.len()] ^ db[(sb + t) % db.len()]))
+            .count();
+        candidates.push(Candidate { positions: vec![sb], dots, total: dz.len() });
+    }
+    candidates.sort_by(|a, b| b.sigma().abs().total_cmp(&a.sigma().abs()).then_with(|| a.positions.cmp(&b.positions)));
+    candidates
+}
+
+/// Recovers the start positions of all five chi wheels from ciphertext, given their pin patterns.
+///
+/// χ1 and χ2 are set together by the 1+2 run. Each of χ3, χ4 and χ5 is then run against every wheel
+/// already set and the position with the largest combined deviation wins, which does not depend on
+/// knowing in advance which impulse pairs of the plaintext are biased, or in which direction.
+/// `keep` is the number of best candidates of each run kept in the report.
+pub fn recover_chi_positions(ciphertext: &[u8], chi: &[&[bool]; 5], keep: usize) -> Result<ChiRecovery, AttackError> {
+    if ciphertext.len() < MIN_CIPHERTEXT_LENGTH {
+        return Err(AttackError::TooShort(ciphertext.len()));
+    }
+    for (pins, size) in chi.iter().zip(CHI_SIZES.iter()) {
+        assert_eq!(pins.len(), *size);
+    }
+    let pair_run = run_pair(ciphertext, (0, chi[0]), (1, chi[1]));
+    let mut positions = [pair_run[0].positions[0], pair_run[0].positions[1], 0, 0, 0];
+    let mut settings = Vec::with_capacity(3);
+    for i in 2..5 {
+        let mut scores: Vec<_> = (0..chi[i].len()).map(|position| Setting { position, score: 0.0 }).collect();
+        for j in 0..i {
+            for candidate in run_single(ciphertext, (j, chi[j], positions[j]), (i, chi[i])) {
+                scores[candidate.positions[0]].score += candidate.sigma().powi(2);
+            }
+        }
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.position.cmp(&b.position)));
+        positions[i] = scores[0].position;
+        settings.push(scores.into_iter().take(keep).collect());
+    }
+    Ok(ChiRecovery { positions, pair_run: pair_run.into_iter().take(keep).collect(), settings })
+}
+
+const VOCABULARY: [&str; 40] = [
+    "AN", "OKH", "VON", "DER", "DIE", "DAS", "UND", "MIT", "FUER", "NACH",
+    "ARMEE", "KORPS", "DIVISION", "REGIMENT", "FEIND", "ANGRIFF", "STELLUNG", "FRONT", "NORD", "SUED",
+    "OST", "WEST", "RAUM", "LAGE", "MELDUNG", "BEFEHL", "TRUPPEN", "PANZER", "VERSORGUNG", "MUNITION",
+    "STAND", "UHR", "HEUTE", "MORGEN", "ABSCHNITT", "VERBAND", "GEHEIM", "KOMMANDO", "ZUG", "LINIE",
+];
+
+/// Generates teleprinter-style traffic as ITA2 codes for offline experiments.
+///
+/// Words are separated by one or two spaces, numbers and full stops are sent between doubled
+/// shift codes the way operators keyed them ("55M88"), which gives ΔP the bias the attack uses.
+pub fn synthetic_plaintext<R: Rng>(rng: &mut R, len: usize) -> Vec<u8> {
+    let mut result = Vec::with_capacity(len + 16);
+    while result.len() < len {
+        let word = VOCABULARY[rng.gen_range(0, VOCABULARY.len())];
+        result.extend(encode(word).unwrap());
+        match rng.gen_range(0, 10) {
+            0 => {
+                result.extend(&[FIGURE_SHIFT, FIGURE_SHIFT]);
+                result.extend(encode(".").unwrap().into_iter().filter(|&c| c != FIGURE_SHIFT));
+                result.extend(&[LETTER_SHIFT, LETTER_SHIFT]);
+            }
+            1 => {
+                result.extend(&[SPACE, FIGURE_SHIFT, FIGURE_SHIFT]);
+                let number = rng.gen_range(1, 1000).to_string();
+                result.extend(encode(&number).unwrap().into_iter().filter(|&c| c != FIGURE_SHIFT));
+                result.extend(&[LETTER_SHIFT, LETTER_SHIFT, SPACE]);
+            }
+            2 | 3 => result.extend(&[SPACE, SPACE]),
+            _ => result.push(SPACE),
+        }
+    }
+    result.truncate(len);
+    result
+}
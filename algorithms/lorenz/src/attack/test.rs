@@ -0,0 +1,59 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::{keygen_with_rng, Limitation, Lorenz};
+
+use super::*;
+
+#[test]
+fn test_delta() {
+    assert_eq!(delta(&[0b00001, 0b00011, 0b00011, 0b10000]), vec![0b00010, 0b00000, 0b10011]);
+    assert_eq!(delta_wheel(&[true, false, false]), vec![true, false, true]);
+}
+
+#[test]
+fn test_synthetic_plaintext() {
+    let mut rng = StdRng::seed_from_u64(10);
+    let plain = synthetic_plaintext(&mut rng, 5000);
+    assert_eq!(plain.len(), 5000);
+    let dp = delta(&plain);
+    let dots = dp.iter().filter(|&&d| !impulse(d, 0) ^ impulse(d, 1)).count();
+    assert!(dots * 100 > dp.len() * 55);
+}
+
+#[test]
+fn test_candidate_sigma() {
+    assert_eq!(Candidate { positions: vec![0], dots: 50, total: 100 }.sigma(), 0.0);
+    assert_eq!(Candidate { positions: vec![0], dots: 60, total: 100 }.sigma(), 2.0);
+    assert_eq!(Candidate { positions: vec![0], dots: 0, total: 0 }.sigma(), 0.0);
+}
+
+#[test]
+fn test_recover_chi_positions_short() {
+    let key = keygen_with_rng(&mut StdRng::seed_from_u64(12));
+    let chi = [key.chi[0].pins(), key.chi[1].pins(), key.chi[2].pins(), key.chi[3].pins(), key.chi[4].pins()];
+    assert_eq!(recover_chi_positions(&[], &chi, 3), Err(AttackError::TooShort(0)));
+    assert_eq!(recover_chi_positions(&[0b10101], &chi, 3), Err(AttackError::TooShort(1)));
+    assert!(recover_chi_positions(&[0b10101, 0b00011], &chi, 3).is_ok());
+}
+
+#[test]
+fn test_recover_chi_positions() {
+    let mut rng = StdRng::seed_from_u64(11);
+    // The limitation makes the psi wheels move more often, so Δψ' is less biased and more text is needed.
+    for &(limitation, len) in &[(Limitation::None, 6000), (Limitation::Chi2, 30000)] {
+        for _ in 0..3 {
+            let key = keygen_with_rng(&mut rng);
+            let plain = synthetic_plaintext(&mut rng, len);
+            let cipher = Lorenz::new(key.clone(), limitation).encrypt(&plain);
+            let chi = [key.chi[0].pins(), key.chi[1].pins(), key.chi[2].pins(), key.chi[3].pins(), key.chi[4].pins()];
+            let recovery = recover_chi_positions(&cipher, &chi, 3).unwrap();
+            let expected: Vec<_> = key.chi.iter().map(|wheel| wheel.position()).collect();
+            assert_eq!(recovery.positions.to_vec(), expected);
+            assert_eq!(recovery.pair_run.len(), 3);
+            assert_eq!(recovery.settings.len(), 3);
+            assert!(recovery.pair_run[0].sigma() > 4.0);
+        }
+    }
+}
+
@@ -0,0 +1,149 @@
+//! ITA2 (Baudot–Murray) 5-bit teleprinter code.
+//!
+//! A code is held in the low 5 bits of a `u8`. Impulse 1 is the most significant bit,
+//! so `0b11000` is the letter `A` (impulses 1 and 2 are marks).
+
+#[cfg(test)]
+mod test;
+
+pub const NULL: u8 = 0b00000;
+pub const CARRIAGE_RETURN: u8 = 0b00010;
+pub const LINE_FEED: u8 = 0b01000;
+pub const SPACE: u8 = 0b00100;
+pub const FIGURE_SHIFT: u8 = 0b11011;
+pub const LETTER_SHIFT: u8 = 0b11111;
+
+const LETTERS: [(char, u8); 26] = [
+    ('A', 0b11000), ('B', 0b10011), ('C', 0b01110), ('D', 0b10010), ('E', 0b10000),
+    ('F', 0b10110), ('G', 0b01011), ('H', 0b00101), ('I', 0b01100), ('J', 0b11010),
+    ('K', 0b11110), ('L', 0b01001), ('M', 0b00111), ('N', 0b00110), ('O', 0b00011),
+    ('P', 0b01101), ('Q', 0b11101), ('R', 0b01010), ('S', 0b10100), ('T', 0b00001),
+    ('U', 0b11100), ('V', 0b01111), ('W', 0b11001), ('X', 0b10111), ('Y', 0b10101),
+    ('Z', 0b10001),
+];
+
+const FIGURES: [(char, u8); 22] = [
+    ('-', 0b11000), ('?', 0b10011), (':', 0b01110), ('3', 0b10000), ('8', 0b01100),
+    ('\u{7}', 0b11010), ('(', 0b11110), (')', 0b01001), ('.', 0b00111), (',', 0b00110),
+    ('9', 0b00011), ('0', 0b01101), ('1', 0b11101), ('4', 0b01010), ('\'', 0b10100),
+    ('5', 0b00001), ('7', 0b11100), ('=', 0b01111), ('2', 0b11001), ('/', 0b10111),
+    ('6', 0b10101), ('+', 0b10001),
+];
+
+/// Characters used at Bletchley Park to write the six codes which are not letters.
+const BLETCHLEY: [(char, u8); 6] = [
+    ('/', NULL), ('9', SPACE), ('3', CARRIAGE_RETURN), ('4', LINE_FEED), ('5', FIGURE_SHIFT), ('8', LETTER_SHIFT),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shift {
+    Letters,
+    Figures,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EncodeError {
+    UnsupportedCharacter(char),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseBletchleyError {
+    InvalidCharacter(char),
+}
+
+fn shift_independent(c: char) -> Option<u8> {
+    match c {
+        ' ' => Some(SPACE),
+        '\r' => Some(CARRIAGE_RETURN),
+        '\n' => Some(LINE_FEED),
+        _ => None
+    }
+}
+
+/// Encodes text into ITA2 codes, inserting shift codes as needed.
+///
+/// The receiving machine is assumed to start in letter shift. Lowercase letters are sent as uppercase.
+pub fn encode(text: &str) -> Result<Vec<u8>, EncodeError> {
+    let mut result = Vec::with_capacity(text.len());
+    let mut shift = Shift::Letters;
+    for c in text.chars() {
+        if let Some(code) = shift_independent(c) {
+            result.push(code);
+            continue;
+        }
+        let upper = c.to_ascii_uppercase();
+        if let Some(&(_, code)) = LETTERS.iter().find(|(l, _)| *l == upper) {
+            if shift != Shift::Letters {
+                result.push(LETTER_SHIFT);
+                shift = Shift::Letters;
+            }
+            result.push(code);
+        } else if let Some(&(_, code)) = FIGURES.iter().find(|(f, _)| *f == c) {
+            if shift != Shift::Figures {
+                result.push(FIGURE_SHIFT);
+                shift = Shift::Figures;
+            }
+            result.push(code);
+        } else {
+            return Err(EncodeError::UnsupportedCharacter(c));
+        }
+    }
+    Ok(result)
+}
+
+/// Decodes ITA2 codes into text, following shift codes. Nulls and figures without a printable
+/// character are dropped.
+pub fn decode(codes: &[u8]) -> String {
+    let mut result = String::with_capacity(codes.len());
+    let mut shift = Shift::Letters;
+    for &code in codes {
+        match code & 0b11111 {
+            NULL => {}
+            SPACE => result.push(' '),
+            CARRIAGE_RETURN => result.push('\r'),
+            LINE_FEED => result.push('\n'),
+            FIGURE_SHIFT => shift = Shift::Figures,
+            LETTER_SHIFT => shift = Shift::Letters,
+            code => {
+                let table: &[(char, u8)] = match shift {
+                    Shift::Letters => &LETTERS,
+                    Shift::Figures => &FIGURES,
+                };
+                if let Some(&(c, _)) = table.iter().find(|(_, v)| *v == code) {
+                    result.push(c);
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Writes a single code in Bletchley Park notation: letters for the letter-shift characters,
+/// `/ 9 3 4 5 8` for null, space, carriage return, line feed, figure shift and letter shift.
+pub fn to_bletchley(code: u8) -> char {
+    let code = code & 0b11111;
+    BLETCHLEY.iter()
+        .chain(LETTERS.iter())
+        .find(|(_, v)| *v == code)
+        .map(|(c, _)| *c)
+        .unwrap()
+}
+
+pub fn to_bletchley_string(codes: &[u8]) -> String {
+    codes.iter().map(|&code| to_bletchley(code)).collect()
+}
+
+pub fn from_bletchley(c: char) -> Option<u8> {
+    let upper = c.to_ascii_uppercase();
+    BLETCHLEY.iter()
+        .chain(LETTERS.iter())
+        .find(|(v, _)| *v == upper)
+        .map(|(_, code)| *code)
+}
+
+pub fn parse_bletchley(s: &str) -> Result<Vec<u8>, ParseBletchleyError> {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| from_bletchley(c).ok_or(ParseBletchleyError::InvalidCharacter(c)))
+        .collect()
+}
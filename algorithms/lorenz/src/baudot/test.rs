@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn test_tables() {
+    let mut letters: Vec<_> = LETTERS.iter().map(|(_, code)| *code).chain(BLETCHLEY.iter().map(|(_, code)| *code)).collect();
+    letters.sort();
+    assert_eq!(letters, (0..32).collect::<Vec<_>>());
+    for (_, code) in FIGURES.iter() {
+        assert!(LETTERS.iter().any(|(_, v)| v == code));
+    }
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(encode("AB"), Ok(vec![0b11000, 0b10011]));
+    assert_eq!(encode("a 1."), Ok(vec![0b11000, SPACE, FIGURE_SHIFT, 0b11101, 0b00111]));
+    assert_eq!(encode("1A"), Ok(vec![FIGURE_SHIFT, 0b11101, LETTER_SHIFT, 0b11000]));
+    assert_eq!(encode("\r\n"), Ok(vec![CARRIAGE_RETURN, LINE_FEED]));
+    assert_eq!(encode("A*"), Err(EncodeError::UnsupportedCharacter('*')));
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(decode(&[0b11000, SPACE, FIGURE_SHIFT, 0b11101, 0b00111, LETTER_SHIFT, 0b00001]), "A 1.T".to_string());
+    assert_eq!(decode(&[NULL, 0b11000, NULL]), "A".to_string());
+    let text = "ANGRIFF UM 0530 (NORD), STELLUNG HALTEN?\r\n";
+    assert_eq!(decode(&encode(text).unwrap()), text.to_string());
+}
+
+#[test]
+fn test_bletchley() {
+    assert_eq!(to_bletchley(NULL), '/');
+    assert_eq!(to_bletchley(SPACE), '9');
+    assert_eq!(to_bletchley(FIGURE_SHIFT), '5');
+    assert_eq!(to_bletchley(LETTER_SHIFT), '8');
+    assert_eq!(to_bletchley(0b11000), 'A');
+    assert_eq!(to_bletchley_string(&encode("A.").unwrap()), "A5M".to_string());
+    assert_eq!(parse_bletchley("a5m 8/"), Ok(vec![0b11000, FIGURE_SHIFT, 0b00111, LETTER_SHIFT, NULL]));
+    assert_eq!(parse_bletchley("A-"), Err(ParseBletchleyError::InvalidCharacter('-')));
+    for code in 0..32 {
+        assert_eq!(from_bletchley(to_bletchley(code)), Some(code));
+    }
+}
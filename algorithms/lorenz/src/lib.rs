@@ -0,0 +1,310 @@
+//! Lorenz SZ40/42 teleprinter cipher attachment ("Tunny").
+//!
+//! Each 5-bit ITA2 character is added (XOR) to one chi character and one psi character.
+//! The five chi wheels step with every character, the five psi wheels step together only when
+//! the motor wheels (and the limitation, on later models) allow it.
+
+use std::convert::TryInto;
+
+use rand::Rng;
+
+pub mod attack;
+pub mod baudot;
+
+#[cfg(test)]
+mod test;
+
+pub const CHI_SIZES: [usize; 5] = [41, 31, 29, 26, 23];
+pub const PSI_SIZES: [usize; 5] = [43, 47, 51, 53, 59];
+pub const MU61_SIZE: usize = 61;
+pub const MU37_SIZE: usize = 37;
+
+/// Number of wheels in the order used by [`Key`]'s string form: χ1–χ5, ψ1–ψ5, μ61, μ37.
+pub const WHEEL_COUNT: usize = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wheel {
+    pins: Vec<bool>,
+    position: usize,
+}
+
+impl Wheel {
+    pub fn new(pins: Vec<bool>, position: usize) -> Wheel {
+        assert!(!pins.is_empty());
+        let position = position % pins.len();
+        Wheel { pins, position }
+    }
+
+    pub fn pins(&self) -> &[bool] {
+        &self.pins
+    }
+
+    pub fn len(&self) -> usize {
+        self.pins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pins.is_empty()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position % self.pins.len();
+    }
+
+    pub fn current(&self) -> bool {
+        self.pins[self.position]
+    }
+
+    /// Pin value `offset` steps ahead of the current position.
+    pub fn at(&self, offset: usize) -> bool {
+        self.pins[(self.position + offset) % self.pins.len()]
+    }
+
+    pub fn step(&mut self) {
+        self.position = (self.position + 1) % self.pins.len();
+    }
+}
+
+/// Additional condition on psi wheel movement fitted to the SZ42 models.
+///
+/// When a limitation is fitted the psi wheels stand still only if the basic motor (μ37) is a dot
+/// and the limitation is a cross. Without limitation the psi wheels follow the basic motor alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limitation {
+    /// SZ40: no limitation.
+    None,
+    /// SZ42A: χ2 one back.
+    Chi2,
+    /// SZ42B: χ2 ⊕ ψ1 one back.
+    Chi2Psi1,
+    /// SZ42B with the "P5" switch: χ2 ⊕ ψ1 one back ⊕ plaintext impulse 5 two back.
+    Chi2Psi1P5,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseKeyError {
+    WheelCount(usize),
+    WheelLength { wheel: usize, expected: usize, actual: usize },
+    InvalidPin(char),
+    InvalidPosition(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseLimitationError {
+    UnknownLimitation(String),
+}
+
+/// Pin patterns and start positions of all twelve wheels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key {
+    pub chi: [Wheel; 5],
+    pub psi: [Wheel; 5],
+    pub mu61: Wheel,
+    pub mu37: Wheel,
+}
+
+fn wheel_sizes() -> [usize; WHEEL_COUNT] {
+    let mut sizes = [0; WHEEL_COUNT];
+    sizes[..5].copy_from_slice(&CHI_SIZES);
+    sizes[5..10].copy_from_slice(&PSI_SIZES);
+    sizes[10] = MU61_SIZE;
+    sizes[11] = MU37_SIZE;
+    sizes
+}
+
+impl Key {
+    fn wheels(&self) -> impl Iterator<Item=&Wheel> {
+        self.chi.iter().chain(self.psi.iter()).chain(std::iter::once(&self.mu61)).chain(std::iter::once(&self.mu37))
+    }
+
+    fn from_wheels(wheels: Vec<Wheel>) -> Key {
+        let mut wheels = wheels.into_iter();
+        let chi: Vec<_> = wheels.by_ref().take(5).collect();
+        let psi: Vec<_> = wheels.by_ref().take(5).collect();
+        let mu61 = wheels.next().unwrap();
+        let mu37 = wheels.next().unwrap();
+        Key { chi: chi.try_into().unwrap(), psi: psi.try_into().unwrap(), mu61, mu37 }
+    }
+
+    /// Parses the form written by [`Key::to_string`]: one `pattern@start` per wheel separated by
+    /// whitespace, where the pattern uses `x` for a cross (active pin) and `.` for a dot.
+    pub fn parse(s: &str) -> Result<Key, ParseKeyError> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        if parts.len() != WHEEL_COUNT {
+            return Err(ParseKeyError::WheelCount(parts.len()));
+        }
+        let mut wheels = Vec::with_capacity(WHEEL_COUNT);
+        for (i, (part, size)) in parts.iter().zip(wheel_sizes().iter()).enumerate() {
+            let (pattern, position) = match part.find('@') {
+                Some(index) => (&part[..index], &part[index + 1..]),
+                None => (*part, "0"),
+            };
+            let pins = pattern.chars()
+                .map(|c| match c {
+                    'x' | 'X' => Ok(true),
+                    '.' => Ok(false),
+                    c => Err(ParseKeyError::InvalidPin(c)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if pins.len() != *size {
+                return Err(ParseKeyError::WheelLength { wheel: i, expected: *size, actual: pins.len() });
+            }
+            let position = position.parse::<usize>().map_err(|_| ParseKeyError::InvalidPosition(position.to_string()))?;
+            if position >= *size {
+                return Err(ParseKeyError::InvalidPosition(position.to_string()));
+            }
+            wheels.push(Wheel::new(pins, position));
+        }
+        Ok(Key::from_wheels(wheels))
+    }
+
+    pub fn set_positions(&mut self, positions: &[usize; WHEEL_COUNT]) {
+        let wheels = self.chi.iter_mut()
+            .chain(self.psi.iter_mut())
+            .chain(std::iter::once(&mut self.mu61))
+            .chain(std::iter::once(&mut self.mu37));
+        for (wheel, &position) in wheels.zip(positions.iter()) {
+            wheel.set_position(position);
+        }
+    }
+
+    pub fn positions(&self) -> [usize; WHEEL_COUNT] {
+        let mut result = [0; WHEEL_COUNT];
+        for (result, wheel) in result.iter_mut().zip(self.wheels()) {
+            *result = wheel.position();
+        }
+        result
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, wheel) in self.wheels().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            for &pin in wheel.pins() {
+                write!(f, "{}", if pin { 'x' } else { '.' })?;
+            }
+            write!(f, "@{}", wheel.position())?;
+        }
+        Ok(())
+    }
+}
+
+/// Generates random pin patterns and start positions.
+///
+/// Chi patterns never have more than four equal pins in a row, which keeps Δχ close to half crosses.
+pub fn keygen() -> Key {
+    let mut rng = rand::thread_rng();
+    keygen_with_rng(&mut rng)
+}
+
+pub fn keygen_with_rng<R: Rng>(rng: &mut R) -> Key {
+    let sizes = wheel_sizes();
+    let mut wheels = Vec::with_capacity(WHEEL_COUNT);
+    for (i, &size) in sizes.iter().enumerate() {
+        let pins = loop {
+            let pins: Vec<bool> = (0..size).map(|_| rng.gen()).collect();
+            let ok = i >= 5 || (0..size).all(|start| (0..5).any(|j| pins[(start + j) % size] != pins[start]));
+            if ok { break pins; }
+        };
+        wheels.push(Wheel::new(pins, rng.gen_range(0, size)));
+    }
+    Key::from_wheels(wheels)
+}
+
+pub fn parse_limitation(s: &str) -> Result<Limitation, ParseLimitationError> {
+    match s {
+        "none" => Ok(Limitation::None),
+        "chi2" => Ok(Limitation::Chi2),
+        "chi2psi1" => Ok(Limitation::Chi2Psi1),
+        "chi2psi1p5" => Ok(Limitation::Chi2Psi1P5),
+        _ => Err(ParseLimitationError::UnknownLimitation(s.to_string())),
+    }
+}
+
+/// A running machine. Encrypting or decrypting advances the wheels, so a fresh machine has to be
+/// built from the key for every message.
+#[derive(Debug, Clone)]
+pub struct Lorenz {
+    key: Key,
+    limitation: Limitation,
+    p5_history: [bool; 2],
+}
+
+fn chi_character(key: &Key) -> u8 {
+    key.chi.iter().fold(0, |acc, wheel| acc << 1 | wheel.current() as u8)
+}
+
+fn psi_character(key: &Key) -> u8 {
+    key.psi.iter().fold(0, |acc, wheel| acc << 1 | wheel.current() as u8)
+}
+
+impl Lorenz {
+    pub fn new(key: Key, limitation: Limitation) -> Lorenz {
+        Lorenz { key, limitation, p5_history: [false; 2] }
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    /// Key character (χ ⊕ ψ) at the current position.
+    pub fn key_character(&self) -> u8 {
+        chi_character(&self.key) ^ psi_character(&self.key)
+    }
+
+    /// Advances the wheels by one character. `plain` is the plaintext character just sent, which
+    /// only matters for the P5 limitation.
+    pub fn step(&mut self, plain: u8) {
+        let limitation = match self.limitation {
+            Limitation::None => true,
+            Limitation::Chi2 => self.key.chi[1].current(),
+            Limitation::Chi2Psi1 => self.key.chi[1].current() ^ self.key.psi[0].current(),
+            // p5_history is [p(i - 1), p(i)] after character i, and this decides the motion before
+            // character i + 1, so two back is the later entry.
+            Limitation::Chi2Psi1P5 => self.key.chi[1].current() ^ self.key.psi[0].current() ^ self.p5_history[1],
+        };
+        let basic_motor = self.key.mu37.current();
+        let total_motor = basic_motor || !limitation;
+
+        for wheel in self.key.chi.iter_mut() {
+            wheel.step();
+        }
+        if total_motor {
+            for wheel in self.key.psi.iter_mut() {
+                wheel.step();
+            }
+        }
+        if self.key.mu61.current() {
+            self.key.mu37.step();
+        }
+        self.key.mu61.step();
+        self.p5_history = [self.p5_history[1], plain & 1 != 0];
+    }
+
+    pub fn encrypt(&mut self, input: &[u8]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(input.len());
+        for &p in input {
+            let p = p & 0b11111;
+            result.push(p ^ self.key_character());
+            self.step(p);
+        }
+        result
+    }
+
+    pub fn decrypt(&mut self, input: &[u8]) -> Vec<u8> {
+        let mut result = Vec::with_capacity(input.len());
+        for &z in input {
+            let p = (z & 0b11111) ^ self.key_character();
+            result.push(p);
+            self.step(p);
+        }
+        result
+    }
+}
@@ -0,0 +1,172 @@
+use std::io::Read;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rand::thread_rng;
+
+use lorenz::attack::{recover_chi_positions, synthetic_plaintext};
+use lorenz::baudot::{decode, encode, parse_bletchley, to_bletchley_string};
+use lorenz::{keygen, parse_limitation, Key, Limitation, Lorenz};
+
+fn main() {
+    let limitation_arg = Arg::with_name("limitation")
+        .short("l")
+        .long("limitation")
+        .help("limitation on psi wheel movement(none, chi2, chi2psi1 or chi2psi1p5)")
+        .takes_value(true)
+        .default_value("none");
+    let matches = App::new("lorenz")
+        .about("Lorenz SZ40/42 cipher")
+        .subcommand(SubCommand::with_name("keygen")
+            .about("generate new wheel patterns and start positions by random"))
+        .subcommand(SubCommand::with_name("attack")
+            .about("set the chi wheels of a ciphertext(in Bletchley notation) by counting delta-chi")
+            .arg(Arg::with_name("key")
+                .short("k")
+                .long("key")
+                .help("Key whose chi wheel patterns are known(start positions are ignored)")
+                .takes_value(true))
+            .arg(Arg::with_name("synthetic")
+                .short("s")
+                .long("synthetic")
+                .help("instead of reading ciphertext, encrypt this many characters of synthetic traffic and attack it")
+                .takes_value(true))
+            .arg(limitation_arg.clone())
+            .arg(Arg::with_name("input")
+                .help("ciphertext to attack")))
+        .arg(Arg::with_name("decrypt")
+            .short("d")
+            .long("decrypt")
+            .help("flag to decrypt"))
+        .arg(Arg::with_name("key")
+            .short("k")
+            .long("key")
+            .help("Key for encrypt or decrypt")
+            .takes_value(true))
+        .arg(limitation_arg)
+        .arg(Arg::with_name("input")
+            .help("input value to encrypt or decrypt(ciphertext is written in Bletchley notation)"))
+        .get_matches();
+    if matches.subcommand_matches("keygen").is_some() {
+        println!("generated key: {}", keygen());
+    } else if let Some(matches) = matches.subcommand_matches("attack") {
+        attack(matches);
+    } else {
+        let (key, limitation) = match parse_key_and_limitation(&matches) {
+            Some(value) => value,
+            None => return,
+        };
+        let input = read_input(&matches);
+        let mut lorenz = Lorenz::new(key, limitation);
+        if matches.is_present("decrypt") {
+            match parse_bletchley(&input) {
+                Ok(input) => println!("{}", decode(&lorenz.decrypt(&input))),
+                Err(e) => eprintln!("error in parsing input: {:?}", e),
+            }
+        } else {
+            match encode(&input) {
+                Ok(input) => println!("{}", to_bletchley_string(&lorenz.encrypt(&input))),
+                Err(e) => eprintln!("error in parsing input: {:?}", e),
+            }
+        }
+    }
+}
+
+fn read_input(matches: &ArgMatches) -> String {
+    matches.value_of("input").map(str::to_string).unwrap_or_else(|| {
+        let mut s = String::new();
+        std::io::stdin().read_to_string(&mut s).expect("failed to read standard input");
+        s
+    })
+}
+
+fn parse_key_and_limitation(matches: &ArgMatches) -> Option<(Key, Limitation)> {
+    let key = match matches.value_of("key").map(Key::parse) {
+        Some(Ok(key)) => key,
+        Some(Err(e)) => {
+            eprintln!("error in parsing key: {:?}", e);
+            return None;
+        }
+        None => {
+            eprintln!("argument 'key' is required");
+            return None;
+        }
+    };
+    match parse_limitation(matches.value_of("limitation").unwrap()) {
+        Ok(limitation) => Some((key, limitation)),
+        Err(e) => {
+            eprintln!("error in parsing limitation: {:?}", e);
+            None
+        }
+    }
+}
+
+fn attack(matches: &ArgMatches) {
+    let (key, ciphertext) = if let Some(length) = matches.value_of("synthetic") {
+        let length = match length.parse() {
+            Ok(length) => length,
+            Err(_) => {
+                eprintln!("argument 'synthetic' should be number");
+                return;
+            }
+        };
+        let limitation = match parse_limitation(matches.value_of("limitation").unwrap()) {
+            Ok(limitation) => limitation,
+            Err(e) => {
+                eprintln!("error in parsing limitation: {:?}", e);
+                return;
+            }
+        };
+        let key = match matches.value_of("key").map(Key::parse) {
+            Some(Ok(key)) => key,
+            Some(Err(e)) => {
+                eprintln!("error in parsing key: {:?}", e);
+                return;
+            }
+            None => keygen(),
+        };
+        let plaintext = synthetic_plaintext(&mut thread_rng(), length);
+        let ciphertext = Lorenz::new(key.clone(), limitation).encrypt(&plaintext);
+        let positions: Vec<_> = key.chi.iter().map(|wheel| wheel.position().to_string()).collect();
+        println!("true chi positions: {}", positions.join(" "));
+        (key, ciphertext)
+    } else {
+        let key = match matches.value_of("key").map(Key::parse) {
+            Some(Ok(key)) => key,
+            Some(Err(e)) => {
+                eprintln!("error in parsing key: {:?}", e);
+                return;
+            }
+            None => {
+                eprintln!("argument 'key' is required");
+                return;
+            }
+        };
+        match parse_bletchley(&read_input(matches)) {
+            Ok(ciphertext) => (key, ciphertext),
+            Err(e) => {
+                eprintln!("error in parsing input: {:?}", e);
+                return;
+            }
+        }
+    };
+    let chi = [key.chi[0].pins(), key.chi[1].pins(), key.chi[2].pins(), key.chi[3].pins(), key.chi[4].pins()];
+    let recovery = match recover_chi_positions(&ciphertext, &chi, 5) {
+        Ok(recovery) => recovery,
+        Err(e) => {
+            eprintln!("error in attacking: {:?}", e);
+            return;
+        }
+    };
+    println!("1+2 run over {} characters:", ciphertext.len().saturating_sub(1));
+    for candidate in &recovery.pair_run {
+        println!("  chi1={:2} chi2={:2} dots={} sigma={:.2}", candidate.positions[0], candidate.positions[1], candidate.dots, candidate.sigma());
+    }
+    for (i, settings) in recovery.settings.iter().enumerate() {
+        println!("chi{} runs:", i + 3);
+        for setting in settings {
+            println!("  chi{}={:2} score={:.2}", i + 3, setting.position, setting.score);
+        }
+    }
+    let positions: Vec<_> = recovery.positions.iter().map(usize::to_string).collect();
+    println!("recovered chi positions: {}", positions.join(" "));
+}
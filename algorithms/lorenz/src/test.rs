@@ -0,0 +1,154 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::*;
+
+fn pattern(s: &str) -> Vec<bool> {
+    s.chars().map(|c| c == 'x').collect()
+}
+
+fn constant_key(value: bool) -> Key {
+    let wheels = wheel_sizes().iter().map(|&size| Wheel::new(vec![value; size], 0)).collect();
+    Key::from_wheels(wheels)
+}
+
+#[test]
+fn test_wheel() {
+    let mut wheel = Wheel::new(pattern("x..x"), 5);
+    assert_eq!(wheel.position(), 1);
+    assert!(!wheel.current());
+    assert!(wheel.at(2));
+    wheel.step();
+    wheel.step();
+    assert!(wheel.current());
+    wheel.step();
+    assert_eq!(wheel.position(), 0);
+    assert!(wheel.current());
+}
+
+#[test]
+fn test_key_parse() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let key = keygen_with_rng(&mut rng);
+    assert_eq!(Key::parse(&key.to_string()), Ok(key.clone()));
+    assert_eq!(key.positions().len(), WHEEL_COUNT);
+
+    let string = key.to_string();
+    let mut parts: Vec<_> = string.split_whitespace().collect();
+    assert_eq!(Key::parse(&parts[..11].join(" ")), Err(ParseKeyError::WheelCount(11)));
+    parts[0] = "x.x@0";
+    assert_eq!(Key::parse(&parts.join(" ")), Err(ParseKeyError::WheelLength { wheel: 0, expected: 41, actual: 3 }));
+    parts[0] = "x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x@41";
+    assert_eq!(Key::parse(&parts.join(" ")), Err(ParseKeyError::InvalidPosition("41".to_string())));
+    parts[0] = "x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.x.o@0";
+    assert_eq!(Key::parse(&parts.join(" ")), Err(ParseKeyError::InvalidPin('o')));
+}
+
+#[test]
+fn test_keygen() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..20 {
+        let key = keygen_with_rng(&mut rng);
+        for wheel in &key.chi {
+            let pins = wheel.pins();
+            for start in 0..pins.len() {
+                assert!((0..5).any(|j| pins[(start + j) % pins.len()] != pins[start]));
+            }
+        }
+    }
+    assert_ne!(keygen(), keygen());
+}
+
+#[test]
+fn test_parse_limitation() {
+    assert_eq!(parse_limitation("none"), Ok(Limitation::None));
+    assert_eq!(parse_limitation("chi2"), Ok(Limitation::Chi2));
+    assert_eq!(parse_limitation("chi2psi1"), Ok(Limitation::Chi2Psi1));
+    assert_eq!(parse_limitation("chi2psi1p5"), Ok(Limitation::Chi2Psi1P5));
+    assert!(parse_limitation("psi1").is_err());
+}
+
+#[test]
+fn test_chi_steps_every_character() {
+    let mut key = constant_key(false);
+    key.chi[0] = Wheel::new(pattern("x.xx.........................x.........."), 0);
+    key.chi[4] = Wheel::new(pattern(".x....................x"), 0);
+    let mut lorenz = Lorenz::new(key, Limitation::None);
+    let result = lorenz.encrypt(&[0; 24]);
+    assert_eq!(&result[..4], &[0b10000, 0b00001, 0b10000, 0b10000]);
+    assert_eq!(result[22], 0b00001);
+    assert_eq!(result[23], 0b00000);
+}
+
+#[test]
+fn test_motor() {
+    // μ61 all crosses makes μ37 step every time, so the psi wheels follow the μ37 pattern.
+    let mut key = constant_key(false);
+    key.mu61 = Wheel::new(vec![true; MU61_SIZE], 0);
+    key.mu37 = Wheel::new(pattern(".x..................................."), 0);
+    key.psi[0] = Wheel::new(pattern("x.x........................................"), 0);
+    let mut lorenz = Lorenz::new(key.clone(), Limitation::None);
+    assert_eq!(lorenz.encrypt(&[0; 5]), vec![0b10000, 0b10000, 0b00000, 0b00000, 0b00000]);
+
+    // With μ61 all dots μ37 never leaves its first pin, a cross here, so the psi wheels always move.
+    key.mu61 = Wheel::new(vec![false; MU61_SIZE], 0);
+    key.mu37 = Wheel::new(pattern("x...................................."), 0);
+    let mut lorenz = Lorenz::new(key, Limitation::None);
+    assert_eq!(lorenz.encrypt(&[0; 4]), vec![0b10000, 0b00000, 0b10000, 0b00000]);
+}
+
+#[test]
+fn test_limitation() {
+    // μ37 is all dots, so the psi wheels only move when the limitation is a dot.
+    let mut key = constant_key(false);
+    key.psi[0] = Wheel::new(pattern("x.x........................................"), 0);
+    key.chi[1] = Wheel::new(pattern(".xx............................"), 0);
+
+    let mut lorenz = Lorenz::new(key.clone(), Limitation::None);
+    let result = lorenz.encrypt(&[0; 4]);
+    assert_eq!(result.iter().map(|c| c & 0b10000).collect::<Vec<_>>(), vec![0b10000; 4]);
+
+    // χ2 dot at the first character lets the psis move once, then χ2 crosses hold them for two characters.
+    let mut lorenz = Lorenz::new(key.clone(), Limitation::Chi2);
+    let result = lorenz.encrypt(&[0; 5]);
+    assert_eq!(result.iter().map(|c| c & 0b10000).collect::<Vec<_>>(), vec![0b10000, 0, 0, 0, 0b10000]);
+
+    // ψ1 cross cancels the first χ2 dot.
+    let mut lorenz = Lorenz::new(key.clone(), Limitation::Chi2Psi1);
+    let result = lorenz.encrypt(&[0; 3]);
+    assert_eq!(result.iter().map(|c| c & 0b10000).collect::<Vec<_>>(), vec![0b10000, 0b10000, 0]);
+}
+
+#[test]
+fn test_limitation_p5() {
+    // χ2, ψ1 and μ37 are all dots, so the psi wheels move unless impulse 5 of the plaintext two
+    // characters back is a cross. ψ2 shows where they are.
+    let mut key = constant_key(false);
+    let mut pins = vec![false; key.psi[1].pins().len()];
+    pins[1] = true;
+    pins[3] = true;
+    key.psi[1] = Wheel::new(pins, 0);
+    let psi2 = |plain: &[u8]| {
+        let mut lorenz = Lorenz::new(key.clone(), Limitation::Chi2Psi1P5);
+        let result = lorenz.encrypt(plain);
+        result.iter().zip(plain.iter()).map(|(z, p)| (z ^ p) & 0b01000).collect::<Vec<_>>()
+    };
+    assert_eq!(psi2(&[0; 5]), vec![0, 0b01000, 0, 0b01000, 0]);
+    // Impulse 5 at character 0 holds the psis before character 2 only.
+    assert_eq!(psi2(&[0b00001, 0, 0, 0, 0]), vec![0, 0b01000, 0b01000, 0, 0b01000]);
+    assert_eq!(psi2(&[0, 0, 0b00001, 0, 0]), vec![0, 0b01000, 0, 0b01000, 0b01000]);
+}
+
+#[test]
+fn test_encrypt_decrypt() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for &limitation in &[Limitation::None, Limitation::Chi2, Limitation::Chi2Psi1, Limitation::Chi2Psi1P5] {
+        for _ in 0..10 {
+            let key = keygen_with_rng(&mut rng);
+            let input: Vec<u8> = (0..rng.gen_range(1, 500)).map(|_| rng.gen_range(0, 32)).collect();
+            let encrypted = Lorenz::new(key.clone(), limitation).encrypt(&input);
+            assert_ne!(encrypted, input);
+            assert_eq!(Lorenz::new(key, limitation).decrypt(&encrypted), input);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Scytale cipher.
+//!
+//! The input is written row by row onto a strip of `key` columns and read column by column.
+//! When the input does not fill the last row, it is padded with characters picked at random from
+//! the input itself, so the output length is always a multiple of the key.
+//!
+//! ```
+//! use scytale::{decrypt, encrypt, Key};
+//!
+//! let key = Key::new(3).unwrap();
+//! let ciphertext = encrypt("attackatdawn", key).unwrap();
+//! assert_eq!(ciphertext, "acdtkatawatn");
+//! assert_eq!(decrypt(&ciphertext, key).unwrap(), "attackatdawn");
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The key is not a positive number.
+    KeyValueError(&'static str),
+    /// The output buffer given to one of the `_into` functions is shorter than [`output_len`].
+    BufferTooSmall { required: usize, actual: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::KeyValueError(message) => write!(f, "invalid key: {}", message),
+            Error::BufferTooSmall { required, actual } => write!(f, "output buffer too small: {} elements required, {} given", required, actual),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Number of columns of the strip, never zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key(usize);
+
+impl Key {
+    pub fn new(columns: usize) -> Result<Key, Error> {
+        if columns == 0 {
+            return Err(Error::KeyValueError("key should be positive"));
+        }
+        Ok(Key(columns))
+    }
+
+    pub fn columns(self) -> usize {
+        self.0
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Key, Error> {
+        let columns = s.trim().parse().map_err(|_| Error::KeyValueError("key should be number"))?;
+        Key::new(columns)
+    }
+}
+
+/// Length of the output for an input of `input_len` elements: `input_len` rounded up to a
+/// multiple of the key.
+pub fn output_len(input_len: usize, key: Key) -> usize {
+    input_len.div_ceil(key.0) * key.0
+}
+
+fn read_into<T: Copy, F: Fn(usize) -> usize>(input: &[T], key: Key, output: &mut [T], index: F) -> Result<usize, Error> {
+    let len = output_len(input.len(), key);
+    if output.len() < len {
+        return Err(Error::BufferTooSmall { required: len, actual: output.len() });
+    }
+    let mut rng = rand::thread_rng();
+    for (i, o) in output[..len].iter_mut().enumerate() {
+        *o = input.get(index(i)).copied().unwrap_or_else(|| input[rng.gen_range(0, input.len())]);
+    }
+    Ok(len)
+}
+
+/// Encrypts `input` into the front of `output` without allocating.
+///
+/// Returns the number of elements written, see [`output_len`].
+pub fn encrypt_into<T: Copy>(input: &[T], key: Key, output: &mut [T]) -> Result<usize, Error> {
+    let rows = output_len(input.len(), key) / key.0;
+    read_into(input, key, output, |i| rows * (i % key.0) + i / key.0)
+}
+
+/// Decrypts `input` into the front of `output` without allocating.
+///
+/// Returns the number of elements written, see [`output_len`].
+pub fn decrypt_into<T: Copy>(input: &[T], key: Key, output: &mut [T]) -> Result<usize, Error> {
+    let rows = output_len(input.len(), key) / key.0;
+    read_into(input, key, output, |i| i % rows * key.0 + i / rows)
+}
+
+pub fn encrypt(input: &str, key: Key) -> Result<String, Error> {
+    let input: Vec<_> = input.chars().collect();
+    let mut result = vec!['\0'; output_len(input.len(), key)];
+    encrypt_into(&input, key, &mut result)?;
+    Ok(result.into_iter().collect())
+}
+
+pub fn decrypt(input: &str, key: Key) -> Result<String, Error> {
+    let input: Vec<_> = input.chars().collect();
+    let mut result = vec!['\0'; output_len(input.len(), key)];
+    decrypt_into(&input, key, &mut result)?;
+    Ok(result.into_iter().collect())
+}
@@ -1,14 +1,8 @@
 use std::io::Read;
-use std::iter::FromIterator;
 
 use clap::{App, Arg};
-use rand::Rng;
 
-#[cfg(test)]
-mod test;
-
-#[derive(Debug, PartialEq)]
-enum Error {}
+use scytale::{decrypt, encrypt, Key};
 
 fn main() {
     let matches = App::new("scytale")
@@ -18,7 +12,7 @@ fn main() {
             .about("flag to decrypt"))
         .arg(Arg::new("key")
             .short('k')
-            .about("amount of rotate")
+            .about("number of columns")
             .takes_value(true)
             .default_value("3"))
         .arg(Arg::new("input")
@@ -29,47 +23,18 @@ fn main() {
         std::io::stdin().read_to_string(&mut s).expect("failed to read standard input");
         s
     });
-    if let Ok(key) = matches.value_of("key").unwrap().parse() {
-        if matches.is_present("decrypt") {
-            match decrypt(&input, key) {
-                Ok(result) => println!("{}", result),
-                Err(e) => eprintln!("error:{:?}", e),
-            }
-        } else {
-            match encrypt(&input, key) {
+    match matches.value_of("key").unwrap().parse::<Key>() {
+        Ok(key) => {
+            let result = if matches.is_present("decrypt") {
+                decrypt(&input, key)
+            } else {
+                encrypt(&input, key)
+            };
+            match result {
                 Ok(result) => println!("{}", result),
-                Err(e) => eprintln!("error:{:?}", e),
+                Err(e) => eprintln!("error: {}", e),
             }
         }
-    } else {
-        eprintln!("argument 'key' should be number");
-    }
-}
-
-fn encrypt(input: &str, key: usize) -> Result<String, Error> {
-    let vec: Vec<_> = input.chars().collect();
-    let len = (vec.len() + key - 1) / key * key;
-    let mut result_chars = Vec::new();
-    let mut rng = rand::thread_rng();
-    for i in 0..len / key {
-        for j in 0..key {
-            let c = vec.get(len / key * j + i).copied().unwrap_or_else(|| vec[rng.gen_range(0, vec.len())]);
-            result_chars.push(c);
-        }
-    }
-    Ok(String::from_iter(result_chars.iter()))
-}
-
-fn decrypt(input: &str, key: usize) -> Result<String, Error> {
-    let vec: Vec<_> = input.chars().collect();
-    let len = (vec.len() + key - 1) / key * key;
-    let mut result_chars = Vec::new();
-    let mut rng = rand::thread_rng();
-    for i in 0..key {
-        for j in 0..len / key {
-            let c = vec.get(j * key + i).copied().unwrap_or_else(|| vec[rng.gen_range(0, vec.len())]);
-            result_chars.push(c);
-        }
+        Err(e) => eprintln!("error in parsing key: {}", e),
     }
-    Ok(String::from_iter(result_chars.iter()))
 }
@@ -1,15 +1,40 @@
-use std::iter::FromIterator;
+use super::*;
 
-use super::{decrypt, encrypt};
+fn key(columns: usize) -> Key {
+    Key::new(columns).unwrap()
+}
+
+#[test]
+fn test_key() {
+    assert_eq!(Key::new(0), Err(Error::KeyValueError("key should be positive")));
+    assert_eq!("4".parse(), Ok(key(4)));
+    assert_eq!("0".parse::<Key>(), Err(Error::KeyValueError("key should be positive")));
+    assert_eq!("x".parse::<Key>(), Err(Error::KeyValueError("key should be number")));
+    assert_eq!(output_len(26, key(3)), 27);
+    assert_eq!(output_len(26, key(13)), 26);
+    assert_eq!(output_len(0, key(3)), 0);
+}
 
 #[test]
 fn test_encrypt() {
-    assert!(encrypt("abcdefghijklmnopqrstuvwxyz", 3).unwrap().chars().zip("ajsbktcludmvenwfoxgpyhqzir-".chars()).all(|(a, b)| b == '-' || a == b));
-    assert!(encrypt("abcdefghijklmnopqrstuvwxyz", 5).unwrap().chars().zip("agmsybhntzciou-djpv-ekqw-flrx-".chars()).all(|(a, b)| b == '-' || a == b));
+    assert!(encrypt("abcdefghijklmnopqrstuvwxyz", key(3)).unwrap().chars().zip("ajsbktcludmvenwfoxgpyhqzir-".chars()).all(|(a, b)| b == '-' || a == b));
+    assert!(encrypt("abcdefghijklmnopqrstuvwxyz", key(5)).unwrap().chars().zip("agmsybhntzciou-djpv-ekqw-flrx-".chars()).all(|(a, b)| b == '-' || a == b));
 }
 
 #[test]
 fn test_decrypt() {
-    assert_eq!(String::from_iter(decrypt("ajsbktcludmvenwfoxgpyhqzir-", 3).unwrap().chars()), "abcdefghijklmnopqrstuvwxyz-".to_string());
-    assert_eq!(String::from_iter(decrypt("agmsybhntzciou-djpv-ekqw-flrx-", 5).unwrap().chars()), "abcdefghijklmnopqrstuvwxyz----".to_string());
-}
\ No newline at end of file
+    assert_eq!(decrypt("ajsbktcludmvenwfoxgpyhqzir-", key(3)).unwrap(), "abcdefghijklmnopqrstuvwxyz-".to_string());
+    assert_eq!(decrypt("agmsybhntzciou-djpv-ekqw-flrx-", key(5)).unwrap(), "abcdefghijklmnopqrstuvwxyz----".to_string());
+}
+
+#[test]
+fn test_encrypt_decrypt_into() {
+    let input: Vec<u32> = (0..12).collect();
+    let mut encrypted = [0; 12];
+    assert_eq!(encrypt_into(&input, key(4), &mut encrypted), Ok(12));
+    assert_eq!(encrypted, [0, 3, 6, 9, 1, 4, 7, 10, 2, 5, 8, 11]);
+    let mut decrypted = [0; 12];
+    assert_eq!(decrypt_into(&encrypted, key(4), &mut decrypted), Ok(12));
+    assert_eq!(&decrypted[..], &input[..]);
+    assert_eq!(encrypt_into(&input[..10], key(4), &mut decrypted[..11]), Err(Error::BufferTooSmall { required: 12, actual: 11 }));
+}
@@ -0,0 +1,150 @@
+//! Block transposition cipher.
+//!
+//! The key is a permutation of `0..n`: element `i` of every block of `n` moves to position
+//! `key[i]`. A trailing block shorter than `n` is permuted by the key restricted to the values
+//! below its length, so the output is exactly as long as the input.
+//!
+//! ```
+//! use transposition::{decrypt, encrypt, Key};
+//!
+//! let key = Key::new("2 0 3 1").unwrap();
+//! assert_eq!(encrypt("TranspositionCipher", &key).unwrap(), "rnTapssotoiiCpnierh");
+//! assert_eq!(decrypt("rnTapssotoiiCpnierh", &key).unwrap(), "TranspositionCipher");
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The key is not a whitespace separated permutation of `0..n`.
+    InputValueError(&'static str),
+    /// The output buffer given to one of the `_into` functions is shorter than the input.
+    BufferTooSmall { required: usize, actual: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InputValueError(message) => write!(f, "invalid key: {}", message),
+            Error::BufferTooSmall { required, actual } => write!(f, "output buffer too small: {} elements required, {} given", required, actual),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key(Vec<usize>);
+
+impl Key {
+    /// Parses a whitespace separated permutation such as `"2 0 3 1"`.
+    pub fn new(s: &str) -> Result<Key, Error> {
+        let list = s.split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| Error::InputValueError("parse error"))?;
+        Key::from_permutation(list)
+    }
+
+    /// Checks that `list` is a non-empty permutation of `0..list.len()`.
+    pub fn from_permutation(list: Vec<usize>) -> Result<Key, Error> {
+        let len = list.len();
+        let mut set = HashSet::new();
+        if len == 0 || !list.iter().all(|&v| v < len && set.insert(v)) {
+            return Err(Error::InputValueError("value error"));
+        }
+        Ok(Key(list))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self) -> &[usize] {
+        &self.0
+    }
+
+    /// The key used for a trailing block of `len` elements: the values below `len`, in order.
+    pub fn small(&self, len: usize) -> Key {
+        assert!(len <= self.0.len());
+        Key(self.0.iter().filter(|&&v| v < len).copied().collect())
+    }
+
+    pub fn inverse(&self) -> Key {
+        let mut vec = vec![0; self.0.len()];
+        for (i, &x) in self.0.iter().enumerate() {
+            vec[x] = i;
+        }
+        Key(vec)
+    }
+}
+
+impl FromStr for Key {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Key, Error> {
+        Key::new(s)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list: Vec<_> = self.0.iter().map(usize::to_string).collect();
+        write!(f, "{}", list.join(" "))
+    }
+}
+
+fn transpose<T: Copy>(input: &[T], key_block: &[usize], key_mod: &[usize], output: &mut [T]) -> Result<usize, Error> {
+    if output.len() < input.len() {
+        return Err(Error::BufferTooSmall { required: input.len(), actual: output.len() });
+    }
+    let block_count = input.len() / key_block.len();
+    for block in 0..block_count {
+        let offset = block * key_block.len();
+        for (i, &k) in key_block.iter().enumerate() {
+            output[offset + k] = input[offset + i];
+        }
+    }
+    let offset = block_count * key_block.len();
+    for (i, &k) in key_mod.iter().enumerate() {
+        output[offset + k] = input[offset + i];
+    }
+    Ok(input.len())
+}
+
+/// Encrypts `input` into the front of `output` without allocating.
+///
+/// Returns the number of elements written, which is always `input.len()`.
+pub fn encrypt_into<T: Copy>(input: &[T], key: &Key, output: &mut [T]) -> Result<usize, Error> {
+    transpose(input, key.get(), key.small(input.len() % key.len()).get(), output)
+}
+
+/// Decrypts `input` into the front of `output` without allocating.
+///
+/// Returns the number of elements written, which is always `input.len()`.
+pub fn decrypt_into<T: Copy>(input: &[T], key: &Key, output: &mut [T]) -> Result<usize, Error> {
+    transpose(input, key.inverse().get(), key.small(input.len() % key.len()).inverse().get(), output)
+}
+
+pub fn encrypt(input: &str, key: &Key) -> Result<String, Error> {
+    let input: Vec<_> = input.chars().collect();
+    let mut result = vec![' '; input.len()];
+    encrypt_into(&input, key, &mut result)?;
+    Ok(result.into_iter().collect())
+}
+
+pub fn decrypt(input: &str, key: &Key) -> Result<String, Error> {
+    let input: Vec<_> = input.chars().collect();
+    let mut result = vec![' '; input.len()];
+    decrypt_into(&input, key, &mut result)?;
+    Ok(result.into_iter().collect())
+}
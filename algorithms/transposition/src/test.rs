@@ -1,4 +1,4 @@
-use super::{decrypt, encrypt, Key};
+use super::*;
 
 #[test]
 fn test_key() {
@@ -6,6 +6,10 @@ fn test_key() {
     Key::new("").unwrap_err();
     Key::new("0 0 2").unwrap_err();
     Key::new("0 2 3").unwrap_err();
+    Key::new("0 a 1").unwrap_err();
+    assert_eq!("1 0 2".parse(), Ok(Key(vec![1, 0, 2])));
+    assert_eq!(Key::from_permutation(vec![1, 0, 2]), Ok(Key(vec![1, 0, 2])));
+    assert_eq!(Key::new("2 0 3 1").unwrap().to_string(), "2 0 3 1");
 
     assert_eq!(Key::new("2 0 3 1").unwrap().small(3), Key(vec![2, 0, 1]));
     assert_eq!(Key::new("2 0 3 1").unwrap().inverse(), Key(vec![1, 3, 0, 2]));
@@ -19,4 +23,17 @@ fn test_encrypt() {
 #[test]
 fn test_decrypt() {
     assert_eq!(decrypt("rnTapssotoiiCpnierh", &Key::new("2 0 3 1").unwrap()), Ok("TranspositionCipher".to_string()))
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_encrypt_decrypt_into() {
+    let key = Key::new("2 0 3 1").unwrap();
+    let input = [10u8, 11, 12, 13, 14, 15];
+    let mut encrypted = [0; 6];
+    assert_eq!(encrypt_into(&input, &key, &mut encrypted), Ok(6));
+    assert_eq!(encrypted, [11, 13, 10, 12, 14, 15]);
+    let mut decrypted = [0; 6];
+    assert_eq!(decrypt_into(&encrypted, &key, &mut decrypted), Ok(6));
+    assert_eq!(decrypted, input);
+    assert_eq!(encrypt_into(&input, &key, &mut decrypted[..5]), Err(Error::BufferTooSmall { required: 6, actual: 5 }));
+}